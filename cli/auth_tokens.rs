@@ -0,0 +1,186 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Parses the `DENO_AUTH_TOKENS` environment variable, which allows remote
+//! modules to be fetched from hosts that require authentication.
+//!
+//! The value is a semicolon separated list of `token@host` entries for bearer
+//! tokens and `username:password@host` entries for basic auth, e.g.
+//! `abc123@deno.land;user:pass@example.com:8080`.
+
+use reqwest::header::HeaderValue;
+use std::env;
+use std::fmt;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthTokenData {
+  Bearer(String),
+  Basic { username: String, password: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthToken {
+  host: String,
+  token: AuthTokenData,
+}
+
+/// Formats the token as the value of an `Authorization` header.
+impl fmt::Display for AuthToken {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match &self.token {
+      AuthTokenData::Bearer(token) => write!(f, "Bearer {}", token),
+      AuthTokenData::Basic { username, password } => {
+        let credentials = format!("{}:{}", username, password);
+        write!(f, "Basic {}", base64::encode(credentials))
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuthTokens(Vec<AuthToken>);
+
+impl AuthTokens {
+  /// Parses a `DENO_AUTH_TOKENS` style string. Malformed entries are skipped
+  /// with a warning.
+  pub fn new(maybe_tokens_str: Option<String>) -> Self {
+    let mut tokens = Vec::new();
+
+    if let Some(tokens_str) = maybe_tokens_str {
+      for token_str in tokens_str.split(';') {
+        let token_str = token_str.trim();
+        if token_str.is_empty() {
+          continue;
+        }
+        // Tokens and passwords may contain "@", hosts may not.
+        let mut parts = token_str.rsplitn(2, '@');
+        let host = parts.next().unwrap().to_lowercase();
+        let token = match parts.next() {
+          Some(token) if !token.is_empty() && !host.is_empty() => token,
+          _ => {
+            eprintln!("Badly formed auth token discarded.");
+            continue;
+          }
+        };
+        let token = match token.find(':') {
+          Some(index) => AuthTokenData::Basic {
+            username: token[..index].to_string(),
+            password: token[index + 1..].to_string(),
+          },
+          None => AuthTokenData::Bearer(token.to_string()),
+        };
+        let token = AuthToken { host, token };
+        // The token is sent as an `Authorization` header, so it may only
+        // contain visible ASCII characters.
+        if HeaderValue::from_str(&token.to_string()).is_err() {
+          eprintln!("Badly formed auth token discarded.");
+          continue;
+        }
+        tokens.push(token);
+      }
+    }
+
+    Self(tokens)
+  }
+
+  /// Reads the tokens from the `DENO_AUTH_TOKENS` environment variable.
+  pub fn from_env() -> Self {
+    Self::new(env::var("DENO_AUTH_TOKENS").ok())
+  }
+
+  /// Returns the token that should be sent with a request to `url`. A token
+  /// applies to its host and all of its subdomains; if the token specifies a
+  /// port, the port has to match as well.
+  pub fn get(&self, url: &Url) -> Option<AuthToken> {
+    let hostname = url.host_str()?.to_lowercase();
+    let hostname_with_port = match url.port() {
+      Some(port) => format!("{}:{}", hostname, port),
+      None => hostname.clone(),
+    };
+
+    self
+      .0
+      .iter()
+      .find(|t| {
+        let candidate = if t.host.contains(':') {
+          &hostname_with_port
+        } else {
+          &hostname
+        };
+        candidate == &t.host || candidate.ends_with(&format!(".{}", t.host))
+      })
+      .cloned()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_auth_token() {
+    let auth_tokens = AuthTokens::new(Some("abc123@deno.land".to_string()));
+    let fixture = Url::parse("https://deno.land/x/mod.ts").unwrap();
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer abc123"
+    );
+    let fixture = Url::parse("https://www.deno.land/x/mod.ts").unwrap();
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer abc123"
+    );
+    let fixture = Url::parse("http://127.0.0.1:8080/x/mod.ts").unwrap();
+    assert_eq!(auth_tokens.get(&fixture), None);
+    let fixture = Url::parse("https://evildeno.land/x/mod.ts").unwrap();
+    assert_eq!(auth_tokens.get(&fixture), None);
+  }
+
+  #[test]
+  fn test_auth_tokens_multiple() {
+    let auth_tokens = AuthTokens::new(Some(
+      "abc123@deno.land;user:pass@example.com:8080".to_string(),
+    ));
+    let fixture = Url::parse("https://deno.land/x/mod.ts").unwrap();
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer abc123"
+    );
+    let fixture = Url::parse("http://example.com:8080/x/mod.ts").unwrap();
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Basic dXNlcjpwYXNz"
+    );
+    let fixture = Url::parse("http://example.com/x/mod.ts").unwrap();
+    assert_eq!(auth_tokens.get(&fixture), None);
+  }
+
+  #[test]
+  fn test_auth_tokens_malformed() {
+    let auth_tokens =
+      AuthTokens::new(Some("abc123;@deno.land; ;def456@localhost".to_string()));
+    let fixture = Url::parse("http://localhost:4545/x/mod.ts").unwrap();
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer def456"
+    );
+    let fixture = Url::parse("https://deno.land/x/mod.ts").unwrap();
+    assert_eq!(auth_tokens.get(&fixture), None);
+  }
+
+  #[test]
+  fn test_auth_tokens_invalid_header_value() {
+    let auth_tokens = AuthTokens::new(Some(
+      "abc\n123@deno.land;t\u{f6}ken@example.com;def456@localhost".to_string(),
+    ));
+    let fixture = Url::parse("https://deno.land/x/mod.ts").unwrap();
+    assert_eq!(auth_tokens.get(&fixture), None);
+    let fixture = Url::parse("https://example.com/x/mod.ts").unwrap();
+    assert_eq!(auth_tokens.get(&fixture), None);
+    let fixture = Url::parse("http://localhost:4545/x/mod.ts").unwrap();
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer def456"
+    );
+  }
+}
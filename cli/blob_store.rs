@@ -0,0 +1,63 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Storage backing `blob:` URLs created with `URL.createObjectURL()`. The
+//! store is shared by all workers of a process, so a blob URL can be
+//! imported or fetched from any of them until it is revoked.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Origin used for blob URLs; Deno doesn't have a meaningful origin yet.
+const BLOB_URL_ORIGIN: &str = "http://deno-opaque-origin";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blob {
+  pub media_type: String,
+  pub data: Vec<u8>,
+}
+
+#[derive(Clone, Default)]
+pub struct BlobStore(Arc<Mutex<HashMap<String, Blob>>>);
+
+impl BlobStore {
+  /// Stores `blob` and returns a new URL pointing to it.
+  pub fn insert(&self, blob: Blob) -> String {
+    let url = format!("blob:{}/{}", BLOB_URL_ORIGIN, Uuid::new_v4());
+    let mut blobs = self.0.lock().unwrap();
+    blobs.insert(url.clone(), blob);
+    url
+  }
+
+  pub fn get(&self, url: &str) -> Option<Blob> {
+    let blobs = self.0.lock().unwrap();
+    blobs.get(url).cloned()
+  }
+
+  pub fn remove(&self, url: &str) {
+    let mut blobs = self.0.lock().unwrap();
+    blobs.remove(url);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_insert_get_remove() {
+    let blob_store = BlobStore::default();
+    let blob = Blob {
+      media_type: "application/javascript".to_string(),
+      data: b"export default 42;".to_vec(),
+    };
+    let url = blob_store.insert(blob.clone());
+    assert!(url.starts_with("blob:http://deno-opaque-origin/"));
+    assert_eq!(blob_store.get(&url), Some(blob));
+
+    // Clones share the same storage.
+    blob_store.clone().remove(&url);
+    assert_eq!(blob_store.get(&url), None);
+  }
+}
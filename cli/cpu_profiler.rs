@@ -0,0 +1,201 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Collects a CPU profile of a worker through an in-process inspector session
+//! using the `Profiler` domain, so that scripts can be profiled without
+//! attaching DevTools. The result is written as a `.cpuprofile` file which can
+//! be loaded in Chrome DevTools.
+
+use crate::inspector::DenoInspector;
+use crate::inspector::InspectorSession;
+use crate::source_maps::get_orig_position;
+use crate::source_maps::CachedMaps;
+use crate::source_maps::SourceMapGetter;
+use deno_core::ErrBox;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+pub struct CpuProfiler {
+  session: Box<InspectorSession>,
+  dir: PathBuf,
+}
+
+impl CpuProfiler {
+  pub fn start(
+    inspector: &mut DenoInspector,
+    dir: PathBuf,
+  ) -> Result<Self, ErrBox> {
+    let mut session = InspectorSession::new(inspector);
+    session.post_message("Profiler.enable", None)?;
+    session.post_message("Profiler.start", None)?;
+    Ok(Self { session, dir })
+  }
+
+  /// Stops profiling and writes the profile to the output directory. Script
+  /// locations are mapped back to the original sources using `getter`.
+  pub fn stop<G: SourceMapGetter>(
+    mut self,
+    getter: &G,
+  ) -> Result<PathBuf, ErrBox> {
+    let mut result = self.session.post_message("Profiler.stop", None)?;
+    self.session.post_message("Profiler.disable", None)?;
+
+    let mut profile = result["profile"].take();
+    remap_profile(&mut profile, getter);
+
+    fs::create_dir_all(&self.dir)?;
+    let path = self.dir.join(profile_file_name());
+    fs::write(&path, serde_json::to_vec(&profile)?)?;
+    Ok(path)
+  }
+}
+
+fn profile_file_name() -> String {
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap()
+    .as_millis();
+  format!("CPU.{}.{}.cpuprofile", timestamp, process::id())
+}
+
+/// Rewrites `url`, `lineNumber` and `columnNumber` of every call frame in the
+/// profile so they point to the original source instead of the emitted
+/// JavaScript. Line and column numbers in the profile are 0-based.
+fn remap_profile<G: SourceMapGetter>(profile: &mut Value, getter: &G) {
+  let mut mappings_map: CachedMaps = HashMap::new();
+
+  let nodes = match profile["nodes"].as_array_mut() {
+    Some(nodes) => nodes,
+    None => return,
+  };
+
+  for node in nodes.iter_mut() {
+    let call_frame = &mut node["callFrame"];
+    let url = match call_frame["url"].as_str() {
+      Some(url) if !url.is_empty() => url.to_string(),
+      _ => continue,
+    };
+    let line_number = call_frame["lineNumber"].as_i64().unwrap_or(-1);
+    let column_number = call_frame["columnNumber"].as_i64().unwrap_or(-1);
+    if line_number < 0 || column_number < 0 {
+      continue;
+    }
+
+    let (orig_url, orig_line_number, orig_column_number) = get_orig_position(
+      url.clone(),
+      line_number + 1,
+      column_number + 1,
+      &mut mappings_map,
+      getter,
+    );
+    call_frame["url"] = json!(orig_url);
+    call_frame["lineNumber"] = json!(orig_line_number - 1);
+    call_frame["columnNumber"] = json!(orig_column_number - 1);
+
+    // Position ticks use 1-based line numbers of the emitted code.
+    if let Some(position_ticks) = node["positionTicks"].as_array_mut() {
+      for tick in position_ticks.iter_mut() {
+        let line = match tick["line"].as_i64() {
+          Some(line) => line,
+          None => continue,
+        };
+        let (_, orig_line, _) =
+          get_orig_position(url.clone(), line, 1, &mut mappings_map, getter);
+        tick["line"] = json!(orig_line);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  struct MockSourceMapGetter {}
+
+  impl SourceMapGetter for MockSourceMapGetter {
+    fn get_source_map(&self, file_name: &str) -> Option<Vec<u8>> {
+      let s = match file_name {
+        "file:///foo.js" => {
+          r#"{"version":3,"sources":["file:///foo.ts"],"names":[],"mappings":";;AAQA,IACE"}"#
+        }
+        _ => return None,
+      };
+      Some(s.as_bytes().to_owned())
+    }
+
+    fn get_source_line(
+      &self,
+      _file_name: &str,
+      _line_number: usize,
+    ) -> Option<String> {
+      None
+    }
+  }
+
+  #[test]
+  fn remap_call_frames() {
+    let mut profile = json!({
+      "nodes": [
+        {
+          "id": 1,
+          "callFrame": {
+            "functionName": "(root)",
+            "scriptId": "0",
+            "url": "",
+            "lineNumber": -1,
+            "columnNumber": -1
+          },
+          "hitCount": 0
+        },
+        {
+          "id": 2,
+          "callFrame": {
+            "functionName": "foo",
+            "scriptId": "1",
+            "url": "file:///foo.js",
+            "lineNumber": 2,
+            "columnNumber": 4
+          },
+          "hitCount": 1,
+          "positionTicks": [{ "line": 3, "ticks": 1 }]
+        },
+        {
+          "id": 3,
+          "callFrame": {
+            "functionName": "bar",
+            "scriptId": "2",
+            "url": "file:///bar.js",
+            "lineNumber": 2,
+            "columnNumber": 4
+          },
+          "hitCount": 1,
+          "positionTicks": [{ "line": 3, "ticks": 1 }]
+        }
+      ]
+    });
+    let getter = MockSourceMapGetter {};
+    remap_profile(&mut profile, &getter);
+
+    assert_eq!(profile["nodes"][0]["callFrame"]["url"], "");
+    assert_eq!(profile["nodes"][0]["callFrame"]["lineNumber"], -1);
+
+    // Emitted 2:4 maps to 9:2 and emitted line 2 starts at original line 8.
+    let call_frame = &profile["nodes"][1]["callFrame"];
+    assert_eq!(call_frame["url"], "file:///foo.ts");
+    assert_eq!(call_frame["lineNumber"], 9);
+    assert_eq!(call_frame["columnNumber"], 2);
+    assert_eq!(profile["nodes"][1]["positionTicks"][0]["line"], 9);
+
+    // Scripts without a source map are left untouched.
+    let call_frame = &profile["nodes"][2]["callFrame"];
+    assert_eq!(call_frame["url"], "file:///bar.js");
+    assert_eq!(call_frame["lineNumber"], 2);
+    assert_eq!(call_frame["columnNumber"], 4);
+    assert_eq!(profile["nodes"][2]["positionTicks"][0]["line"], 3);
+  }
+}
@@ -91,6 +91,11 @@ impl DiskCache {
 
         out = out.join(remaining_components);
       }
+      "data" | "blob" => {
+        // These URLs can be very long and contain characters that are not
+        // allowed in file names, so they are hashed.
+        out.push(crate::checksum::gen2(url.as_str()));
+      }
       scheme => {
         unimplemented!(
           "Don't know how to create cache name for scheme: {}",
@@ -191,6 +196,10 @@ mod tests {
         "https://deno.land/std/http/file_server.ts",
         "https/deno.land/std/http/file_server.ts",
       ),
+      (
+        "data:application/javascript,export%20default%201",
+        "data/785211161150007c5fe4522a87218efc8f29140f095007cde41fd42d93e0be83",
+      ),
     ];
 
     if cfg!(target_os = "windows") {
@@ -0,0 +1,588 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::colors;
+use crate::swc_ecma_ast;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use super::printer::render_params;
+use super::printer::render_ts_type;
+use super::ts_type::TsTypeDef;
+use super::ts_type_param::TsTypeParamDef;
+use super::DocNode;
+use super::DocNodeKind;
+use super::ParamDef;
+use super::ParamKind;
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiChangeKind {
+  Added,
+  Removed,
+  Changed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiChange {
+  pub kind: ApiChangeKind,
+  /// Dot separated path to the symbol, eg. `MyClass.prototype.someMethod`.
+  pub name: String,
+  pub breaking: bool,
+  pub old_signature: Option<String>,
+  pub new_signature: Option<String>,
+}
+
+/// Simplified shape of a single API entry, used to decide whether a change
+/// to it is breaking.
+#[derive(Debug, Clone, PartialEq)]
+enum EntryShape {
+  Callable {
+    params: Vec<(ParamKind, bool, Option<String>)>,
+    return_type: Option<String>,
+    type_params: Vec<String>,
+  },
+  Property {
+    optional: bool,
+    readonly: bool,
+    ts_type: Option<String>,
+    /// Members of interfaces can be implemented by consumers, so adding a
+    /// required one is breaking.
+    implementable: bool,
+  },
+  Other,
+}
+
+#[derive(Debug, Clone)]
+struct ApiEntry {
+  signature: String,
+  shape: EntryShape,
+}
+
+/// Compares public API of two versions of a module and returns list of
+/// added, removed and changed symbols.
+pub fn diff(old_nodes: &[DocNode], new_nodes: &[DocNode]) -> Vec<ApiChange> {
+  let mut old_entries = IndexMap::new();
+  flatten_nodes(old_nodes, "", &mut old_entries);
+  let mut new_entries = IndexMap::new();
+  flatten_nodes(new_nodes, "", &mut new_entries);
+
+  let mut changes = vec![];
+
+  for (name, old_entry) in old_entries.iter() {
+    match new_entries.get(name) {
+      None => changes.push(ApiChange {
+        kind: ApiChangeKind::Removed,
+        name: name.to_string(),
+        breaking: true,
+        old_signature: Some(old_entry.signature.clone()),
+        new_signature: None,
+      }),
+      Some(new_entry) => {
+        if old_entry.signature == new_entry.signature
+          && old_entry.shape == new_entry.shape
+        {
+          continue;
+        }
+        changes.push(ApiChange {
+          kind: ApiChangeKind::Changed,
+          name: name.to_string(),
+          breaking: is_breaking_change(&old_entry.shape, &new_entry.shape),
+          old_signature: Some(old_entry.signature.clone()),
+          new_signature: Some(new_entry.signature.clone()),
+        });
+      }
+    }
+  }
+
+  for (name, new_entry) in new_entries.iter() {
+    if old_entries.contains_key(name) {
+      continue;
+    }
+    let breaking = match &new_entry.shape {
+      EntryShape::Property {
+        optional,
+        implementable,
+        ..
+      } => *implementable && !optional,
+      _ => false,
+    };
+    changes.push(ApiChange {
+      kind: ApiChangeKind::Added,
+      name: name.to_string(),
+      breaking,
+      old_signature: None,
+      new_signature: Some(new_entry.signature.clone()),
+    });
+  }
+
+  changes
+}
+
+pub fn format(changes: &[ApiChange]) -> String {
+  let mut output = String::new();
+
+  let (breaking, non_breaking): (Vec<&ApiChange>, Vec<&ApiChange>) =
+    changes.iter().partition(|change| change.breaking);
+
+  for (title, group) in &[
+    ("Breaking changes", breaking),
+    ("Non-breaking changes", non_breaking),
+  ] {
+    if group.is_empty() {
+      continue;
+    }
+    output.push_str(&format!(
+      "{}\n",
+      colors::bold(format!("{} ({}):", title, group.len()))
+    ));
+    for change in group {
+      output.push_str(&format_change(change));
+    }
+    output.push_str("\n");
+  }
+
+  if output.is_empty() {
+    output.push_str("No changes to public API.\n");
+  }
+
+  output
+}
+
+fn format_change(change: &ApiChange) -> String {
+  match change.kind {
+    ApiChangeKind::Added => format!(
+      "  {} {}\n      {}\n",
+      colors::green("added".to_string()),
+      change.name,
+      change.new_signature.as_ref().unwrap()
+    ),
+    ApiChangeKind::Removed => format!(
+      "  {} {}\n      {}\n",
+      colors::red("removed".to_string()),
+      change.name,
+      change.old_signature.as_ref().unwrap()
+    ),
+    ApiChangeKind::Changed => format!(
+      "  {} {}\n    {} {}\n    {} {}\n",
+      colors::yellow("changed".to_string()),
+      change.name,
+      colors::red("-".to_string()),
+      change.old_signature.as_ref().unwrap(),
+      colors::green("+".to_string()),
+      change.new_signature.as_ref().unwrap()
+    ),
+  }
+}
+
+fn is_breaking_change(old: &EntryShape, new: &EntryShape) -> bool {
+  match (old, new) {
+    (
+      EntryShape::Callable {
+        params: old_params,
+        return_type: old_return_type,
+        type_params: old_type_params,
+      },
+      EntryShape::Callable {
+        params: new_params,
+        return_type: new_return_type,
+        type_params: new_type_params,
+      },
+    ) => {
+      if old_return_type != new_return_type
+        || old_type_params != new_type_params
+      {
+        return true;
+      }
+      if new_params.len() < old_params.len() {
+        return true;
+      }
+      for (i, (new_kind, new_optional, new_type)) in
+        new_params.iter().enumerate()
+      {
+        match old_params.get(i) {
+          Some((old_kind, old_optional, old_type)) => {
+            if old_kind != new_kind
+              || old_type != new_type
+              || (*old_optional && !new_optional)
+            {
+              return true;
+            }
+          }
+          None => {
+            // New trailing parameters must be optional.
+            if !new_optional && *new_kind != ParamKind::Rest {
+              return true;
+            }
+          }
+        }
+      }
+      false
+    }
+    (
+      EntryShape::Property {
+        optional: old_optional,
+        readonly: old_readonly,
+        ts_type: old_type,
+        ..
+      },
+      EntryShape::Property {
+        optional: new_optional,
+        readonly: new_readonly,
+        ts_type: new_type,
+        ..
+      },
+    ) => {
+      old_type != new_type
+        || old_optional != new_optional
+        || (!old_readonly && *new_readonly)
+    }
+    _ => true,
+  }
+}
+
+fn render_type_params(type_params: &[TsTypeParamDef]) -> Vec<String> {
+  type_params
+    .iter()
+    .map(|param| {
+      let mut rendered = param.name.clone();
+      if let Some(constraint) = &param.constraint {
+        rendered += &format!(" extends {}", render_ts_type(constraint.clone()));
+      }
+      if let Some(default) = &param.default {
+        rendered += &format!(" = {}", render_ts_type(default.clone()));
+      }
+      rendered
+    })
+    .collect()
+}
+
+fn type_params_suffix(type_params: &[String]) -> String {
+  if type_params.is_empty() {
+    "".to_string()
+  } else {
+    format!("<{}>", type_params.join(", "))
+  }
+}
+
+fn callable_entry(
+  prefix: &str,
+  name: &str,
+  params: &[ParamDef],
+  return_type: Option<&TsTypeDef>,
+  type_params: &[TsTypeParamDef],
+) -> ApiEntry {
+  let type_params = render_type_params(type_params);
+  let return_type = return_type.cloned().map(render_ts_type);
+  let signature = format!(
+    "{}{}{}({}){}",
+    prefix,
+    name,
+    type_params_suffix(&type_params),
+    render_params(params.to_vec()),
+    return_type
+      .as_ref()
+      .map(|t| format!(": {}", t))
+      .unwrap_or_else(|| "".to_string())
+  );
+  let params = params
+    .iter()
+    .map(|param| {
+      (
+        param.kind.clone(),
+        param.optional,
+        param.ts_type.clone().map(render_ts_type),
+      )
+    })
+    .collect();
+
+  ApiEntry {
+    signature,
+    shape: EntryShape::Callable {
+      params,
+      return_type,
+      type_params,
+    },
+  }
+}
+
+fn property_entry(
+  name: &str,
+  optional: bool,
+  readonly: bool,
+  ts_type: Option<&TsTypeDef>,
+  implementable: bool,
+) -> ApiEntry {
+  let ts_type = ts_type.cloned().map(render_ts_type);
+  let signature = format!(
+    "{}{}{}{}",
+    if readonly { "readonly " } else { "" },
+    name,
+    if optional { "?" } else { "" },
+    ts_type
+      .as_ref()
+      .map(|t| format!(": {}", t))
+      .unwrap_or_else(|| "".to_string())
+  );
+
+  ApiEntry {
+    signature,
+    shape: EntryShape::Property {
+      optional,
+      readonly,
+      ts_type,
+      implementable,
+    },
+  }
+}
+
+fn other_entry(signature: String) -> ApiEntry {
+  ApiEntry {
+    signature,
+    shape: EntryShape::Other,
+  }
+}
+
+/// Inserts entry under given name; overloads of the same symbol get
+/// numbered suffix so they can be told apart.
+fn insert_entry(
+  entries: &mut IndexMap<String, ApiEntry>,
+  name: String,
+  entry: ApiEntry,
+) {
+  if !entries.contains_key(&name) {
+    entries.insert(name, entry);
+    return;
+  }
+  let mut i = 2;
+  loop {
+    let overload_name = format!("{}#{}", name, i);
+    if !entries.contains_key(&overload_name) {
+      entries.insert(overload_name, entry);
+      return;
+    }
+    i += 1;
+  }
+}
+
+fn flatten_nodes(
+  doc_nodes: &[DocNode],
+  prefix: &str,
+  entries: &mut IndexMap<String, ApiEntry>,
+) {
+  use swc_ecma_ast::Accessibility;
+
+  for node in doc_nodes {
+    let name = format!("{}{}", prefix, node.name);
+
+    match node.kind {
+      DocNodeKind::Function => {
+        let function_def = node.function_def.as_ref().unwrap();
+        let entry = callable_entry(
+          "function ",
+          &node.name,
+          &function_def.params,
+          function_def.return_type.as_ref(),
+          &function_def.type_params,
+        );
+        insert_entry(entries, name, entry);
+      }
+      DocNodeKind::Variable => {
+        let variable_def = node.variable_def.as_ref().unwrap();
+        let kind = match variable_def.kind {
+          swc_ecma_ast::VarDeclKind::Const => "const",
+          swc_ecma_ast::VarDeclKind::Let => "let",
+          swc_ecma_ast::VarDeclKind::Var => "var",
+        };
+        let signature = format!(
+          "{} {}{}",
+          kind,
+          node.name,
+          variable_def
+            .ts_type
+            .as_ref()
+            .map(|t| format!(": {}", render_ts_type(t.clone())))
+            .unwrap_or_else(|| "".to_string())
+        );
+        insert_entry(entries, name, other_entry(signature));
+      }
+      DocNodeKind::Class => {
+        let class_def = node.class_def.as_ref().unwrap();
+        let type_params = render_type_params(&class_def.type_params);
+        let mut signature = format!(
+          "{}class {}{}",
+          if class_def.is_abstract {
+            "abstract "
+          } else {
+            ""
+          },
+          node.name,
+          type_params_suffix(&type_params)
+        );
+        if let Some(extends) = &class_def.extends {
+          signature += &format!(" extends {}", extends);
+        }
+        if !class_def.implements.is_empty() {
+          signature +=
+            &format!(" implements {}", class_def.implements.join(", "));
+        }
+        insert_entry(entries, name.clone(), other_entry(signature));
+
+        for ctor in &class_def.constructors {
+          if let Some(Accessibility::Private) = ctor.accessibility {
+            continue;
+          }
+          let entry =
+            callable_entry("", "constructor", &ctor.params, None, &[]);
+          insert_entry(entries, format!("{}.constructor", name), entry);
+        }
+
+        for prop in &class_def.properties {
+          if let Some(Accessibility::Private) = prop.accessibility {
+            continue;
+          }
+          let entry = property_entry(
+            &prop.name,
+            prop.optional,
+            prop.readonly,
+            prop.ts_type.as_ref(),
+            false,
+          );
+          let entry = ApiEntry {
+            signature: format!(
+              "{}{}",
+              if prop.is_static { "static " } else { "" },
+              entry.signature
+            ),
+            ..entry
+          };
+          insert_entry(
+            entries,
+            member_name(&name, &prop.name, prop.is_static),
+            entry,
+          );
+        }
+
+        for method in &class_def.methods {
+          if let Some(Accessibility::Private) = method.accessibility {
+            continue;
+          }
+          let method_prefix = match method.kind {
+            swc_ecma_ast::MethodKind::Getter => "get ",
+            swc_ecma_ast::MethodKind::Setter => "set ",
+            swc_ecma_ast::MethodKind::Method => "",
+          };
+          let prefix = format!(
+            "{}{}",
+            if method.is_static { "static " } else { "" },
+            method_prefix
+          );
+          let entry = callable_entry(
+            &prefix,
+            &method.name,
+            &method.function_def.params,
+            method.function_def.return_type.as_ref(),
+            &method.function_def.type_params,
+          );
+          let method_name = format!(
+            "{}{}",
+            method_prefix,
+            member_name(&name, &method.name, method.is_static)
+          );
+          insert_entry(entries, method_name, entry);
+        }
+      }
+      DocNodeKind::Enum => {
+        let enum_def = node.enum_def.as_ref().unwrap();
+        let signature = format!("enum {}", node.name);
+        insert_entry(entries, name.clone(), other_entry(signature));
+        for member in &enum_def.members {
+          let signature = format!("{}.{}", node.name, member.name);
+          insert_entry(
+            entries,
+            format!("{}.{}", name, member.name),
+            other_entry(signature),
+          );
+        }
+      }
+      DocNodeKind::Interface => {
+        let interface_def = node.interface_def.as_ref().unwrap();
+        let type_params = render_type_params(&interface_def.type_params);
+        let mut signature = format!(
+          "interface {}{}",
+          node.name,
+          type_params_suffix(&type_params)
+        );
+        if !interface_def.extends.is_empty() {
+          signature +=
+            &format!(" extends {}", interface_def.extends.join(", "));
+        }
+        insert_entry(entries, name.clone(), other_entry(signature));
+
+        for prop in &interface_def.properties {
+          let entry = property_entry(
+            &prop.name,
+            prop.optional,
+            false,
+            prop.ts_type.as_ref(),
+            true,
+          );
+          insert_entry(entries, format!("{}.{}", name, prop.name), entry);
+        }
+
+        for method in &interface_def.methods {
+          let mut entry = callable_entry(
+            "",
+            &method.name,
+            &method.params,
+            method.return_type.as_ref(),
+            &method.type_params,
+          );
+          if method.optional {
+            entry.signature = entry.signature.replacen(
+              &method.name,
+              &format!("{}?", method.name),
+              1,
+            );
+          }
+          insert_entry(entries, format!("{}.{}", name, method.name), entry);
+        }
+
+        for call_sig in &interface_def.call_signatures {
+          let entry = callable_entry(
+            "",
+            "",
+            &call_sig.params,
+            call_sig.ts_type.as_ref(),
+            &call_sig.type_params,
+          );
+          insert_entry(entries, format!("{}()", name), entry);
+        }
+      }
+      DocNodeKind::TypeAlias => {
+        let type_alias_def = node.type_alias_def.as_ref().unwrap();
+        let type_params = render_type_params(&type_alias_def.type_params);
+        let signature = format!(
+          "type {}{} = {}",
+          node.name,
+          type_params_suffix(&type_params),
+          render_ts_type(type_alias_def.ts_type.clone())
+        );
+        insert_entry(entries, name, other_entry(signature));
+      }
+      DocNodeKind::Namespace => {
+        let namespace_def = node.namespace_def.as_ref().unwrap();
+        let signature = format!("namespace {}", node.name);
+        insert_entry(entries, name.clone(), other_entry(signature));
+        flatten_nodes(&namespace_def.elements, &format!("{}.", name), entries);
+      }
+    }
+  }
+}
+
+fn member_name(class_name: &str, member_name: &str, is_static: bool) -> String {
+  if is_static {
+    format!("{}.{}", class_name, member_name)
+  } else {
+    format!("{}.prototype.{}", class_name, member_name)
+  }
+}
@@ -0,0 +1,580 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::swc_ecma_ast;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use super::class::ClassDef;
+use super::function::FunctionDef;
+use super::interface::InterfaceDef;
+use super::ts_type::TsTypeDef;
+use super::ts_type_param::TsTypeParamDef;
+use super::DocNode;
+use super::DocNodeKind;
+use super::Location;
+use super::ParamDef;
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum LintDiagnosticKind {
+  MissingJsDoc,
+  MissingParamDoc,
+  MissingReturnType,
+  PrivateTypeRef,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LintDiagnostic {
+  pub kind: LintDiagnosticKind,
+  pub location: Location,
+  pub message: String,
+}
+
+impl std::fmt::Display for LintDiagnostic {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+      f,
+      "{}:{}:{}: {}",
+      self.location.filename,
+      self.location.line,
+      self.location.col,
+      self.message
+    )
+  }
+}
+
+/// Checks exported symbols for missing JSDoc, undocumented parameters,
+/// missing return types and references to types that are declared in the
+/// module but not exported from it.
+pub fn lint(
+  doc_nodes: &[DocNode],
+  private_types: &[String],
+) -> Vec<LintDiagnostic> {
+  let mut exported = HashSet::new();
+  collect_exported_names(doc_nodes, &mut exported);
+
+  let mut linter = Linter {
+    exported,
+    private_types: private_types.iter().cloned().collect(),
+    diagnostics: vec![],
+  };
+
+  for node in doc_nodes {
+    linter.lint_node(node, "");
+  }
+
+  linter.diagnostics
+}
+
+fn collect_exported_names(doc_nodes: &[DocNode], names: &mut HashSet<String>) {
+  for node in doc_nodes {
+    names.insert(node.name.clone());
+    if let Some(namespace_def) = &node.namespace_def {
+      collect_exported_names(&namespace_def.elements, names);
+    }
+  }
+}
+
+fn kind_name(kind: &DocNodeKind) -> &'static str {
+  match kind {
+    DocNodeKind::Function => "function",
+    DocNodeKind::Variable => "variable",
+    DocNodeKind::Class => "class",
+    DocNodeKind::Enum => "enum",
+    DocNodeKind::Interface => "interface",
+    DocNodeKind::TypeAlias => "type alias",
+    DocNodeKind::Namespace => "namespace",
+  }
+}
+
+/// Returns names of all parameters documented with `@param` tags.
+fn documented_params(js_doc: &str) -> HashSet<String> {
+  lazy_static! {
+    static ref PARAM_TAG: Regex =
+      Regex::new(r"@param\s+(?:\{[^}]*\}\s*)?\[?(?:\.\.\.)?([\w$]+)").unwrap();
+  }
+  PARAM_TAG
+    .captures_iter(js_doc)
+    .map(|caps| caps[1].to_string())
+    .collect()
+}
+
+struct Linter {
+  exported: HashSet<String>,
+  private_types: HashSet<String>,
+  diagnostics: Vec<LintDiagnostic>,
+}
+
+impl Linter {
+  fn report(
+    &mut self,
+    kind: LintDiagnosticKind,
+    location: &Location,
+    message: String,
+  ) {
+    self.diagnostics.push(LintDiagnostic {
+      kind,
+      location: location.clone(),
+      message,
+    });
+  }
+
+  fn lint_node(&mut self, node: &DocNode, prefix: &str) {
+    let name = format!("{}{}", prefix, node.name);
+
+    if node.js_doc.is_none() {
+      self.report(
+        LintDiagnosticKind::MissingJsDoc,
+        &node.location,
+        format!(
+          "Missing JSDoc for exported {} \"{}\"",
+          kind_name(&node.kind),
+          name
+        ),
+      );
+    }
+
+    match node.kind {
+      DocNodeKind::Function => {
+        let function_def = node.function_def.as_ref().unwrap();
+        self.lint_function(
+          function_def,
+          node.js_doc.as_ref(),
+          &node.location,
+          &name,
+          true,
+          &[],
+        );
+      }
+      DocNodeKind::Variable => {
+        let variable_def = node.variable_def.as_ref().unwrap();
+        if let Some(ts_type) = &variable_def.ts_type {
+          self.check_type(ts_type, &[], &node.location, &name);
+        }
+      }
+      DocNodeKind::Class => {
+        let class_def = node.class_def.as_ref().unwrap();
+        self.lint_class(class_def, &node.location, &name);
+      }
+      DocNodeKind::Interface => {
+        let interface_def = node.interface_def.as_ref().unwrap();
+        self.lint_interface(interface_def, &node.location, &name);
+      }
+      DocNodeKind::TypeAlias => {
+        let type_alias_def = node.type_alias_def.as_ref().unwrap();
+        self.check_type_params(
+          &type_alias_def.type_params,
+          &type_alias_def.type_params,
+          &node.location,
+          &name,
+        );
+        self.check_type(
+          &type_alias_def.ts_type,
+          &type_alias_def.type_params,
+          &node.location,
+          &name,
+        );
+      }
+      DocNodeKind::Namespace => {
+        let namespace_def = node.namespace_def.as_ref().unwrap();
+        let prefix = format!("{}.", name);
+        for element in &namespace_def.elements {
+          self.lint_node(element, &prefix);
+        }
+      }
+      DocNodeKind::Enum => {}
+    }
+  }
+
+  fn lint_function(
+    &mut self,
+    function_def: &FunctionDef,
+    js_doc: Option<&String>,
+    location: &Location,
+    name: &str,
+    require_return_type: bool,
+    outer_type_params: &[TsTypeParamDef],
+  ) {
+    let mut type_params = outer_type_params.to_vec();
+    type_params.extend(function_def.type_params.iter().cloned());
+
+    self.check_params(&function_def.params, js_doc, location, name);
+    self.check_params_types(&function_def.params, &type_params, location, name);
+    self.check_type_params(
+      &function_def.type_params,
+      &type_params,
+      location,
+      name,
+    );
+
+    match &function_def.return_type {
+      Some(return_type) => {
+        self.check_type(return_type, &type_params, location, name)
+      }
+      None if require_return_type => self.report(
+        LintDiagnosticKind::MissingReturnType,
+        location,
+        format!("Missing explicit return type for \"{}\"", name),
+      ),
+      None => {}
+    }
+  }
+
+  fn lint_class(
+    &mut self,
+    class_def: &ClassDef,
+    location: &Location,
+    name: &str,
+  ) {
+    use swc_ecma_ast::Accessibility;
+    use swc_ecma_ast::MethodKind;
+
+    let type_params = &class_def.type_params;
+    self.check_type_params(type_params, type_params, location, name);
+
+    for constructor in &class_def.constructors {
+      if let Some(Accessibility::Private) = constructor.accessibility {
+        continue;
+      }
+      let ctor_name = format!("{}.constructor", name);
+      if constructor.js_doc.is_none() && !constructor.params.is_empty() {
+        self.report(
+          LintDiagnosticKind::MissingJsDoc,
+          &constructor.location,
+          format!("Missing JSDoc for \"{}\"", ctor_name),
+        );
+      }
+      self.check_params(
+        &constructor.params,
+        constructor.js_doc.as_ref(),
+        &constructor.location,
+        &ctor_name,
+      );
+      self.check_params_types(
+        &constructor.params,
+        type_params,
+        &constructor.location,
+        &ctor_name,
+      );
+    }
+
+    for property in &class_def.properties {
+      if let Some(Accessibility::Private) = property.accessibility {
+        continue;
+      }
+      let prop_name = format!("{}.{}", name, property.name);
+      if property.js_doc.is_none() {
+        self.report(
+          LintDiagnosticKind::MissingJsDoc,
+          &property.location,
+          format!("Missing JSDoc for property \"{}\"", prop_name),
+        );
+      }
+      if let Some(ts_type) = &property.ts_type {
+        self.check_type(ts_type, type_params, &property.location, &prop_name);
+      }
+    }
+
+    for method in &class_def.methods {
+      if let Some(Accessibility::Private) = method.accessibility {
+        continue;
+      }
+      let method_name = format!("{}.{}", name, method.name);
+      if method.js_doc.is_none() {
+        self.report(
+          LintDiagnosticKind::MissingJsDoc,
+          &method.location,
+          format!("Missing JSDoc for method \"{}\"", method_name),
+        );
+      }
+      // Setters can't have a return type annotation.
+      let require_return_type = method.kind != MethodKind::Setter;
+      self.lint_function(
+        &method.function_def,
+        method.js_doc.as_ref(),
+        &method.location,
+        &method_name,
+        require_return_type,
+        type_params,
+      );
+    }
+  }
+
+  fn lint_interface(
+    &mut self,
+    interface_def: &InterfaceDef,
+    location: &Location,
+    name: &str,
+  ) {
+    let type_params = &interface_def.type_params;
+    self.check_type_params(type_params, type_params, location, name);
+
+    for property in &interface_def.properties {
+      let prop_name = format!("{}.{}", name, property.name);
+      if property.js_doc.is_none() {
+        self.report(
+          LintDiagnosticKind::MissingJsDoc,
+          &property.location,
+          format!("Missing JSDoc for property \"{}\"", prop_name),
+        );
+      }
+      if let Some(ts_type) = &property.ts_type {
+        self.check_type(ts_type, type_params, &property.location, &prop_name);
+      }
+    }
+
+    for method in &interface_def.methods {
+      let method_name = format!("{}.{}", name, method.name);
+      if method.js_doc.is_none() {
+        self.report(
+          LintDiagnosticKind::MissingJsDoc,
+          &method.location,
+          format!("Missing JSDoc for method \"{}\"", method_name),
+        );
+      }
+      let mut method_type_params = type_params.clone();
+      method_type_params.extend(method.type_params.iter().cloned());
+      self.check_params(
+        &method.params,
+        method.js_doc.as_ref(),
+        &method.location,
+        &method_name,
+      );
+      self.check_params_types(
+        &method.params,
+        &method_type_params,
+        &method.location,
+        &method_name,
+      );
+      if let Some(return_type) = &method.return_type {
+        self.check_type(
+          return_type,
+          &method_type_params,
+          &method.location,
+          &method_name,
+        );
+      }
+    }
+
+    for call_signature in &interface_def.call_signatures {
+      let mut sig_type_params = type_params.clone();
+      sig_type_params.extend(call_signature.type_params.iter().cloned());
+      self.check_params(
+        &call_signature.params,
+        call_signature.js_doc.as_ref(),
+        &call_signature.location,
+        name,
+      );
+      self.check_params_types(
+        &call_signature.params,
+        &sig_type_params,
+        &call_signature.location,
+        name,
+      );
+      if let Some(ts_type) = &call_signature.ts_type {
+        self.check_type(
+          ts_type,
+          &sig_type_params,
+          &call_signature.location,
+          name,
+        );
+      }
+    }
+  }
+
+  /// Reports parameters that are missing a `@param` tag. Nothing is
+  /// reported if the symbol has no JSDoc at all, as that is already
+  /// reported separately.
+  fn check_params(
+    &mut self,
+    params: &[ParamDef],
+    js_doc: Option<&String>,
+    location: &Location,
+    name: &str,
+  ) {
+    let js_doc = match js_doc {
+      Some(js_doc) => js_doc,
+      None => return,
+    };
+    let documented = documented_params(js_doc);
+
+    for param in params {
+      // Destructured parameters don't have a name that could be documented.
+      if param.name.is_empty() || documented.contains(&param.name) {
+        continue;
+      }
+      self.report(
+        LintDiagnosticKind::MissingParamDoc,
+        location,
+        format!(
+          "Missing @param documentation for \"{}\" in \"{}\"",
+          param.name, name
+        ),
+      );
+    }
+  }
+
+  fn check_params_types(
+    &mut self,
+    params: &[ParamDef],
+    type_params: &[TsTypeParamDef],
+    location: &Location,
+    name: &str,
+  ) {
+    for param in params {
+      if let Some(ts_type) = &param.ts_type {
+        self.check_type(ts_type, type_params, location, name);
+      }
+    }
+  }
+
+  fn check_type_params(
+    &mut self,
+    declared: &[TsTypeParamDef],
+    in_scope: &[TsTypeParamDef],
+    location: &Location,
+    name: &str,
+  ) {
+    for type_param in declared {
+      if let Some(constraint) = &type_param.constraint {
+        self.check_type(constraint, in_scope, location, name);
+      }
+      if let Some(default) = &type_param.default {
+        self.check_type(default, in_scope, location, name);
+      }
+    }
+  }
+
+  fn check_type(
+    &mut self,
+    ts_type: &TsTypeDef,
+    type_params: &[TsTypeParamDef],
+    location: &Location,
+    name: &str,
+  ) {
+    let mut type_names = vec![];
+    collect_type_ref_names(ts_type, &mut type_names);
+
+    for type_name in type_names {
+      // Only the first segment matters for qualified names like `Deno.Reader`.
+      let root_name = type_name.split('.').next().unwrap().to_string();
+      if type_params.iter().any(|param| param.name == root_name)
+        || self.exported.contains(&root_name)
+        || !self.private_types.contains(&root_name)
+      {
+        continue;
+      }
+      self.report(
+        LintDiagnosticKind::PrivateTypeRef,
+        location,
+        format!(
+          "\"{}\" references type \"{}\" which is not exported",
+          name, type_name
+        ),
+      );
+    }
+  }
+}
+
+fn collect_type_ref_names(ts_type: &TsTypeDef, names: &mut Vec<String>) {
+  if let Some(type_ref) = &ts_type.type_ref {
+    names.push(type_ref.type_name.clone());
+    if let Some(type_args) = &type_ref.type_params {
+      for type_arg in type_args {
+        collect_type_ref_names(type_arg, names);
+      }
+    }
+  }
+
+  let lists = [&ts_type.union, &ts_type.intersection, &ts_type.tuple];
+  for list in lists.iter() {
+    if let Some(types) = list {
+      for t in types {
+        collect_type_ref_names(t, names);
+      }
+    }
+  }
+
+  let boxed = [
+    &ts_type.array,
+    &ts_type.parenthesized,
+    &ts_type.rest,
+    &ts_type.optional,
+  ];
+  for maybe_type in boxed.iter() {
+    if let Some(t) = maybe_type {
+      collect_type_ref_names(t, names);
+    }
+  }
+
+  if let Some(type_operator) = &ts_type.type_operator {
+    collect_type_ref_names(&type_operator.ts_type, names);
+  }
+
+  if let Some(fn_or_constructor) = &ts_type.fn_or_constructor {
+    let mut inner = vec![];
+    collect_type_ref_names(&fn_or_constructor.ts_type, &mut inner);
+    for param in &fn_or_constructor.params {
+      if let Some(t) = &param.ts_type {
+        collect_type_ref_names(t, &mut inner);
+      }
+    }
+    names.extend(filter_type_params(inner, &fn_or_constructor.type_params));
+  }
+
+  if let Some(conditional) = &ts_type.conditional_type {
+    collect_type_ref_names(&conditional.check_type, names);
+    collect_type_ref_names(&conditional.extends_type, names);
+    collect_type_ref_names(&conditional.true_type, names);
+    collect_type_ref_names(&conditional.false_type, names);
+  }
+
+  if let Some(indexed_access) = &ts_type.indexed_access {
+    collect_type_ref_names(&indexed_access.obj_type, names);
+    collect_type_ref_names(&indexed_access.index_type, names);
+  }
+
+  if let Some(type_literal) = &ts_type.type_literal {
+    for property in &type_literal.properties {
+      if let Some(t) = &property.ts_type {
+        collect_type_ref_names(t, names);
+      }
+    }
+    for method in &type_literal.methods {
+      let mut inner = vec![];
+      for param in &method.params {
+        if let Some(t) = &param.ts_type {
+          collect_type_ref_names(t, &mut inner);
+        }
+      }
+      if let Some(t) = &method.return_type {
+        collect_type_ref_names(t, &mut inner);
+      }
+      names.extend(filter_type_params(inner, &method.type_params));
+    }
+    for call_signature in &type_literal.call_signatures {
+      let mut inner = vec![];
+      for param in &call_signature.params {
+        if let Some(t) = &param.ts_type {
+          collect_type_ref_names(t, &mut inner);
+        }
+      }
+      if let Some(t) = &call_signature.ts_type {
+        collect_type_ref_names(t, &mut inner);
+      }
+      names.extend(filter_type_params(inner, &call_signature.type_params));
+    }
+  }
+}
+
+/// Drops names that refer to type parameters declared by a nested signature.
+fn filter_type_params(
+  names: Vec<String>,
+  type_params: &[TsTypeParamDef],
+) -> Vec<String> {
+  names
+    .into_iter()
+    .filter(|name| !type_params.iter().any(|param| &param.name == name))
+    .collect()
+}
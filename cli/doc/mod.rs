@@ -1,8 +1,10 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 pub mod class;
+pub mod diff;
 pub mod r#enum;
 pub mod function;
 pub mod interface;
+pub mod lint;
 pub mod module;
 pub mod namespace;
 mod node;
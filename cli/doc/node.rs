@@ -14,7 +14,7 @@ pub enum DocNodeKind {
   Namespace,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, PartialEq, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum ParamKind {
   Identifier,
@@ -190,6 +190,90 @@ impl DocParser {
     Ok(flattened_docs)
   }
 
+  /// Returns names of types (classes, interfaces, type aliases and enums)
+  /// that are declared but not exported in the module or in any of the
+  /// modules `parse_with_reexports` reads its re-exports from.
+  pub async fn parse_private_types(
+    &self,
+    file_name: &str,
+  ) -> Result<Vec<String>, ErrBox> {
+    let source_code = self.loader.load_source_code(file_name).await?;
+    let (mut private_types, reexports) =
+      self.parse_private_types_source(file_name, &source_code)?;
+
+    let mut specifiers: Vec<&str> = vec![];
+    for reexport in &reexports {
+      if !specifiers.contains(&reexport.src.as_str()) {
+        specifiers.push(&reexport.src);
+      }
+    }
+
+    for specifier in specifiers {
+      let resolved_specifier =
+        self.loader.resolve(specifier, file_name)?.to_string();
+      let source_code =
+        self.loader.load_source_code(&resolved_specifier).await?;
+      let (reexported_private_types, _) =
+        self.parse_private_types_source(&resolved_specifier, &source_code)?;
+      for name in reexported_private_types {
+        if !private_types.contains(&name) {
+          private_types.push(name);
+        }
+      }
+    }
+
+    Ok(private_types)
+  }
+
+  fn parse_private_types_source(
+    &self,
+    file_name: &str,
+    source_code: &str,
+  ) -> Result<(Vec<String>, Vec<node::Reexport>), SwcDiagnosticBuffer> {
+    self
+      .ast_parser
+      .parse_module(file_name, source_code, |parse_result| {
+        let module = parse_result?;
+        let private_types =
+          self.get_private_types_for_module_body(&module.body);
+        let reexports = self.get_reexports_for_module_body(module.body);
+        Ok((private_types, reexports))
+      })
+  }
+
+  pub fn get_private_types_for_module_body(
+    &self,
+    module_body: &[swc_ecma_ast::ModuleItem],
+  ) -> Vec<String> {
+    let mut private_types = vec![];
+
+    for node in module_body.iter() {
+      if let swc_ecma_ast::ModuleItem::Stmt(Stmt::Decl(decl)) = node {
+        let maybe_name = match decl {
+          Decl::Class(class_decl) if !class_decl.declare => {
+            Some(class_decl.ident.sym.to_string())
+          }
+          Decl::TsInterface(interface_decl) if !interface_decl.declare => {
+            Some(interface_decl.id.sym.to_string())
+          }
+          Decl::TsTypeAlias(type_alias) if !type_alias.declare => {
+            Some(type_alias.id.sym.to_string())
+          }
+          Decl::TsEnum(ts_enum) if !ts_enum.declare => {
+            Some(ts_enum.id.sym.to_string())
+          }
+          _ => None,
+        };
+
+        if let Some(name) = maybe_name {
+          private_types.push(name);
+        }
+      }
+    }
+
+    private_types
+  }
+
   pub fn get_doc_nodes_for_module_exports(
     &self,
     module_decl: &ModuleDecl,
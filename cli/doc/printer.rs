@@ -107,7 +107,7 @@ fn format_(doc_nodes: Vec<doc::DocNode>, indent: i64) -> String {
   output
 }
 
-fn render_params(params: Vec<doc::ParamDef>) -> String {
+pub fn render_params(params: Vec<doc::ParamDef>) -> String {
   let mut rendered = String::from("");
   if !params.is_empty() {
     for param in params {
@@ -126,8 +126,12 @@ fn render_params(params: Vec<doc::ParamDef>) -> String {
   rendered
 }
 
-fn render_ts_type(ts_type: doc::ts_type::TsTypeDef) -> String {
-  let kind = ts_type.kind.unwrap();
+pub fn render_ts_type(ts_type: doc::ts_type::TsTypeDef) -> String {
+  let kind = match ts_type.kind {
+    Some(kind) => kind,
+    // Types the doc parser doesn't understand yet only carry a `repr`.
+    None => return ts_type.repr,
+  };
   match kind {
     TsTypeDefKind::Array => {
       format!("{}[]", render_ts_type(*ts_type.array.unwrap()))
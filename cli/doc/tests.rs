@@ -1436,3 +1436,211 @@ export function fooFn(a: number) {
       .contains("function fooFn(a: number)")
   );
 }
+
+#[tokio::test]
+async fn lint() {
+  let source_code = r#"
+interface Options {
+  verbose: boolean;
+}
+
+/**
+ * Documented function.
+ * @param a first operand
+ */
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+export function noDocs(opts: Options) {
+  return opts;
+}
+
+/** Documented class */
+export class Foo {
+  /** Documented property */
+  bar: string = "";
+
+  baz(): void {}
+}
+"#;
+  let loader =
+    TestLoader::new(vec![("test.ts".to_string(), source_code.to_string())]);
+  let doc_parser = DocParser::new(loader);
+  let entries = doc_parser.parse_with_reexports("test.ts").await.unwrap();
+  let private_types = doc_parser.parse_private_types("test.ts").await.unwrap();
+  assert_eq!(private_types, vec!["Options".to_string()]);
+
+  let diagnostics = super::lint::lint(&entries, &private_types);
+  let messages: Vec<String> =
+    diagnostics.iter().map(|d| d.to_string()).collect();
+  assert_eq!(
+    messages,
+    vec![
+      "test.ts:10:0: Missing @param documentation for \"b\" in \"add\"",
+      "test.ts:14:0: Missing JSDoc for exported function \"noDocs\"",
+      "test.ts:14:0: \"noDocs\" references type \"Options\" which is not exported",
+      "test.ts:14:0: Missing explicit return type for \"noDocs\"",
+      "test.ts:23:2: Missing JSDoc for method \"Foo.baz\"",
+    ]
+  );
+}
+
+#[tokio::test]
+async fn lint_reexported_module() {
+  let reexport_source_code = r#"
+interface Options {
+  verbose: boolean;
+}
+
+/**
+ * Documented function.
+ * @param opts options
+ */
+export function run(opts: Options): void {}
+"#;
+  let test_source_code = r#"
+export { run } from "./reexport.ts";
+"#;
+  let loader = TestLoader::new(vec![
+    ("file:///test.ts".to_string(), test_source_code.to_string()),
+    (
+      "file:///reexport.ts".to_string(),
+      reexport_source_code.to_string(),
+    ),
+  ]);
+  let doc_parser = DocParser::new(loader);
+  let entries = doc_parser
+    .parse_with_reexports("file:///test.ts")
+    .await
+    .unwrap();
+  let private_types = doc_parser
+    .parse_private_types("file:///test.ts")
+    .await
+    .unwrap();
+  assert_eq!(private_types, vec!["Options".to_string()]);
+
+  let diagnostics = super::lint::lint(&entries, &private_types);
+  let messages: Vec<String> =
+    diagnostics.iter().map(|d| d.to_string()).collect();
+  assert_eq!(
+    messages,
+    vec![
+      "file:///reexport.ts:10:0: \"run\" references type \"Options\" which is not exported",
+    ]
+  );
+}
+
+#[tokio::test]
+async fn diff() {
+  let old_source_code = r#"
+export function foo(a: number): string {
+  return "";
+}
+
+export function bar(a: number): void {}
+
+export interface Options {
+  verbose: boolean;
+}
+
+export const VERSION = "1.0.0";
+"#;
+  let new_source_code = r#"
+export function foo(a: number, b?: string): string {
+  return "";
+}
+
+export function bar(a: string): void {}
+
+export interface Options {
+  verbose: boolean;
+  debug: boolean;
+}
+
+export function baz(): void {}
+"#;
+  let loader = TestLoader::new(vec![
+    ("old.ts".to_string(), old_source_code.to_string()),
+    ("new.ts".to_string(), new_source_code.to_string()),
+  ]);
+  let doc_parser = DocParser::new(loader);
+  let old_entries = doc_parser.parse_with_reexports("old.ts").await.unwrap();
+  let new_entries = doc_parser.parse_with_reexports("new.ts").await.unwrap();
+
+  let changes = super::diff::diff(&old_entries, &new_entries);
+  let expected_json = json!([
+    {
+      "kind": "changed",
+      "name": "foo",
+      "breaking": false,
+      "oldSignature": "function foo(a: number): string",
+      "newSignature": "function foo(a: number, b?: string): string"
+    },
+    {
+      "kind": "changed",
+      "name": "bar",
+      "breaking": true,
+      "oldSignature": "function bar(a: number): void",
+      "newSignature": "function bar(a: string): void"
+    },
+    {
+      "kind": "removed",
+      "name": "VERSION",
+      "breaking": true,
+      "oldSignature": "const VERSION",
+      "newSignature": null
+    },
+    {
+      "kind": "added",
+      "name": "Options.debug",
+      "breaking": true,
+      "oldSignature": null,
+      "newSignature": "debug: boolean"
+    },
+    {
+      "kind": "added",
+      "name": "baz",
+      "breaking": false,
+      "oldSignature": null,
+      "newSignature": "function baz(): void"
+    }
+  ]);
+  let actual = serde_json::to_value(&changes).unwrap();
+  assert_eq!(actual, expected_json);
+
+  assert!(
+    colors::strip_ansi_codes(super::diff::format(&changes).as_str())
+      .contains("Breaking changes (3):")
+  );
+}
+
+#[tokio::test]
+async fn diff_unsupported_type() {
+  let old_source_code = r#"
+export function pick(obj: { [K in "a" | "b"]: number }): void {}
+"#;
+  let new_source_code = r#"
+export function pick(obj: { [K in "a" | "b"]: number }, key?: string): void {}
+"#;
+  let loader = TestLoader::new(vec![
+    ("old.ts".to_string(), old_source_code.to_string()),
+    ("new.ts".to_string(), new_source_code.to_string()),
+  ]);
+  let doc_parser = DocParser::new(loader);
+  let old_entries = doc_parser.parse_with_reexports("old.ts").await.unwrap();
+  let new_entries = doc_parser.parse_with_reexports("new.ts").await.unwrap();
+
+  let changes = super::diff::diff(&old_entries, &new_entries);
+  let expected_json = json!([
+    {
+      "kind": "changed",
+      "name": "pick",
+      "breaking": false,
+      "oldSignature": "function pick(obj: <UNIMPLEMENTED>): void",
+      "newSignature": "function pick(obj: <UNIMPLEMENTED>, key?: string): void"
+    }
+  ]);
+  let actual = serde_json::to_value(&changes).unwrap();
+  assert_eq!(actual, expected_json);
+}
@@ -1,15 +1,20 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::auth_tokens::AuthTokens;
+use crate::blob_store::BlobStore;
 use crate::colors;
+use crate::http_cache::CachePolicy;
 use crate::http_cache::HttpCache;
 use crate::http_util;
 use crate::http_util::create_http_client;
 use crate::http_util::FetchOnceResult;
 use crate::msg;
 use crate::op_error::OpError;
+use crate::registry_mirror::RegistryMirrors;
 use deno_core::ErrBox;
 use deno_core::ModuleSpecifier;
 use futures::future::FutureExt;
 use log::info;
+use percent_encoding::percent_decode_str;
 use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
@@ -22,6 +27,7 @@ use std::result::Result;
 use std::str;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::SystemTime;
 use url::Url;
 
 /// Structure representing local or remote file.
@@ -57,7 +63,8 @@ impl SourceFileCache {
   }
 }
 
-const SUPPORTED_URL_SCHEMES: [&str; 3] = ["http", "https", "file"];
+const SUPPORTED_URL_SCHEMES: [&str; 5] =
+  ["http", "https", "file", "data", "blob"];
 
 #[derive(Clone)]
 pub struct SourceFileFetcher {
@@ -66,9 +73,13 @@ pub struct SourceFileFetcher {
   use_disk_cache: bool,
   no_remote: bool,
   cached_only: bool,
+  cache_policy: CachePolicy,
+  registry_mirrors: RegistryMirrors,
   http_client: reqwest::Client,
+  auth_tokens: AuthTokens,
   // This field is public only to expose it's location
   pub http_cache: HttpCache,
+  pub blob_store: BlobStore,
 }
 
 impl SourceFileFetcher {
@@ -78,6 +89,8 @@ impl SourceFileFetcher {
     cache_blacklist: Vec<String>,
     no_remote: bool,
     cached_only: bool,
+    cache_policy: CachePolicy,
+    registry_mirrors: RegistryMirrors,
     ca_file: Option<String>,
   ) -> Result<Self, ErrBox> {
     let file_fetcher = Self {
@@ -87,7 +100,11 @@ impl SourceFileFetcher {
       use_disk_cache,
       no_remote,
       cached_only,
+      cache_policy,
+      registry_mirrors,
       http_client: create_http_client(ca_file)?,
+      auth_tokens: AuthTokens::from_env(),
+      blob_store: BlobStore::default(),
     };
 
     Ok(file_fetcher)
@@ -131,6 +148,26 @@ impl SourceFileFetcher {
     None
   }
 
+  /// Returns true if fetching `specifier` will go to the network.
+  pub fn needs_download(&self, specifier: &ModuleSpecifier) -> bool {
+    let url = specifier.as_url();
+    match url.scheme() {
+      "http" | "https" => {}
+      _ => return false,
+    }
+    if self.no_remote
+      || self.source_file_cache.get(specifier.to_string()).is_some()
+    {
+      return false;
+    }
+    !self.use_disk_cache
+      || check_cache_blacklist(url, &self.cache_blacklist)
+      || self.http_cache.get_metadata(url).is_err()
+      || (self.cache_policy == CachePolicy::Http
+        && !self.cached_only
+        && !self.is_cache_fresh(url))
+  }
+
   /// Save a given source file into cache.
   /// Allows injection of files that normally would not present
   /// in filesystem.
@@ -232,7 +269,11 @@ impl SourceFileFetcher {
       return self.fetch_local_file(&module_url).map(Some);
     }
 
-    self.fetch_cached_remote_source(&module_url)
+    match url_scheme {
+      "data" => self.fetch_data_url(&module_url).map(Some),
+      "blob" => self.fetch_blob_url(&module_url).map(Some),
+      _ => self.fetch_cached_remote_source(&module_url),
+    }
   }
 
   /// This is main method that is responsible for fetching local or remote files.
@@ -262,6 +303,13 @@ impl SourceFileFetcher {
       return self.fetch_local_file(&module_url);
     }
 
+    // Data and blob URLs carry their contents, so they are never cached.
+    match url_scheme {
+      "data" => return self.fetch_data_url(&module_url),
+      "blob" => return self.fetch_blob_url(&module_url),
+      _ => {}
+    }
+
     // The file is remote, fail if `no_remote` is true.
     if no_remote {
       let e = std::io::Error::new(
@@ -309,6 +357,52 @@ impl SourceFileFetcher {
     })
   }
 
+  /// Decodes the contents of a `data:` URL, which are either base64 or
+  /// percent-encoded.
+  fn fetch_data_url(&self, module_url: &Url) -> Result<SourceFile, ErrBox> {
+    let (content_type, source_code) = decode_data_url(module_url)?;
+    Ok(self.inline_source_file(module_url, &content_type, source_code))
+  }
+
+  /// Looks up the contents of a `blob:` URL created with
+  /// `URL.createObjectURL()`.
+  fn fetch_blob_url(&self, module_url: &Url) -> Result<SourceFile, ErrBox> {
+    let blob = self.blob_store.get(module_url.as_str()).ok_or_else(|| {
+      std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("Blob URL not found: \"{}\"", module_url),
+      )
+    })?;
+    Ok(self.inline_source_file(module_url, &blob.media_type, blob.data))
+  }
+
+  fn inline_source_file(
+    &self,
+    module_url: &Url,
+    content_type: &str,
+    source_code: Vec<u8>,
+  ) -> SourceFile {
+    let content_type = if content_type.is_empty() {
+      None
+    } else {
+      Some(content_type)
+    };
+    let media_type = map_content_type(Path::new(""), content_type);
+    let types_url = match media_type {
+      msg::MediaType::JavaScript | msg::MediaType::JSX => {
+        get_types_url(module_url, &source_code, None)
+      }
+      _ => None,
+    };
+    SourceFile {
+      url: module_url.clone(),
+      filename: PathBuf::from(module_url.as_str()),
+      media_type,
+      source_code,
+      types_url,
+    }
+  }
+
   /// Fetch cached remote file.
   ///
   /// This is a recursive operation if source file has redirections.
@@ -380,6 +474,31 @@ impl SourceFileFetcher {
     }))
   }
 
+  /// Checks whether the cached file for `module_url`, and every cached
+  /// redirect leading to it, is fresh according to its caching headers.
+  fn is_cache_fresh(&self, module_url: &Url) -> bool {
+    let now = SystemTime::now();
+    let mut url = module_url.clone();
+    // Same limit as the number of redirects followed when fetching.
+    for _ in 0..=10 {
+      let metadata = match self.http_cache.get_metadata(&url) {
+        Ok(metadata) => metadata,
+        Err(_) => return false,
+      };
+      if !metadata.is_fresh(now) {
+        return false;
+      }
+      match metadata.headers.get("location") {
+        Some(location) => match url.join(location) {
+          Ok(redirect_url) => url = redirect_url,
+          Err(_) => return false,
+        },
+        None => return true,
+      }
+    }
+    false
+  }
+
   /// Asynchronously fetch remote source file specified by the URL following redirects.
   ///
   /// Note that this is a recursive method so it can't be "async", but rather return
@@ -398,8 +517,13 @@ impl SourceFileFetcher {
 
     let is_blacklisted =
       check_cache_blacklist(module_url, self.cache_blacklist.as_ref());
+    // With the HTTP cache policy stale files are revalidated, unless we're
+    // not allowed to go to the network.
+    let is_stale = self.cache_policy == CachePolicy::Http
+      && !cached_only
+      && !self.is_cache_fresh(module_url);
     // First try local cache
-    if use_disk_cache && !is_blacklisted {
+    if use_disk_cache && !is_blacklisted && !is_stale {
       match self.fetch_cached_remote_source(&module_url) {
         Ok(Some(source_file)) => {
           return futures::future::ok(source_file).boxed_local();
@@ -437,22 +561,50 @@ impl SourceFileFetcher {
 
     let dir = self.clone();
     let module_url = module_url.clone();
-    let module_etag = match self.http_cache.get(&module_url) {
-      Ok((_, headers)) => headers.get("etag").map(String::from),
-      Err(_) => None,
-    };
+    let (module_etag, module_last_modified) =
+      match self.http_cache.get(&module_url) {
+        Ok((_, headers)) => {
+          let etag = headers.get("etag").map(String::from);
+          let last_modified = headers
+            .get("last-modified")
+            .filter(|_| self.cache_policy == CachePolicy::Http)
+            .map(String::from);
+          (etag, last_modified)
+        }
+        Err(_) => (None, None),
+      };
+    let cache_policy = self.cache_policy;
     let http_client = self.http_client.clone();
+    // The request may go to a mirror, but the file is cached under the
+    // original URL.
+    let fetch_url = self.registry_mirrors.rewrite(&module_url);
+    // Redirects are fetched by a recursive call which looks up the token for
+    // the new URL, so a token is only ever sent to the host it belongs to.
+    let maybe_auth_token = self.auth_tokens.get(&fetch_url);
     // Single pass fetch, either yields code or yields redirect.
     let f = async move {
-      match http_util::fetch_once(http_client, &module_url, module_etag).await?
+      match http_util::fetch_once(
+        http_client,
+        &fetch_url,
+        module_etag,
+        module_last_modified,
+        maybe_auth_token,
+      )
+      .await?
       {
         FetchOnceResult::NotModified => {
+          if cache_policy == CachePolicy::Http {
+            dir.http_cache.mark_revalidated(&module_url)?;
+          }
           let source_file =
             dir.fetch_cached_remote_source(&module_url)?.unwrap();
 
           Ok(source_file)
         }
-        FetchOnceResult::Redirect(new_module_url, headers) => {
+        FetchOnceResult::Redirect(new_module_url, mut headers) => {
+          // Redirects within a mirror are recorded under the original URLs.
+          let new_module_url = dir.registry_mirrors.restore(&new_module_url);
+          headers.insert("location".to_string(), new_module_url.to_string());
           // If redirects, update module_name and filename for next looped call.
           dir.http_cache.set(&module_url, headers, &[])?;
 
@@ -608,6 +760,53 @@ fn get_types_url(
   }
 }
 
+/// Splits a `data:` URL into its media type and decoded contents, see
+/// https://fetch.spec.whatwg.org/#data-urls. A missing media type defaults to
+/// `text/plain`.
+fn decode_data_url(url: &Url) -> Result<(String, Vec<u8>), ErrBox> {
+  let url_str = url.as_str();
+  let body = &url_str["data:".len()..];
+  let comma = body.find(',').ok_or_else(|| {
+    OpError::uri_error(format!("Malformed data URL: \"{}\"", url_str))
+  })?;
+  let (header, data) = (&body[..comma], &body[comma + 1..]);
+
+  let mut params: Vec<&str> = header.split(';').map(str::trim).collect();
+  let is_base64 = params
+    .last()
+    .map(|p| p.eq_ignore_ascii_case("base64"))
+    .unwrap_or(false);
+  if is_base64 {
+    params.pop();
+  }
+  let media_type = if params.is_empty() || params[0].is_empty() {
+    "text/plain".to_string()
+  } else {
+    percent_decode_str(&params.join(";"))
+      .decode_utf8_lossy()
+      .to_string()
+  };
+
+  let data = percent_decode_str(data).collect::<Vec<u8>>();
+  let data = if is_base64 {
+    // Whitespace is allowed in base64 encoded data URLs.
+    let data: Vec<u8> = data
+      .into_iter()
+      .filter(|b| !b.is_ascii_whitespace())
+      .collect();
+    base64::decode(&data).map_err(|e| {
+      OpError::uri_error(format!(
+        "Invalid base64 in data URL \"{}\": {}",
+        url_str, e
+      ))
+    })?
+  } else {
+    data
+  };
+
+  Ok((media_type, data))
+}
+
 fn filter_shebang(bytes: Vec<u8>) -> Vec<u8> {
   let string = str::from_utf8(&bytes).unwrap();
   if let Some(i) = string.find('\n') {
@@ -657,6 +856,7 @@ pub struct SourceCodeHeaders {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::blob_store::Blob;
   use tempfile::TempDir;
 
   fn setup_file_fetcher(dir_path: &Path) -> SourceFileFetcher {
@@ -666,6 +866,8 @@ mod tests {
       vec![],
       false,
       false,
+      CachePolicy::Default,
+      RegistryMirrors::default(),
       None,
     )
     .expect("setup fail")
@@ -1646,6 +1848,155 @@ mod tests {
     drop(http_server_guard);
   }
 
+  #[tokio::test]
+  async fn test_fetch_with_http_cache_policy() {
+    let http_server_guard = crate::test_util::http_server();
+    let temp_dir = TempDir::new().unwrap();
+    let fetcher = SourceFileFetcher::new(
+      HttpCache::new(&temp_dir.path().join("deps")),
+      true,
+      vec![],
+      false,
+      false,
+      CachePolicy::Http,
+      RegistryMirrors::default(),
+      None,
+    )
+    .unwrap();
+    let module_url =
+      Url::parse("http://127.0.0.1:4545/last_modified_script.ts").unwrap();
+
+    let source = fetcher
+      .fetch_remote_source(&module_url, true, false, 1)
+      .await
+      .unwrap();
+    assert_eq!(source.source_code, b"console.log('modified')");
+
+    // The response is still fresh, so the cached file is used as is.
+    let file_name = fetcher.http_cache.get_cache_filename(&module_url);
+    fs::write(&file_name, "changed content").unwrap();
+    let cached_source = fetcher
+      .fetch_remote_source(&module_url, true, false, 1)
+      .await
+      .unwrap();
+    assert_eq!(cached_source.source_code, b"changed content");
+
+    // Once stale, the file is revalidated with "If-Modified-Since" and the
+    // server responds with 304, which makes the cached file fresh again.
+    let mut metadata = fetcher.http_cache.get_metadata(&module_url).unwrap();
+    metadata.stored_at = Some(0);
+    metadata.write(&file_name).unwrap();
+    assert!(!fetcher.is_cache_fresh(&module_url));
+    let cached_source = fetcher
+      .fetch_remote_source(&module_url, true, false, 1)
+      .await
+      .unwrap();
+    assert_eq!(cached_source.source_code, b"changed content");
+    assert!(fetcher.is_cache_fresh(&module_url));
+
+    drop(http_server_guard);
+  }
+
+  #[tokio::test]
+  async fn test_fetch_from_registry_mirror() {
+    let http_server_guard = crate::test_util::http_server();
+    let temp_dir = TempDir::new().unwrap();
+    let registry_mirrors = RegistryMirrors::new(
+      &[(
+        "https://deno.land/mirrored/".to_string(),
+        "http://localhost:4545/cli/tests/".to_string(),
+      )],
+      None,
+    )
+    .unwrap();
+    let fetcher = SourceFileFetcher::new(
+      HttpCache::new(&temp_dir.path().join("deps")),
+      true,
+      vec![],
+      false,
+      false,
+      CachePolicy::Default,
+      registry_mirrors,
+      None,
+    )
+    .unwrap();
+    let module_url =
+      Url::parse("https://deno.land/mirrored/subdir/mod2.ts").unwrap();
+
+    let source = fetcher
+      .fetch_remote_source(&module_url, true, false, 1)
+      .await
+      .unwrap();
+    assert_eq!(source.url, module_url);
+    assert!(String::from_utf8(source.source_code)
+      .unwrap()
+      .contains("printHello"));
+
+    // The file is cached under the original URL only.
+    let mirror_url =
+      Url::parse("http://localhost:4545/cli/tests/subdir/mod2.ts").unwrap();
+    assert!(fetcher.http_cache.get(&module_url).is_ok());
+    assert!(fetcher.http_cache.get(&mirror_url).is_err());
+
+    drop(http_server_guard);
+  }
+
+  #[test]
+  fn test_decode_data_url() {
+    let url =
+      Url::parse("data:application/javascript;base64,ZXhwb3J0IGRlZmF1bHQgNDI7")
+        .unwrap();
+    let (media_type, data) = decode_data_url(&url).unwrap();
+    assert_eq!(media_type, "application/javascript");
+    assert_eq!(data, b"export default 42;");
+
+    let url = Url::parse(
+      "data:text/typescript,export%20const%20a%3A%20number%20%3D%201;",
+    )
+    .unwrap();
+    let (media_type, data) = decode_data_url(&url).unwrap();
+    assert_eq!(media_type, "text/typescript");
+    assert_eq!(data, b"export const a: number = 1;");
+
+    let url = Url::parse("data:,hello").unwrap();
+    let (media_type, data) = decode_data_url(&url).unwrap();
+    assert_eq!(media_type, "text/plain");
+    assert_eq!(data, b"hello");
+
+    let url = Url::parse("data:application/javascript;base64,!!!").unwrap();
+    assert!(decode_data_url(&url).is_err());
+    let url = Url::parse("data:application/javascript").unwrap();
+    assert!(decode_data_url(&url).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_fetch_data_and_blob_urls() {
+    let (_temp_dir, fetcher) = test_setup();
+
+    let specifier = ModuleSpecifier::resolve_url(
+      "data:application/typescript;base64,ZXhwb3J0IGNvbnN0IGE6IG51bWJlciA9IDE7",
+    )
+    .unwrap();
+    let source = fetcher.fetch_source_file(&specifier, None).await.unwrap();
+    assert_eq!(source.source_code, b"export const a: number = 1;");
+    assert_eq!(source.media_type, msg::MediaType::TypeScript);
+    assert_eq!(&source.url, specifier.as_url());
+
+    let url = fetcher.blob_store.insert(Blob {
+      media_type: "application/javascript".to_string(),
+      data: b"export default 42;".to_vec(),
+    });
+    let specifier = ModuleSpecifier::resolve_url(&url).unwrap();
+    let source = fetcher.fetch_source_file(&specifier, None).await.unwrap();
+    assert_eq!(source.source_code, b"export default 42;");
+    assert_eq!(source.media_type, msg::MediaType::JavaScript);
+
+    fetcher.blob_store.remove(&url);
+    let specifier = ModuleSpecifier::resolve_url(&url).unwrap();
+    // The in-process cache still has the module, but a fresh lookup fails.
+    assert!(fetcher.fetch_blob_url(specifier.as_url()).is_err());
+  }
+
   #[test]
   fn test_get_types_url_1() {
     let module_url = Url::parse("https://example.com/mod.js").unwrap();
@@ -1,5 +1,6 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use crate::fs::resolve_from_cwd;
+use crate::http_cache::CachePolicy;
 use clap::App;
 use clap::AppSettings;
 use clap::Arg;
@@ -25,6 +26,8 @@ pub enum DenoSubcommand {
   },
   Doc {
     json: bool,
+    lint: bool,
+    diff: Option<(String, String)>,
     source_file: Option<String>,
     filter: Option<String>,
   },
@@ -92,7 +95,12 @@ pub struct Flags {
   pub cache_blacklist: Vec<String>,
   pub ca_file: Option<String>,
   pub cached_only: bool,
+  pub cache_policy: CachePolicy,
+  pub registry_mirrors: Vec<(String, String)>,
+  pub registry_mirror_config: Option<String>,
   pub config_path: Option<String>,
+  pub cpu_prof: Option<PathBuf>,
+  pub heap_snapshot_signal: Option<String>,
   pub import_map_path: Option<String>,
   pub inspect: Option<SocketAddr>,
   pub inspect_brk: Option<SocketAddr>,
@@ -174,6 +182,9 @@ impl Flags {
 }
 
 static ENV_VARIABLES_HELP: &str = "ENVIRONMENT VARIABLES:
+    DENO_AUTH_TOKENS     A semi-colon separated list of bearer tokens and
+                         hostnames to use when fetching remote modules
+                         (e.g. abc123@deno.land;user:pass@example.com)
     DENO_DIR             Set deno's base directory (defaults to $HOME/.deno)
     DENO_INSTALL_ROOT    Set deno install's output directory
                          (defaults to $HOME/.deno/bin)
@@ -452,6 +463,8 @@ fn cache_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   importmap_arg_parse(flags, matches);
   config_arg_parse(flags, matches);
   no_remote_arg_parse(flags, matches);
+  cache_policy_arg_parse(flags, matches);
+  registry_mirror_args_parse(flags, matches);
   ca_file_arg_parse(flags, matches);
   unstable_arg_parse(flags, matches);
   let files = matches
@@ -487,6 +500,8 @@ fn run_test_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   config_arg_parse(flags, matches);
   v8_flags_arg_parse(flags, matches);
   no_remote_arg_parse(flags, matches);
+  cache_policy_arg_parse(flags, matches);
+  registry_mirror_args_parse(flags, matches);
   permission_args_parse(flags, matches);
   ca_file_arg_parse(flags, matches);
   inspect_arg_parse(flags, matches);
@@ -576,10 +591,18 @@ fn doc_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 
   let source_file = matches.value_of("source_file").map(String::from);
   let json = matches.is_present("json");
+  let lint = matches.is_present("lint");
+  let diff = matches.values_of("diff").map(|mut values| {
+    let old = values.next().unwrap().to_string();
+    let new = values.next().unwrap().to_string();
+    (old, new)
+  });
   let filter = matches.value_of("filter").map(String::from);
   flags.subcommand = DenoSubcommand::Doc {
     source_file,
     json,
+    lint,
+    diff,
     filter,
   };
 }
@@ -689,7 +712,10 @@ The installation root is determined, in order of precedence:
   - DENO_INSTALL_ROOT environment variable
   - $HOME/.deno
 
-These must be added to the path manually if required.")
+These must be added to the path manually if required.
+
+Credentials from DENO_AUTH_TOKENS are read when the executable runs, so they
+are never written to the installed script.")
 }
 
 fn bundle_subcommand<'a, 'b>() -> App<'a, 'b> {
@@ -785,7 +811,7 @@ TypeScript compiler cache: Subdirectory containing TS compiler output.",
 }
 
 fn cache_subcommand<'a, 'b>() -> App<'a, 'b> {
-  SubCommand::with_name("cache")
+  registry_mirror_args(SubCommand::with_name("cache"))
     .arg(reload_arg())
     .arg(lock_arg())
     .arg(lock_write_arg())
@@ -793,6 +819,7 @@ fn cache_subcommand<'a, 'b>() -> App<'a, 'b> {
     .arg(unstable_arg())
     .arg(config_arg())
     .arg(no_remote_arg())
+    .arg(cache_policy_arg())
     .arg(
       Arg::with_name("file")
         .takes_value(true)
@@ -859,6 +886,13 @@ Output documentation in JSON format:
 Target a specific symbol:
     deno doc ./path/to/module.ts MyClass.someField
 
+Check that all exported symbols are documented:
+    deno doc --lint ./path/to/module.ts
+
+Compare public API of two versions of a module:
+    deno doc --diff https://deno.land/std@0.50.0/fs/mod.ts \
+      https://deno.land/std@0.51.0/fs/mod.ts
+
 Show documentation for runtime built-ins:
     deno doc
     deno doc --builtin Deno.Listener",
@@ -870,6 +904,24 @@ Show documentation for runtime built-ins:
         .help("Output documentation in JSON format.")
         .takes_value(false),
     )
+    .arg(
+      Arg::with_name("lint")
+        .long("lint")
+        .help("Report undocumented exports and exit with non-zero code.")
+        .takes_value(false),
+    )
+    .arg(
+      Arg::with_name("diff")
+        .long("diff")
+        .help(
+          "Report API changes between two modules and exit with non-zero \
+          code if any of them are breaking.",
+        )
+        .number_of_values(2)
+        .value_names(&["OLD", "NEW"])
+        .conflicts_with("lint")
+        .conflicts_with("source_file"),
+    )
     // TODO(nayeemrmn): Make `--builtin` a proper option. Blocked by
     // https://github.com/clap-rs/clap/issues/1794. Currently `--builtin` is
     // just a possible value of `source_file` so leading hyphens must be
@@ -882,6 +934,7 @@ Show documentation for runtime built-ins:
         .takes_value(true)
         .required(false)
         .conflicts_with("json")
+        .conflicts_with("lint")
         .conflicts_with("pretty"),
     )
 }
@@ -944,7 +997,7 @@ fn permission_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
 }
 
 fn run_test_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-  permission_args(inspect_args(app))
+  registry_mirror_args(permission_args(inspect_args(app)))
     .arg(importmap_arg())
     .arg(unstable_arg())
     .arg(reload_arg())
@@ -952,6 +1005,7 @@ fn run_test_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
     .arg(lock_arg())
     .arg(lock_write_arg())
     .arg(no_remote_arg())
+    .arg(cache_policy_arg())
     .arg(v8_flags_arg())
     .arg(ca_file_arg())
     .arg(
@@ -1120,6 +1174,45 @@ fn inspect_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         .takes_value(true)
         .validator(inspect_arg_validate),
     )
+    .arg(
+      Arg::with_name("cpu-prof")
+        .long("cpu-prof")
+        .value_name("DIR")
+        .help(
+          "Write a CPU profile of the main thread to DIR on exit \
+          (default: current directory)",
+        )
+        .min_values(0)
+        .max_values(1)
+        .require_equals(true)
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("heap-snapshot-signal")
+        .long("heap-snapshot-signal")
+        .value_name("SIGNAL")
+        .help(
+          "Write a heap snapshot of the main thread to the current directory \
+          when SIGNAL is received (e.g. SIGUSR2)",
+        )
+        .require_equals(true)
+        .takes_value(true)
+        .validator(heap_snapshot_signal_validate),
+    )
+}
+
+#[cfg(unix)]
+fn heap_snapshot_signal_validate(val: String) -> Result<(), String> {
+  use std::str::FromStr;
+  match nix::sys::signal::Signal::from_str(&val) {
+    Ok(_) => Ok(()),
+    Err(_) => Err(format!("Unknown signal: {}", val)),
+  }
+}
+
+#[cfg(not(unix))]
+fn heap_snapshot_signal_validate(_val: String) -> Result<(), String> {
+  Err("--heap-snapshot-signal is only supported on Unix".to_string())
 }
 
 fn inspect_arg_validate(val: String) -> Result<(), String> {
@@ -1149,6 +1242,12 @@ fn inspect_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   } else {
     None
   };
+  if matches.is_present("cpu-prof") {
+    let dir = matches.value_of("cpu-prof").unwrap_or(".");
+    flags.cpu_prof = Some(resolve_from_cwd(Path::new(dir)).unwrap());
+  }
+  flags.heap_snapshot_signal =
+    matches.value_of("heap-snapshot-signal").map(String::from);
 }
 
 fn reload_arg<'a, 'b>() -> Arg<'a, 'b> {
@@ -1234,6 +1333,76 @@ fn no_remote_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   }
 }
 
+fn cache_policy_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("cache-policy")
+    .long("cache-policy")
+    .value_name("POLICY")
+    .help(
+      "Set when cached remote modules are refreshed. 'http' follows the \
+      Cache-Control, Expires and Last-Modified headers",
+    )
+    .takes_value(true)
+    .require_equals(true)
+    .possible_values(&["default", "http"])
+}
+
+fn cache_policy_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  if let Some(cache_policy) = matches.value_of("cache-policy") {
+    flags.cache_policy = cache_policy.parse().unwrap();
+  }
+}
+
+fn registry_mirror_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+  app
+    .arg(
+      Arg::with_name("registry-mirror")
+        .long("registry-mirror")
+        .value_name("FROM=TO")
+        .help("Download remote modules starting with FROM from TO instead")
+        .long_help(
+          "Download remote modules whose URL starts with FROM from the URL with
+that prefix replaced by TO. Modules are still cached and locked under their
+original URL. Can be specified multiple times:
+  --registry-mirror=https://deno.land/std@0.50.0/=https://mirror.internal/std/",
+        )
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .require_equals(true)
+        .validator(registry_mirror_validate),
+    )
+    .arg(
+      Arg::with_name("registry-mirror-config")
+        .long("registry-mirror-config")
+        .value_name("FILE")
+        .help("Load registry mirrors from a JSON file mapping FROM to TO")
+        .takes_value(true),
+    )
+}
+
+fn registry_mirror_validate(val: String) -> Result<(), String> {
+  match val.find('=') {
+    Some(index) if index > 0 && index < val.len() - 1 => Ok(()),
+    _ => Err("Registry mirror must be given as FROM=TO".to_string()),
+  }
+}
+
+fn registry_mirror_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  if let Some(mirrors) = matches.values_of("registry-mirror") {
+    flags.registry_mirrors = mirrors
+      .map(|mirror| {
+        let mut parts = mirror.splitn(2, '=');
+        let from = parts.next().unwrap().to_string();
+        let to = parts.next().unwrap().to_string();
+        (from, to)
+      })
+      .collect();
+  }
+  flags.registry_mirror_config = matches
+    .value_of("registry-mirror-config")
+    .map(ToOwned::to_owned);
+}
+
 fn permission_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   if matches.is_present("allow-read") {
     if matches.value_of("allow-read").is_some() {
@@ -2318,6 +2487,91 @@ mod tests {
     );
   }
 
+  #[test]
+  fn cache_policy() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--cache-policy=http",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        cache_policy: CachePolicy::Http,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "cache",
+      "--cache-policy=http",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache {
+          files: svec!["script.ts"],
+        },
+        cache_policy: CachePolicy::Http,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--cache-policy=never",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn registry_mirror() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--registry-mirror=https://deno.land/std/=https://mirror.internal/std/",
+      "--registry-mirror=https://deno.land/x/=https://mirror.internal/x/",
+      "--registry-mirror-config=mirrors.json",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        registry_mirrors: vec![
+          (
+            "https://deno.land/std/".to_string(),
+            "https://mirror.internal/std/".to_string()
+          ),
+          (
+            "https://deno.land/x/".to_string(),
+            "https://mirror.internal/x/".to_string()
+          ),
+        ],
+        registry_mirror_config: Some("mirrors.json".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "cache",
+      "--registry-mirror=https://deno.land/std/",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn allow_net_whitelist_with_ports() {
     let r = flags_from_vec_safe(svec![
@@ -2631,6 +2885,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Doc {
           json: true,
+          lint: false,
+          diff: None,
           source_file: Some("path/to/module.ts".to_string()),
           filter: None,
         },
@@ -2649,6 +2905,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Doc {
           json: false,
+          lint: false,
+          diff: None,
           source_file: Some("path/to/module.ts".to_string()),
           filter: Some("SomeClass.someField".to_string()),
         },
@@ -2662,6 +2920,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Doc {
           json: false,
+          lint: false,
+          diff: None,
           source_file: None,
           filter: None,
         },
@@ -2676,12 +2936,108 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Doc {
           json: false,
+          lint: false,
+          diff: None,
           source_file: Some("--builtin".to_string()),
           filter: Some("Deno.Listener".to_string()),
         },
         ..Flags::default()
       }
     );
+
+    let r =
+      flags_from_vec_safe(svec!["deno", "doc", "--lint", "path/to/module.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc {
+          json: false,
+          lint: true,
+          diff: None,
+          source_file: Some("path/to/module.ts".to_string()),
+          filter: None,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno", "doc", "--json", "--diff", "old.ts", "new.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc {
+          json: true,
+          lint: false,
+          diff: Some(("old.ts".to_string(), "new.ts".to_string())),
+          source_file: None,
+          filter: None,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn cpu_prof() {
+    let r = flags_from_vec_safe(svec!["deno", "run", "--cpu-prof", "foo.js"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "foo.js".to_string(),
+        },
+        cpu_prof: Some(current_dir().unwrap()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--cpu-prof=profiles",
+      "foo.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "foo.js".to_string(),
+        },
+        cpu_prof: Some(current_dir().unwrap().join("profiles")),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn heap_snapshot_signal() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--heap-snapshot-signal=SIGUSR2",
+      "foo.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "foo.js".to_string(),
+        },
+        heap_snapshot_signal: Some("SIGUSR2".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--heap-snapshot-signal=SIGFOO",
+      "foo.js"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
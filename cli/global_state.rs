@@ -1,18 +1,25 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use crate::deno_dir;
+use crate::file_fetcher::SourceFile;
 use crate::file_fetcher::SourceFileFetcher;
 use crate::flags;
 use crate::http_cache;
+use crate::http_util::create_http_client;
 use crate::lockfile::Lockfile;
 use crate::msg;
+use crate::op_error::OpError;
 use crate::permissions::Permissions;
+use crate::registry_mirror::RegistryMirrors;
+use crate::swc_util;
 use crate::tsc::CompiledModule;
 use crate::tsc::TargetLib;
 use crate::tsc::TsCompiler;
+use crate::wasm;
 use deno_core::ErrBox;
 use deno_core::ModuleSpecifier;
 use std::env;
 use std::ops::Deref;
+use std::str;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -34,6 +41,9 @@ pub struct GlobalStateInner {
   pub file_fetcher: SourceFileFetcher,
   pub ts_compiler: TsCompiler,
   pub lockfile: Option<Mutex<Lockfile>>,
+  /// Client used by `fetch()` when no client is given. Clones share its
+  /// connection pool.
+  pub http_client: reqwest::Client,
   pub compiler_starts: AtomicUsize,
   compile_lock: AsyncMutex<()>,
 }
@@ -59,6 +69,11 @@ impl GlobalState {
       flags.cache_blacklist.clone(),
       flags.no_remote,
       flags.cached_only,
+      flags.cache_policy,
+      RegistryMirrors::new(
+        &flags.registry_mirrors,
+        flags.registry_mirror_config.as_deref(),
+      )?,
       flags.ca_file.clone(),
     )?;
 
@@ -76,6 +91,8 @@ impl GlobalState {
       None
     };
 
+    let http_client = create_http_client(flags.ca_file.clone())?;
+
     let inner = GlobalStateInner {
       dir,
       permissions: Permissions::from_flags(&flags),
@@ -83,6 +100,7 @@ impl GlobalState {
       file_fetcher,
       ts_compiler,
       lockfile,
+      http_client,
       compiler_starts: AtomicUsize::new(0),
       compile_lock: AsyncMutex::new(()),
     };
@@ -109,7 +127,8 @@ impl GlobalState {
     // sanity.
     let compile_lock = self.compile_lock.lock().await;
 
-    let compiled_module = match out.media_type {
+    let media_type = out.media_type;
+    let mut compiled_module = match out.media_type {
       msg::MediaType::TypeScript
       | msg::MediaType::TSX
       | msg::MediaType::JSX => {
@@ -140,12 +159,16 @@ impl GlobalState {
           Ok(CompiledModule {
             code: String::from_utf8(out.source_code)?,
             name: out.url.to_string(),
+            binary: None,
           })
         }
       }
+      msg::MediaType::Json => wrap_json_module(&out),
+      msg::MediaType::Wasm => wasm::compile_wasm_module(&out),
       _ => Ok(CompiledModule {
         code: String::from_utf8(out.source_code)?,
         name: out.url.to_string(),
+        binary: None,
       }),
     }?;
     drop(compile_lock);
@@ -168,6 +191,13 @@ impl GlobalState {
         }
       }
     }
+
+    // JSON import assertions are stripped only after the integrity check, so
+    // the lockfile hashes the module exactly as it was fetched.
+    if media_type == msg::MediaType::JavaScript {
+      compiled_module.code =
+        swc_util::strip_json_import_assertions(&compiled_module.code);
+    }
     Ok(compiled_module)
   }
 
@@ -181,6 +211,31 @@ impl GlobalState {
   }
 }
 
+/// Turns a JSON file into a synthetic ES module whose default export is the
+/// parsed value.
+fn wrap_json_module(
+  source_file: &SourceFile,
+) -> Result<CompiledModule, ErrBox> {
+  let json = str::from_utf8(&source_file.source_code)
+    .map_err(|_| OpError::invalid_utf8())?;
+  // Validate the contents here so that invalid files are reported as JSON
+  // errors instead of syntax errors in the generated module.
+  if let Err(err) = serde_json::from_str::<serde_json::Value>(json) {
+    return Err(
+      OpError::type_error(format!(
+        "Failed to parse JSON module \"{}\": {}",
+        source_file.url, err
+      ))
+      .into(),
+    );
+  }
+  Ok(CompiledModule {
+    code: format!("export default {};", json.trim()),
+    name: source_file.url.to_string(),
+    binary: None,
+  })
+}
+
 #[test]
 fn thread_safe() {
   fn f<S: Send + Sync>(_: S) {}
@@ -194,3 +249,24 @@ fn import_map_given_for_repl() {
     ..flags::Flags::default()
   });
 }
+
+#[test]
+fn test_wrap_json_module() {
+  let url = url::Url::parse("file:///data.json").unwrap();
+  let source_file = SourceFile {
+    url: url.clone(),
+    filename: std::path::PathBuf::from("/data.json"),
+    media_type: msg::MediaType::Json,
+    source_code: b"{ \"a\": [1, 2] }\n".to_vec(),
+    types_url: None,
+  };
+  let compiled_module = wrap_json_module(&source_file).unwrap();
+  assert_eq!(compiled_module.code, "export default { \"a\": [1, 2] };");
+  assert_eq!(compiled_module.name, "file:///data.json");
+
+  let source_file = SourceFile {
+    source_code: b"{ a: 1 }".to_vec(),
+    ..source_file
+  };
+  assert!(wrap_json_module(&source_file).is_err());
+}
@@ -0,0 +1,81 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Takes V8 heap snapshots using the `HeapProfiler` domain of an in-process
+//! inspector session. Snapshots are written as `.heapsnapshot` files which
+//! can be loaded in the Memory tab of Chrome DevTools.
+//!
+//! Snapshots are only taken on request. Writing one when the isolate runs out
+//! of memory needs V8's near-heap-limit callback, which rusty_v8 doesn't
+//! expose yet.
+
+use crate::inspector::DenoInspector;
+use crate::inspector::InspectorSession;
+use deno_core::ErrBox;
+use futures::channel::oneshot;
+use std::cell::RefCell;
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+use std::rc::Rc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// A request to write a heap snapshot, queued by `Deno.writeHeapSnapshot()`.
+/// Snapshots can't be taken while JavaScript is running, so requests are
+/// handled by the worker in between polls of the isolate.
+pub struct HeapSnapshotRequest {
+  pub path: PathBuf,
+  pub sender: oneshot::Sender<Result<(), ErrBox>>,
+}
+
+pub fn write_heap_snapshot(
+  inspector: &mut DenoInspector,
+  path: &Path,
+) -> Result<(), ErrBox> {
+  // The snapshot is delivered in chunks as notifications that are sent
+  // before the response to `takeHeapSnapshot`. Each chunk is written as soon
+  // as it arrives, the first write error is kept and ends the writing.
+  let writer: Rc<RefCell<io::Result<BufWriter<File>>>> =
+    Rc::new(RefCell::new(Ok(BufWriter::new(File::create(path)?))));
+
+  let mut session = InspectorSession::new(inspector);
+  let writer_ = writer.clone();
+  session.set_notification_handler(move |notification| {
+    if notification["method"] != "HeapProfiler.addHeapSnapshotChunk" {
+      return;
+    }
+    let mut writer = writer_.borrow_mut();
+    if let (Ok(file), Some(chunk)) =
+      (writer.as_mut(), notification["params"]["chunk"].as_str())
+    {
+      if let Err(err) = file.write_all(chunk.as_bytes()) {
+        *writer = Err(err);
+      }
+    }
+  });
+  session.post_message(
+    "HeapProfiler.takeHeapSnapshot",
+    Some(json!({ "reportProgress": false })),
+  )?;
+  drop(session);
+
+  let writer = Rc::try_unwrap(writer).ok().unwrap().into_inner();
+  writer?.flush()?;
+  Ok(())
+}
+
+/// Returns path in the current directory that is used for snapshots
+/// triggered by a signal.
+pub fn default_heap_snapshot_path() -> PathBuf {
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap()
+    .as_millis();
+  let file_name = format!("Heap.{}.{}.heapsnapshot", timestamp, process::id());
+  env::current_dir().unwrap().join(file_name)
+}
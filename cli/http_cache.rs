@@ -14,6 +14,10 @@ use std::fs::File;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use url::Url;
 
 /// Turn base of url (scheme, hostname, port) into a valid filename.
@@ -69,6 +73,63 @@ pub fn url_to_filename(url: &Url) -> PathBuf {
   cache_filename
 }
 
+/// Decides whether a cached remote module can be used without going to the
+/// network.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CachePolicy {
+  /// Cached modules are used until `--reload` is passed.
+  Default,
+  /// Cached modules are used only while they are fresh according to the
+  /// caching headers of the response, stale ones are revalidated.
+  Http,
+}
+
+impl Default for CachePolicy {
+  fn default() -> Self {
+    CachePolicy::Default
+  }
+}
+
+impl FromStr for CachePolicy {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "default" => Ok(CachePolicy::Default),
+      "http" => Ok(CachePolicy::Http),
+      _ => Err(format!("Unknown cache policy: {}", s)),
+    }
+  }
+}
+
+/// Directives of a `Cache-Control` header that affect freshness.
+#[derive(Debug, Default, PartialEq)]
+struct CacheControl {
+  max_age: Option<u64>,
+  no_cache: bool,
+  immutable: bool,
+}
+
+impl CacheControl {
+  fn parse(value: &str) -> Self {
+    let mut cache_control = CacheControl::default();
+    for directive in value.split(',') {
+      let mut parts = directive.trim().splitn(2, '=');
+      let name = parts.next().unwrap().to_lowercase();
+      let value = parts.next().map(|v| v.trim_matches('"'));
+      match name.as_str() {
+        "max-age" => cache_control.max_age = value.and_then(|v| v.parse().ok()),
+        // Modules are always written to disk, so "no-store" can only mean
+        // that the cached copy must not be reused without revalidation.
+        "no-cache" | "no-store" => cache_control.no_cache = true,
+        "immutable" => cache_control.immutable = true,
+        _ => {}
+      }
+    }
+    cache_control
+  }
+}
+
 #[derive(Clone)]
 pub struct HttpCache {
   pub location: PathBuf,
@@ -78,6 +139,10 @@ pub struct HttpCache {
 pub struct Metadata {
   pub headers: HeadersMap,
   pub url: String,
+  /// Seconds since the UNIX epoch at which the response was stored or last
+  /// revalidated. Missing in metadata written by older versions.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub stored_at: Option<u64>,
 }
 
 impl Metadata {
@@ -99,6 +164,57 @@ impl Metadata {
   pub fn filename(cache_filename: &Path) -> PathBuf {
     cache_filename.with_extension("metadata.json")
   }
+
+  /// Checks whether the cached response can be used at `now` without
+  /// revalidation, following RFC 7234 section 4.2.
+  pub fn is_fresh(&self, now: SystemTime) -> bool {
+    let stored_at = match self.stored_at {
+      Some(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+      None => return false,
+    };
+
+    let cache_control = self
+      .headers
+      .get("cache-control")
+      .map(|v| CacheControl::parse(v))
+      .unwrap_or_default();
+    if cache_control.no_cache {
+      return false;
+    }
+    if cache_control.immutable {
+      return true;
+    }
+
+    let date = self.header_date("date").unwrap_or(stored_at);
+    let freshness_lifetime = if let Some(max_age) = cache_control.max_age {
+      Duration::from_secs(max_age)
+    } else if let Some(expires) = self.header_date("expires") {
+      expires.duration_since(date).unwrap_or_default()
+    } else if let Some(last_modified) = self.header_date("last-modified") {
+      // Heuristic freshness, 10% of the time since the last modification.
+      date.duration_since(last_modified).unwrap_or_default() / 10
+    } else {
+      return false;
+    };
+
+    let age_header = self
+      .headers
+      .get("age")
+      .and_then(|v| v.parse().ok())
+      .map(Duration::from_secs)
+      .unwrap_or_default();
+    let current_age =
+      now.duration_since(stored_at).unwrap_or_default() + age_header;
+
+    current_age < freshness_lifetime
+  }
+
+  fn header_date(&self, name: &str) -> Option<SystemTime> {
+    self
+      .headers
+      .get(name)
+      .and_then(|v| httpdate::parse_http_date(v).ok())
+  }
 }
 
 impl HttpCache {
@@ -167,9 +283,26 @@ impl HttpCache {
     let metadata = Metadata {
       url: url.to_string(),
       headers: headers_map,
+      stored_at: Some(now_secs()),
     };
     metadata.write(&cache_filename)
   }
+
+  /// Records that the cached response for `url` was successfully revalidated
+  /// with the server, which makes it fresh again.
+  pub fn mark_revalidated(&self, url: &Url) -> Result<(), ErrBox> {
+    let cache_filename = self.location.join(url_to_filename(url));
+    let mut metadata = Metadata::read(&cache_filename)?;
+    metadata.stored_at = Some(now_secs());
+    metadata.write(&cache_filename)
+  }
+}
+
+fn now_secs() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap()
+    .as_secs()
 }
 
 #[cfg(test)]
@@ -219,6 +352,90 @@ mod tests {
     drop(dir);
   }
 
+  fn metadata(headers: &[(&str, &str)], stored_at: u64) -> Metadata {
+    Metadata {
+      url: "https://deno.land/x/welcome.ts".to_string(),
+      headers: headers
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect(),
+      stored_at: Some(stored_at),
+    }
+  }
+
+  #[test]
+  fn test_is_fresh() {
+    let stored_at = 1_000_000;
+    let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(stored_at + secs);
+
+    let m = metadata(&[("cache-control", "public, max-age=60")], stored_at);
+    assert!(m.is_fresh(at(59)));
+    assert!(!m.is_fresh(at(60)));
+
+    let m =
+      metadata(&[("cache-control", "max-age=60"), ("age", "30")], stored_at);
+    assert!(m.is_fresh(at(29)));
+    assert!(!m.is_fresh(at(30)));
+
+    let m = metadata(&[("cache-control", "no-cache, max-age=60")], stored_at);
+    assert!(!m.is_fresh(at(0)));
+
+    let m = metadata(&[("cache-control", "max-age=0, immutable")], stored_at);
+    assert!(m.is_fresh(at(1_000_000)));
+
+    let m = metadata(
+      &[
+        ("date", "Mon, 12 Jan 1970 13:46:40 GMT"),
+        ("expires", "Mon, 12 Jan 1970 13:48:20 GMT"),
+      ],
+      stored_at,
+    );
+    assert!(m.is_fresh(at(99)));
+    assert!(!m.is_fresh(at(100)));
+
+    let m = metadata(
+      &[
+        ("date", "Mon, 12 Jan 1970 13:46:40 GMT"),
+        ("last-modified", "Mon, 12 Jan 1970 13:30:00 GMT"),
+      ],
+      stored_at,
+    );
+    assert!(m.is_fresh(at(99)));
+    assert!(!m.is_fresh(at(100)));
+
+    let m = metadata(&[("etag", "33a64df551425fcc55e")], stored_at);
+    assert!(!m.is_fresh(at(0)));
+
+    let mut m = metadata(&[("cache-control", "max-age=60")], stored_at);
+    m.stored_at = None;
+    assert!(!m.is_fresh(at(0)));
+  }
+
+  #[test]
+  fn test_mark_revalidated() {
+    let dir = TempDir::new().unwrap();
+    let cache = HttpCache::new(dir.path());
+    let url = Url::parse("https://deno.land/x/welcome.ts").unwrap();
+    let mut headers = HashMap::new();
+    headers.insert("cache-control".to_string(), "max-age=60".to_string());
+    cache.set(&url, headers, b"Hello world").unwrap();
+
+    let cache_filename = cache.get_cache_filename(&url);
+    let mut metadata = Metadata::read(&cache_filename).unwrap();
+    metadata.stored_at = Some(0);
+    metadata.write(&cache_filename).unwrap();
+    assert!(!cache
+      .get_metadata(&url)
+      .unwrap()
+      .is_fresh(SystemTime::now()));
+
+    cache.mark_revalidated(&url).unwrap();
+    assert!(cache
+      .get_metadata(&url)
+      .unwrap()
+      .is_fresh(SystemTime::now()));
+  }
+
   #[test]
   fn test_url_to_filename() {
     let test_cases = [
@@ -4,8 +4,10 @@
 //! https://chromedevtools.github.io/devtools-protocol/
 //! https://hyperandroid.com/2020/02/12/v8-inspector-from-an-embedder-standpoint/
 
+use crate::op_error::OpError;
 use core::convert::Infallible as Never; // Alias for the future `!` type.
 use deno_core::v8;
+use deno_core::ErrBox;
 use futures::channel::mpsc;
 use futures::channel::mpsc::UnboundedReceiver;
 use futures::channel::mpsc::UnboundedSender;
@@ -303,7 +305,7 @@ pub struct DenoInspector {
   flags: RefCell<InspectorFlags>,
   waker: Arc<InspectorWaker>,
   _canary_tx: oneshot::Sender<Never>,
-  pub debugger_url: String,
+  pub debugger_url: Option<String>,
 }
 
 impl Deref for DenoInspector {
@@ -371,9 +373,12 @@ impl Future for DenoInspector {
 impl DenoInspector {
   const CONTEXT_GROUP_ID: i32 = 1;
 
+  /// Creates an inspector for the isolate. When `host` is `None` no
+  /// websocket server is started and the inspector can only be used through
+  /// an in-process `InspectorSession`.
   pub fn new(
     isolate: &mut deno_core::CoreIsolate,
-    host: SocketAddr,
+    host: Option<SocketAddr>,
     wait_for_debugger: bool,
   ) -> Box<Self> {
     let deno_core::CoreIsolate {
@@ -390,13 +395,13 @@ impl DenoInspector {
       mpsc::unbounded::<WebSocketProxy>();
     let (canary_tx, canary_rx) = oneshot::channel::<Never>();
 
-    let info = InspectorInfo {
+    let maybe_info = host.map(|host| InspectorInfo {
       host,
       uuid: Uuid::new_v4(),
       thread_name: thread::current().name().map(|n| n.to_owned()),
       new_websocket_tx,
       canary_rx,
-    };
+    });
 
     // Create DenoInspector instance.
     let mut self_ = new_box_with(|self_ptr| {
@@ -416,7 +421,9 @@ impl DenoInspector {
         flags,
         waker,
         _canary_tx: canary_tx,
-        debugger_url: info.get_websocket_debugger_url(),
+        debugger_url: maybe_info
+          .as_ref()
+          .map(|info| info.get_websocket_debugger_url()),
       }
     });
 
@@ -429,7 +436,9 @@ impl DenoInspector {
     // Note: poll_sessions() might block if we need to wait for a
     // debugger front-end to connect. Therefore the server thread must to be
     // nofified *before* polling.
-    InspectorServer::register_inspector(info);
+    if let Some(info) = maybe_info {
+      InspectorServer::register_inspector(info);
+    }
 
     // Poll the session handler so we will get notified whenever there is
     // new_incoming debugger activity.
@@ -794,6 +803,132 @@ impl Future for DenoInspectorSession {
   }
 }
 
+/// An inspector session that is driven from within Deno instead of a remote
+/// DevTools front-end. Protocol messages are dispatched synchronously and
+/// their responses are collected, so the result is available as soon as
+/// `post_message()` returns.
+pub struct InspectorSession {
+  v8_channel: v8::inspector::ChannelBase,
+  v8_session: v8::UniqueRef<v8::inspector::V8InspectorSession>,
+  responses: HashMap<i32, serde_json::Value>,
+  notification_handler: Option<Box<dyn FnMut(serde_json::Value)>>,
+  next_message_id: i32,
+}
+
+impl Deref for InspectorSession {
+  type Target = v8::inspector::V8InspectorSession;
+  fn deref(&self) -> &Self::Target {
+    &self.v8_session
+  }
+}
+
+impl DerefMut for InspectorSession {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.v8_session
+  }
+}
+
+impl InspectorSession {
+  const CONTEXT_GROUP_ID: i32 = 1;
+
+  pub fn new(inspector: &mut DenoInspector) -> Box<Self> {
+    let inspector_ptr = inspector as *mut DenoInspector;
+    new_box_with(move |self_ptr| {
+      let v8_channel = v8::inspector::ChannelBase::new::<Self>();
+      let v8_session = unsafe { &mut *inspector_ptr }.connect(
+        Self::CONTEXT_GROUP_ID,
+        unsafe { &mut *self_ptr },
+        v8::inspector::StringView::empty(),
+      );
+
+      Self {
+        v8_channel,
+        v8_session,
+        responses: HashMap::new(),
+        notification_handler: None,
+        next_message_id: 1,
+      }
+    })
+  }
+
+  /// Dispatches a protocol message to V8 and returns the `result` field of
+  /// the response.
+  pub fn post_message(
+    &mut self,
+    method: &str,
+    params: Option<serde_json::Value>,
+  ) -> Result<serde_json::Value, ErrBox> {
+    let id = self.next_message_id;
+    self.next_message_id += 1;
+
+    let mut message = json!({
+      "id": id,
+      "method": method,
+    });
+    if let Some(params) = params {
+      message["params"] = params;
+    }
+    let raw_message = serde_json::to_string(&message).unwrap();
+    let raw_message = v8::inspector::StringView::from(raw_message.as_bytes());
+    self.dispatch_protocol_message(raw_message);
+
+    let mut response = self.responses.remove(&id).ok_or_else(|| {
+      OpError::other(format!("No response to inspector message {}", method))
+    })?;
+    if let Some(error) = response.get("error") {
+      return Err(
+        OpError::other(format!(
+          "Inspector message {} failed: {}",
+          method, error["message"]
+        ))
+        .into(),
+      );
+    }
+    Ok(response["result"].take())
+  }
+
+  /// Sets the function that is called with each notification as soon as V8
+  /// sends it. Notifications are dropped while no handler is set.
+  pub fn set_notification_handler(
+    &mut self,
+    handler: impl FnMut(serde_json::Value) + 'static,
+  ) {
+    self.notification_handler = Some(Box::new(handler));
+  }
+}
+
+impl v8::inspector::ChannelImpl for InspectorSession {
+  fn base(&self) -> &v8::inspector::ChannelBase {
+    &self.v8_channel
+  }
+
+  fn base_mut(&mut self) -> &mut v8::inspector::ChannelBase {
+    &mut self.v8_channel
+  }
+
+  fn send_response(
+    &mut self,
+    call_id: i32,
+    message: v8::UniquePtr<v8::inspector::StringBuffer>,
+  ) {
+    let message = message.unwrap().string().to_string();
+    let message = serde_json::from_str(&message).unwrap();
+    self.responses.insert(call_id, message);
+  }
+
+  fn send_notification(
+    &mut self,
+    message: v8::UniquePtr<v8::inspector::StringBuffer>,
+  ) {
+    if let Some(handler) = self.notification_handler.as_mut() {
+      let message = message.unwrap().string().to_string();
+      handler(serde_json::from_str(&message).unwrap());
+    }
+  }
+
+  fn flush_protocol_notifications(&mut self) {}
+}
+
 fn new_box_with<T>(new_fn: impl FnOnce(*mut T) -> T) -> Box<T> {
   let b = Box::new(MaybeUninit::<T>::uninit());
   let p = Box::into_raw(b) as *mut T;
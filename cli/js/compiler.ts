@@ -210,6 +210,8 @@ function getExtension(fileName: string, mediaType: MediaType): ts.Extension {
       return fileName.endsWith(".d.ts") ? ts.Extension.Dts : ts.Extension.Ts;
     case MediaType.TSX:
       return ts.Extension.Tsx;
+    case MediaType.Json:
+      return ts.Extension.Json;
     case MediaType.Wasm:
       // Custom marker for Wasm type.
       return ts.Extension.Js;
@@ -513,10 +515,18 @@ class Host implements ts.CompilerHost {
           ? sourceFile.filename
           : fileName;
 
+        // The URL of a JSON module doesn't have to end with `.json`, so the
+        // kind can't be inferred from the file name.
+        const scriptKind =
+          sourceFile.mediaType === MediaType.Json
+            ? ts.ScriptKind.JSON
+            : undefined;
         sourceFile.tsSourceFile = ts.createSourceFile(
           tsSourceFileName,
           sourceFile.sourceCode,
-          languageVersion
+          languageVersion,
+          false,
+          scriptKind
         );
         delete sourceFile.sourceCode;
       }
@@ -667,6 +677,8 @@ function getMediaType(filename: string): MediaType {
       return MediaType.TypeScript;
     case "tsx":
       return MediaType.TSX;
+    case "json":
+      return MediaType.Json;
     case "wasm":
       return MediaType.Wasm;
     default:
@@ -13,9 +13,18 @@ export { signal, signals, Signal, SignalStream } from "./signals.ts";
 export { setRaw } from "./ops/tty.ts";
 export { utimeSync, utime } from "./ops/fs/utime.ts";
 export { ShutdownMode, shutdown } from "./net.ts";
-export { listen, listenDatagram, connect } from "./net_unstable.ts";
+export {
+  listen,
+  listenDatagram,
+  connect,
+  resolveDns,
+} from "./net_unstable.ts";
 export { startTls } from "./tls.ts";
 export { kill } from "./ops/process.ts";
+export { runSync } from "./process.ts";
+export { writeHeapSnapshot } from "./ops/runtime.ts";
+export { createHttpClient, HttpClient } from "./web/fetch.ts";
+export { serveHttp } from "./http.ts";
 export {
   permissions,
   PermissionName,
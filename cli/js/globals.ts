@@ -16,11 +16,13 @@ import * as eventTarget from "./web/event_target.ts";
 import * as formData from "./web/form_data.ts";
 import * as fetchTypes from "./web/fetch.ts";
 import * as headers from "./web/headers.ts";
+import * as messageChannel from "./web/message_channel.ts";
 import * as textEncoding from "./web/text_encoding.ts";
 import * as timers from "./web/timers.ts";
 import * as url from "./web/url.ts";
 import * as urlSearchParams from "./web/url_search_params.ts";
 import * as workers from "./web/workers.ts";
+import * as webSocket from "./web/websocket.ts";
 import * as performanceUtil from "./web/performance.ts";
 import * as request from "./web/request.ts";
 import * as readableStream from "./web/streams/readable_stream.ts";
@@ -165,7 +167,7 @@ declare global {
   // Called in compiler
   var close: () => void;
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
-  var postMessage: (msg: any) => void;
+  var postMessage: (msg: any, transferOrOptions?: any) => void;
   /* eslint-enable */
 }
 
@@ -219,6 +221,8 @@ export const windowOrWorkerGlobalScopeProperties = {
   AbortController: nonEnumerable(abortController.AbortControllerImpl),
   AbortSignal: nonEnumerable(abortSignal.AbortSignalImpl),
   Blob: nonEnumerable(blob.DenoBlob),
+  BroadcastChannel: nonEnumerable(messageChannel.BroadcastChannelImpl),
+  CloseEvent: nonEnumerable(webSocket.CloseEventImpl),
   ByteLengthQueuingStrategy: nonEnumerable(
     queuingStrategy.ByteLengthQueuingStrategyImpl
   ),
@@ -232,6 +236,8 @@ export const windowOrWorkerGlobalScopeProperties = {
   URL: nonEnumerable(url.URLImpl),
   URLSearchParams: nonEnumerable(urlSearchParams.URLSearchParamsImpl),
   Headers: nonEnumerable(headers.HeadersImpl),
+  MessageChannel: nonEnumerable(messageChannel.MessageChannelImpl),
+  MessagePort: nonEnumerable(messageChannel.MessagePortImpl),
   FormData: nonEnumerable(formData.FormDataImpl),
   TextEncoder: nonEnumerable(textEncoding.TextEncoder),
   TextDecoder: nonEnumerable(textEncoding.TextDecoder),
@@ -240,6 +246,7 @@ export const windowOrWorkerGlobalScopeProperties = {
   Request: nonEnumerable(request.Request),
   Response: nonEnumerable(fetchTypes.Response),
   performance: writable(new performanceUtil.Performance()),
+  WebSocket: nonEnumerable(webSocket.WebSocketImpl),
   Worker: nonEnumerable(workers.WorkerImpl),
   WritableStream: nonEnumerable(writableStream.WritableStreamImpl),
 };
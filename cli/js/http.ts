@@ -0,0 +1,183 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { errors } from "./errors.ts";
+import { Reader } from "./io.ts";
+import { Conn, ConnImpl } from "./net.ts";
+import * as httpOps from "./ops/http.ts";
+import { read } from "./ops/io.ts";
+import { close } from "./ops/resources.ts";
+import { bodyChunks, closeIfOpen, StreamBody } from "./web/fetch.ts";
+import { Request } from "./web/request.ts";
+import { ReadableStreamImpl } from "./web/streams/readable_stream.ts";
+import { TextEncoder } from "./web/text_encoding.ts";
+
+// Size of the buffer request bodies are read into.
+const REQUEST_BODY_BUFFER_SIZE = 64 * 1024;
+
+export interface HttpResponse {
+  status?: number;
+  headers?: HeadersInit;
+  body?: Uint8Array | string | Reader | ReadableStream<Uint8Array> | null;
+}
+
+export interface RequestEvent {
+  readonly request: Request;
+  respondWith(response: HttpResponse | Promise<HttpResponse>): Promise<void>;
+  upgradeWebSocket(): Promise<Conn>;
+}
+
+export interface HttpConn extends AsyncIterable<RequestEvent> {
+  readonly rid: number;
+  nextRequest(): Promise<RequestEvent | null>;
+  close(): void;
+  [Symbol.asyncIterator](): AsyncIterableIterator<RequestEvent>;
+}
+
+function requestBodyStream(rid: number): ReadableStreamImpl<Uint8Array> {
+  return new ReadableStreamImpl<Uint8Array>({
+    async pull(controller): Promise<void> {
+      const buf = new Uint8Array(REQUEST_BODY_BUFFER_SIZE);
+      let nread: number | null;
+      try {
+        nread = await read(rid, buf);
+      } catch (e) {
+        closeIfOpen(rid);
+        controller.error(e);
+        return;
+      }
+      if (nread === null) {
+        closeIfOpen(rid);
+        controller.close();
+      } else {
+        controller.enqueue(buf.subarray(0, nread));
+      }
+    },
+    cancel(): void {
+      closeIfOpen(rid);
+    },
+  });
+}
+
+class RequestEventImpl implements RequestEvent {
+  #requestRid: number;
+  #bodyRid: number | null;
+  #conn: Conn;
+  #handled = false;
+
+  constructor(
+    readonly request: Request,
+    requestRid: number,
+    bodyRid: number | null,
+    conn: Conn
+  ) {
+    this.#requestRid = requestRid;
+    this.#bodyRid = bodyRid;
+    this.#conn = conn;
+  }
+
+  #handle = (): void => {
+    if (this.#handled) {
+      throw new errors.Busy("The request has already been responded to.");
+    }
+    this.#handled = true;
+  };
+
+  async respondWith(
+    response: HttpResponse | Promise<HttpResponse>
+  ): Promise<void> {
+    this.#handle();
+    const { status = 200, headers, body } = await response;
+    const headerList = headers ? Array.from(new Headers(headers)) : [];
+
+    let bytes: Uint8Array | undefined;
+    let stream: StreamBody | undefined;
+    if (typeof body === "string") {
+      bytes = new TextEncoder().encode(body);
+    } else if (body instanceof Uint8Array) {
+      bytes = body;
+    } else if (body != null) {
+      stream = body;
+    }
+
+    const { bodyRid } = httpOps.respond(
+      {
+        rid: this.#requestRid,
+        status,
+        headers: headerList,
+        hasBody: stream !== undefined,
+      },
+      bytes
+    );
+    try {
+      if (bodyRid !== null) {
+        try {
+          for await (const chunk of bodyChunks(stream!)) {
+            await httpOps.responseWrite(bodyRid, chunk);
+          }
+        } finally {
+          closeIfOpen(bodyRid);
+        }
+      }
+    } finally {
+      if (this.#bodyRid !== null) {
+        closeIfOpen(this.#bodyRid);
+      }
+    }
+  }
+
+  async upgradeWebSocket(): Promise<Conn> {
+    this.#handle();
+    const rid = await httpOps.upgradeWebSocket(this.#requestRid);
+    return new ConnImpl(rid, this.#conn.remoteAddr, this.#conn.localAddr);
+  }
+}
+
+class HttpConnImpl implements HttpConn {
+  #conn: Conn;
+
+  constructor(readonly rid: number, conn: Conn) {
+    this.#conn = conn;
+  }
+
+  async nextRequest(): Promise<RequestEvent | null> {
+    let request: httpOps.HttpRequest | null;
+    try {
+      ({ request } = await httpOps.nextRequest(this.rid));
+    } catch (e) {
+      if (e instanceof errors.BadResource) {
+        return null;
+      }
+      throw e;
+    }
+    if (request === null) {
+      return null;
+    }
+
+    const { requestRid, bodyRid, method, url, headers } = request;
+    const body = bodyRid === null ? undefined : requestBodyStream(bodyRid);
+    return new RequestEventImpl(
+      new Request(url, { method, headers, body }),
+      requestRid,
+      bodyRid,
+      this.#conn
+    );
+  }
+
+  close(): void {
+    close(this.rid);
+  }
+
+  async *[Symbol.asyncIterator](): AsyncIterableIterator<RequestEvent> {
+    while (true) {
+      const event = await this.nextRequest();
+      if (event === null) {
+        return;
+      }
+      yield event;
+    }
+  }
+}
+
+export function serveHttp(conn: Conn): HttpConn {
+  const rid = httpOps.serveHttp(conn.rid);
+  return new HttpConnImpl(rid, conn);
+}
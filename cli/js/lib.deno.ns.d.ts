@@ -1429,13 +1429,33 @@ declare namespace Deno {
     transport?: "tcp";
   }
 
+  export interface TlsConn extends Conn {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The application protocol negotiated with ALPN, or `null` if none was. */
+    readonly alpnProtocol: string | null;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The certificate chain presented by the peer as PEM strings, starting
+     * with its own certificate. Empty if the peer presented none. */
+    readonly peerCertificates: string[];
+  }
+
+  export interface TlsListener extends Listener, AsyncIterable<TlsConn> {
+    /** Waits for and resolves to the next connection to the `Listener`. */
+    accept(): Promise<TlsConn>;
+
+    [Symbol.asyncIterator](): AsyncIterableIterator<TlsConn>;
+  }
+
   /** Listen announces on the local transport address over TLS (transport layer
    * security).
    *
    *      const lstnr = Deno.listenTls({ port: 443, certFile: "./server.crt", keyFile: "./server.key" });
    *
-   * Requires `allow-net` permission. */
-  export function listenTls(options: ListenTlsOptions): Listener;
+   * Requires `allow-net` permission, and `allow-read` permission for
+   * `certFile` and `keyFile`. */
+  export function listenTls(options: ListenTlsOptions): TlsListener;
 
   export interface ConnectOptions {
     /** The port to connect to. */
@@ -1481,7 +1501,7 @@ declare namespace Deno {
    *
    * Requires `allow-net` permission.
    */
-  export function connectTls(options: ConnectTlsOptions): Promise<Conn>;
+  export function connectTls(options: ConnectTlsOptions): Promise<TlsConn>;
 
   export interface Metrics {
     opsDispatched: number;
@@ -1569,6 +1589,11 @@ declare namespace Deno {
     readonly stdin?: Writer & Closer;
     readonly stdout?: Reader & Closer;
     readonly stderr?: Reader & Closer;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The master side of the pseudo-terminal, present when the process was
+     * spawned with `opt.pty`. */
+    readonly pty?: Pty;
     /** Resolves to the current status of the process. */
     status(): Promise<ProcessStatus>;
     /** Buffer the stdout until EOF and return it as `Uint8Array`.
@@ -1590,8 +1615,25 @@ declare namespace Deno {
      *
      * Send a signal to process. This functionality currently only works on
      * Linux and Mac OS.
+     *
+     * With `options.group` the signal is sent to every process in the process
+     * group of the process, which requires it to have been spawned with
+     * `detached` or `pty`.
      */
-    kill(signo: number): void;
+    kill(signo: number, options?: { group?: boolean }): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * The master side of a pseudo-terminal allocated by `Deno.run()`. Reading
+   * yields everything the child writes to its terminal and writing is seen by
+   * the child as typed input. Reads resolve to `null` once the child and all
+   * of its descendants have closed the terminal. */
+  export interface Pty extends Reader, Writer, Closer {
+    readonly rid: number;
+    /** Changes the size of the terminal, which sends `SIGWINCH` to the
+     * foreground process group of the child. */
+    resize(cols: number, rows: number): void;
   }
 
   export type ProcessStatus =
@@ -1616,7 +1658,48 @@ declare namespace Deno {
     };
     stdout?: "inherit" | "piped" | "null" | number;
     stderr?: "inherit" | "piped" | "null" | number;
-    stdin?: "inherit" | "piped" | "null" | number;
+    stdin?: "inherit" | "piped" | "null" | number | Uint8Array;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Allocates a pseudo-terminal of the given size and attaches the child's
+     * stdin, stdout and stderr to it. The child becomes a session leader with
+     * the terminal as its controlling terminal. Cannot be combined with
+     * `stdin`, `stdout` or `stderr`. Only supported on Unix. */
+    pty?: { cols: number; rows: number };
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Runs the process in a new session and process group, and keeps it
+     * running when the `Process` is closed. Only supported on Unix. */
+    detached?: boolean;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Sets the user id of the process. Only supported on Unix. */
+    uid?: number;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Sets the group id of the process. Only supported on Unix. */
+    gid?: number;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Sets the file mode creation mask of the process. Only supported on
+     * Unix. */
+    umask?: number;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Sets both the soft and hard resource limits of the process: `cpu` time
+     * in seconds, `memory` as the size of the address space in bytes and the
+     * number of `openFiles`. Only supported on Unix. */
+    rlimits?: { cpu?: number; memory?: number; openFiles?: number };
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Time in milliseconds after which the process is sent `SIGTERM`,
+     * followed by `SIGKILL` if it is still running `killGracePeriod`
+     * milliseconds (5000 by default) later. The whole process group is
+     * signalled if the process was spawned with `detached` or `pty`. The timer
+     * stops when the `Process` is closed. Only supported on Unix. */
+    timeout?: number;
+    /** **UNSTABLE**: new API, yet to be vetted. See `timeout`. */
+    killGracePeriod?: number;
   }
 
   /** Spawns new subprocess.  RunOptions must contain at a minimum the `opt.cmd`,
@@ -1634,9 +1717,19 @@ declare namespace Deno {
    *
    * By default subprocess inherits stdio of parent process. To change that
    * `opt.stdout`, `opt.stderr` and `opt.stdin` can be specified independently -
-   * they can be set to either an rid of open file or set to "inherit" "piped"
+   * they can be set to either an rid of a resource or set to "inherit" "piped"
    * or "null":
    *
+   * A file rid is shared with the subprocess. Any other resource, such as a
+   * socket or the stdout of another subprocess, is handed over to the
+   * subprocess and can no longer be used by the caller:
+   *
+   *       const ls = Deno.run({ cmd: ["ls"], stdout: "piped" });
+   *       const wc = Deno.run({ cmd: ["wc", "-l"], stdin: ls.stdout!.rid });
+   *
+   * `opt.stdin` can also be a `Uint8Array`, which is written to the
+   * subprocess before its stdin is closed.
+   *
    * `"inherit"` The default if unspecified. The child inherits from the
    * corresponding parent descriptor.
    *
@@ -1155,12 +1155,15 @@ interface MessageEventInit extends EventInit {
   data?: any;
   origin?: string;
   lastEventId?: string;
+  ports?: MessagePort[];
 }
 
 declare class MessageEvent extends Event {
   readonly data: any;
   readonly origin: string;
   readonly lastEventId: string;
+  /** The message ports sent along with the message. */
+  readonly ports: ReadonlyArray<MessagePort>;
   constructor(type: string, eventInitDict?: MessageEventInit);
 }
 
@@ -1201,9 +1204,19 @@ declare class Worker extends EventTarget {
        * Set to `true` to make `Deno` namespace and all of its methods
        * available to worker thread.
        *
-       * Currently worker inherits permissions from main thread (permissions
-       * given using `--allow-*` flags).
-       * Configurable permissions are on the roadmap to be implemented.
+       * By default the worker inherits permissions from the thread creating
+       * it (permissions given using `--allow-*` flags). Set
+       * `deno.permissions` to narrow them down. Each permission can be
+       * `"inherit"`, `false` to deny it, `true` to grant it in full or, for
+       * `read`, `write` and `net`, a list of paths or hosts. Omitted
+       * permissions are inherited. Creating the worker throws
+       * `Deno.errors.PermissionDenied` if it would be granted anything its
+       * parent doesn't have, and workers can't prompt for more permissions.
+       *
+       *    new Worker("./plugin.ts", {
+       *      type: "module",
+       *      deno: { permissions: { read: ["./data"], net: false } },
+       *    });
        *
        * Example:
        *    // mod.ts
@@ -1233,14 +1246,152 @@ declare class Worker extends EventTarget {
        *    hello world2
        *
        */
-      deno?: boolean;
+      deno?:
+        | boolean
+        | {
+            permissions?:
+              | "inherit"
+              | {
+                  read?: "inherit" | boolean | string[];
+                  write?: "inherit" | boolean | string[];
+                  net?: "inherit" | boolean | string[];
+                  env?: "inherit" | boolean;
+                  run?: "inherit" | boolean;
+                  plugin?: "inherit" | boolean;
+                  hrtime?: "inherit" | boolean;
+                };
+          };
     }
   );
-  postMessage(message: any, transfer: ArrayBuffer[]): void;
+  /** Sends `message` to the worker as JSON, along with the `MessagePort`s in
+   * the transfer list. */
+  postMessage(message: any, transfer: any[]): void;
   postMessage(message: any, options?: PostMessageOptions): void;
   terminate(): void;
 }
 
+interface CloseEventInit extends EventInit {
+  code?: number;
+  reason?: string;
+  wasClean?: boolean;
+}
+
+declare class CloseEvent extends Event {
+  /** The close code sent by the server. */
+  readonly code: number;
+  /** The reason given by the server for closing the connection. */
+  readonly reason: string;
+  /** Whether the connection was closed with a closing handshake. */
+  readonly wasClean: boolean;
+  constructor(type: string, eventInitDict?: CloseEventInit);
+}
+
+type BinaryType = "blob" | "arraybuffer";
+
+/** A WebSocket client connection. Connecting requires the `--allow-net`
+ * permission for the host of the URL.
+ *
+ *       const ws = new WebSocket("wss://echo.example.com", ["chat"]);
+ *       ws.onopen = (): void => ws.send("Hello");
+ *       ws.onmessage = (e: MessageEvent): void => {
+ *         console.log(e.data);
+ *         ws.close();
+ *       };
+ */
+declare class WebSocket extends EventTarget {
+  constructor(url: string, protocols?: string | string[]);
+
+  static readonly CONNECTING: number;
+  static readonly OPEN: number;
+  static readonly CLOSING: number;
+  static readonly CLOSED: number;
+
+  readonly CONNECTING: number;
+  readonly OPEN: number;
+  readonly CLOSING: number;
+  readonly CLOSED: number;
+
+  /** Whether binary messages are delivered as `Blob`s or `ArrayBuffer`s.
+   * Defaults to `"blob"`. */
+  binaryType: BinaryType;
+  /** The number of bytes queued by `send()` that were not yet sent. */
+  readonly bufferedAmount: number;
+  /** The extensions selected by the server. */
+  readonly extensions: string;
+  /** The subprotocol selected by the server. */
+  readonly protocol: string;
+  /** The state of the connection, one of the `CONNECTING`, `OPEN`, `CLOSING`
+   * and `CLOSED` constants. */
+  readonly readyState: number;
+  readonly url: string;
+
+  onclose: ((ev: CloseEvent) => any) | null;
+  onerror: ((ev: Event | ErrorEvent) => any) | null;
+  onmessage: ((ev: MessageEvent) => any) | null;
+  onopen: ((ev: Event) => any) | null;
+
+  /** Starts the closing handshake. The code must be either 1000 or in the
+   * range of 3000 to 4999. */
+  close(code?: number, reason?: string): void;
+  /** Sends a message. Strings are sent as text messages, everything else as
+   * binary messages. */
+  send(data: string | ArrayBufferLike | Blob | ArrayBufferView): void;
+}
+
+/** One end of a `MessageChannel`. Ports can be sent to other workers by
+ * listing them in the transfer list of `postMessage()`, the receiver finds
+ * them in the `ports` of the message event.
+ *
+ * Messages are only dispatched once the port is started, either by calling
+ * `start()` or by assigning `onmessage`. A started port keeps the program
+ * alive until it is closed. */
+declare class MessagePort extends EventTarget {
+  onmessage: ((ev: MessageEvent) => any) | null;
+  onmessageerror: ((ev: MessageEvent) => any) | null;
+  /** Sends `message` to the entangled port as JSON, along with the ports in
+   * the transfer list. */
+  postMessage(message: any, transfer: any[]): void;
+  postMessage(message: any, options?: PostMessageOptions): void;
+  start(): void;
+  close(): void;
+}
+
+/** A pair of entangled message ports, used to let workers talk to each other
+ * directly.
+ *
+ *       const { port1, port2 } = new MessageChannel();
+ *       worker1.postMessage("connect", [port1]);
+ *       worker2.postMessage("connect", [port2]);
+ *
+ *       // In the workers
+ *       onmessage = (e: MessageEvent): void => {
+ *         const [port] = e.ports;
+ *       };
+ */
+declare class MessageChannel {
+  constructor();
+  readonly port1: MessagePort;
+  readonly port2: MessagePort;
+}
+
+/** Delivers messages to every other `BroadcastChannel` with the same name,
+ * across all workers of the program. Once `onmessage` is set or a "message"
+ * listener is added, the channel keeps the program alive until it is closed.
+ *
+ *       const channel = new BroadcastChannel("updates");
+ *       channel.onmessage = (e: MessageEvent): void => console.log(e.data);
+ *       channel.postMessage("hello");
+ */
+declare class BroadcastChannel extends EventTarget {
+  constructor(name: string);
+  readonly name: string;
+  onmessage: ((ev: MessageEvent) => any) | null;
+  onmessageerror: ((ev: MessageEvent) => any) | null;
+  /** Sends `message` to all other subscribers as JSON. */
+  postMessage(message: any): void;
+  close(): void;
+}
+
 declare namespace performance {
   /** Returns a current time from Deno's start in milliseconds.
    *
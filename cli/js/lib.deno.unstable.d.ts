@@ -952,9 +952,62 @@ declare namespace Deno {
     close(): void;
     /** Return the address of the `UDPConn`. */
     readonly addr: Addr;
+    /** UNSTABLE: new API, yet to be vetted.
+     *
+     * Joins an IPv4 multicast group. The network interface is given by its
+     * address and defaults to `"0.0.0.0"`, which lets the system choose.
+     *
+     *       const socket = Deno.listenDatagram({
+     *         hostname: "0.0.0.0",
+     *         port: 5353,
+     *         transport: "udp",
+     *       });
+     *       socket.joinMulticastV4("224.0.0.251");
+     */
+    joinMulticastV4(address: string, networkInterface?: string): void;
+    /** UNSTABLE: new API, yet to be vetted.
+     *
+     * Joins an IPv6 multicast group. The network interface is given by its
+     * index and defaults to `0`, which lets the system choose. */
+    joinMulticastV6(address: string, networkInterface?: number): void;
+    /** UNSTABLE: new API, yet to be vetted.
+     *
+     * Leaves a multicast group joined with `joinMulticastV4()` or
+     * `joinMulticastV6()`, using the same network interface. */
+    leaveMulticast(address: string, networkInterface?: string | number): void;
+    /** UNSTABLE: new API, yet to be vetted.
+     *
+     * Sets whether datagrams may be sent to broadcast addresses. */
+    setBroadcast(broadcast: boolean): void;
+    /** UNSTABLE: new API, yet to be vetted.
+     *
+     * Sets the time-to-live of outgoing multicast datagrams, which limits
+     * how many routers they pass. For IPv6 sockets this sets the hop limit.
+     * Defaults to `1`. */
+    setMulticastTTL(ttl: number): void;
+    /** UNSTABLE: new API, yet to be vetted.
+     *
+     * Sets whether outgoing multicast datagrams are delivered back to the
+     * local host. */
+    setMulticastLoopback(loopback: boolean): void;
     [Symbol.asyncIterator](): AsyncIterableIterator<[Uint8Array, Addr]>;
   }
 
+  export interface Conn {
+    /** UNSTABLE: new API, yet to be vetted.
+     *
+     * Enables or disables Nagle's algorithm (`TCP_NODELAY`). When enabled,
+     * which is the default argument, small writes are sent immediately
+     * instead of being coalesced. */
+    setNoDelay(noDelay?: boolean): void;
+    /** UNSTABLE: new API, yet to be vetted.
+     *
+     * Enables or disables TCP keepalive probes (`SO_KEEPALIVE`). The initial
+     * delay is the idle time in milliseconds before the first probe, and is
+     * rounded up to whole seconds. */
+    setKeepAlive(keepAlive?: boolean, initialDelay?: number): void;
+  }
+
   export interface UnixListenOptions {
     /** A Path to the Unix Socket. */
     path: string;
@@ -1024,12 +1077,152 @@ declare namespace Deno {
     options: ConnectOptions | UnixConnectOptions
   ): Promise<Conn>;
 
+  export interface ResolveDnsOptions {
+    /** The name server to query, instead of the ones in the system
+     * configuration. The port defaults to 53. */
+    nameServer?: {
+      ipAddr: string;
+      port?: number;
+    };
+  }
+
+  export interface MXRecord {
+    preference: number;
+    exchange: string;
+  }
+
+  export interface SRVRecord {
+    priority: number;
+    weight: number;
+    port: number;
+    target: string;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Resolves the DNS records of the given type for a name. Names in the
+   * results are fully qualified and end with a dot.
+   *
+   *     const a = await Deno.resolveDns("example.com", "A");
+   *     const mx = await Deno.resolveDns("example.com", "MX", {
+   *       nameServer: { ipAddr: "1.1.1.1", port: 53 },
+   *     });
+   *
+   * Requires `allow-net` permission for the name servers that are queried,
+   * which are the ones in the system configuration if `nameServer` is not
+   * given. */
+  export function resolveDns(
+    query: string,
+    recordType: "A" | "AAAA" | "CNAME" | "NS",
+    options?: ResolveDnsOptions
+  ): Promise<string[]>;
+
+  export function resolveDns(
+    query: string,
+    recordType: "MX",
+    options?: ResolveDnsOptions
+  ): Promise<MXRecord[]>;
+
+  export function resolveDns(
+    query: string,
+    recordType: "SRV",
+    options?: ResolveDnsOptions
+  ): Promise<SRVRecord[]>;
+
+  export function resolveDns(
+    query: string,
+    recordType: "TXT",
+    options?: ResolveDnsOptions
+  ): Promise<string[][]>;
+
+  export interface ListenTlsOptions {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Server certificate chain as a PEM string, instead of `certFile`. */
+    cert?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Server private key as a PEM string, instead of `keyFile`. */
+    key?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Certificates and keys as PEM strings, keyed by the host name clients
+     * ask for with SNI. Clients asking for other names, or none, get the
+     * default certificate. */
+    sniCerts?: Record<string, { cert: string; key: string }>;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Application protocols to offer to clients with ALPN, in order of
+     * preference, e.g. `["h2", "http/1.1"]` for `Deno.serveHttp`. */
+    alpnProtocols?: string[];
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Root certificates as PEM strings that client certificates are verified
+     * against. Setting them enables client authentication. */
+    clientCaCerts?: string[];
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Whether clients without a certificate are rejected when
+     * `clientCaCerts` is set. Defaults to `true`. */
+    requireClientCert?: boolean;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Listen announces on the local transport address over TLS, with the
+   * server certificate given as PEM strings. `certFile` and `keyFile` may be
+   * omitted when `cert` and `key`, or `sniCerts`, are given.
+   *
+   *      const lstnr = Deno.listenTls({ port: 443, cert, key });
+   *
+   * Requires `allow-net` permission, and `allow-read` permission for
+   * `certFile` and `keyFile`. */
+  export function listenTls(
+    options: Omit<ListenTlsOptions, "certFile" | "keyFile"> & {
+      certFile?: string;
+      keyFile?: string;
+    }
+  ): TlsListener;
+
+  export interface ConnectTlsOptions {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Additional root certificates as PEM strings, that the server
+     * certificate is verified against. */
+    caCerts?: string[];
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Application protocols to offer to the server with ALPN, in order of
+     * preference. */
+    alpnProtocols?: string[];
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Client certificate chain as a PEM string, for servers that require
+     * client authentication. Requires `privateKey`. */
+    certChain?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Private key of the client certificate as a PEM string. */
+    privateKey?: string;
+  }
+
   export interface StartTlsOptions {
     /** A literal IP address or host name that can be resolved to an IP address.
      * If not specified, defaults to `127.0.0.1`. */
     hostname?: string;
     /** Server certificate file. */
     certFile?: string;
+    /** Additional root certificates as PEM strings, that the server
+     * certificate is verified against. */
+    caCerts?: string[];
+    /** Application protocols to offer to the server with ALPN, in order of
+     * preference. */
+    alpnProtocols?: string[];
+    /** Client certificate chain as a PEM string, for servers that require
+     * client authentication. Requires `privateKey`. */
+    certChain?: string;
+    /** Private key of the client certificate as a PEM string. */
+    privateKey?: string;
   }
 
   /** **UNSTABLE**: new API, yet to be vetted.
@@ -1049,7 +1242,7 @@ declare namespace Deno {
   export function startTls(
     conn: Conn,
     options?: StartTlsOptions
-  ): Promise<Conn>;
+  ): Promise<TlsConn>;
 
   /** **UNSTABLE**: The `signo` argument may change to require the Deno.Signal
    * enum.
@@ -1071,6 +1264,48 @@ declare namespace Deno {
    * Requires `allow-run` permission. */
   export function kill(pid: number, signo: number): void;
 
+  export interface RunSyncOptions {
+    /** Arguments to pass. Note, the first element needs to be a path to the
+     * binary */
+    cmd: string[];
+    cwd?: string;
+    env?: {
+      [key: string]: string;
+    };
+    /** Defaults to `"null"`. A `Uint8Array` is written to the stdin of the
+     * process. */
+    stdin?: "inherit" | "null" | Uint8Array;
+    /** Defaults to `"piped"`, which captures the output. */
+    stdout?: "inherit" | "piped" | "null";
+    /** Defaults to `"piped"`, which captures the output. */
+    stderr?: "inherit" | "piped" | "null";
+    /** The largest number of bytes allowed on stdout or stderr. If exceeded,
+     * the process is killed and an error is thrown. Unlimited by default. */
+    maxBuffer?: number;
+  }
+
+  export type RunSyncOutput = ProcessStatus & {
+    /** The captured stdout, empty unless `stdout` was `"piped"`. */
+    stdout: Uint8Array;
+    /** The captured stderr, empty unless `stderr` was `"piped"`. */
+    stderr: Uint8Array;
+  };
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Spawns a subprocess, waits for it to exit and returns its exit status
+   * together with its output. No resources are left open.
+   *
+   *      const { success, stdout } = Deno.runSync({
+   *        cmd: ["echo", "hello"],
+   *      });
+   *      console.log(new TextDecoder().decode(stdout)); // "hello\n"
+   *
+   * Blocks the event loop until the subprocess has exited.
+   *
+   * Requires `allow-run` permission. */
+  export function runSync(opt: RunSyncOptions): RunSyncOutput;
+
   /** The name of a "powerful feature" which needs permission.
    *
    * See: https://w3c.github.io/permissions/#permission-registry
@@ -1182,4 +1417,142 @@ declare namespace Deno {
    *  Requires `allow-env` permission.
    */
   export function hostname(): string;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Writes a snapshot of the V8 heap of the current thread to `path`. The
+   * file can be loaded in the Memory tab of Chrome DevTools. By convention
+   * the file name should end with `.heapsnapshot`.
+   *
+   *       await Deno.writeHeapSnapshot("./app.heapsnapshot");
+   *
+   * Requires `allow-write` permission. */
+  export function writeHeapSnapshot(path: string): Promise<void>;
+
+  export interface CreateHttpClientOptions {
+    /** PEM encoded root certificates to trust, in addition to the default
+     * ones and the `--cert` flag. */
+    caData?: string;
+    /** A proxy to send all requests through, e.g.
+     * `"http://proxy.local:3128"`. Defaults to the `HTTP_PROXY` and
+     * `HTTPS_PROXY` environment variables. Hosts listed in `NO_PROXY` are
+     * always reached directly. */
+    proxy?: string;
+    /** PEM encoded certificate chain presented to servers that ask for a
+     * client certificate. Requires `clientKey`. */
+    clientCert?: string;
+    /** PEM encoded private key of `clientCert`. */
+    clientKey?: string;
+    /** Time in milliseconds after which a request fails, including reading
+     * the response body. */
+    timeout?: number;
+    /** `true` uses HTTP/2 without negotiating it first. This is required
+     * for HTTP/2 over plain `http:`, but fails for servers that only speak
+     * HTTP/1.1. `false` only uses HTTP/1.1. By default HTTP/2 is used for
+     * servers that offer it over TLS. */
+    http2?: boolean;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A client for `fetch`, created by `Deno.createHttpClient`. */
+  export class HttpClient {
+    readonly rid: number;
+    /** Closes the client. Requests that are in progress are not aborted. */
+    close(): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Creates a client with custom options to send requests with `fetch`.
+   * Requests sent with the same client reuse its connections.
+   *
+   *       const client = Deno.createHttpClient({
+   *         caData: await Deno.readTextFile("./ca.pem"),
+   *         timeout: 10000,
+   *       });
+   *       const res = await fetch("https://example.com", { client });
+   *       client.close();
+   *
+   * Requires `allow-net` permission for the proxy, if one is used. */
+  export function createHttpClient(
+    options?: CreateHttpClientOptions
+  ): HttpClient;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A response to a request received by `Deno.serveHttp`. A `Response`
+   * returned by `fetch` can be used as well, e.g. to proxy requests. */
+  export interface HttpResponse {
+    /** Defaults to 200. */
+    status?: number;
+    headers?: HeadersInit;
+    /** Readers and streams are sent as they are read. */
+    body?: Uint8Array | string | Reader | ReadableStream<Uint8Array> | null;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface RequestEvent {
+    /** The request, whose body is streamed as it is received. */
+    readonly request: Request;
+    /** Sends the response. Resolves once the whole body has been sent. */
+    respondWith(response: HttpResponse | Promise<HttpResponse>): Promise<void>;
+    /** Accepts a WebSocket handshake and resolves with the upgraded
+     * connection, which carries raw WebSocket frames. Throws if the request
+     * isn't a WebSocket handshake. */
+    upgradeWebSocket(): Promise<Conn>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface HttpConn extends AsyncIterable<RequestEvent> {
+    readonly rid: number;
+    /** Resolves with the next request of the connection, or `null` once the
+     * connection has been closed. */
+    nextRequest(): Promise<RequestEvent | null>;
+    /** Closes the connection, aborting any response that is being sent. */
+    close(): void;
+    [Symbol.asyncIterator](): AsyncIterableIterator<RequestEvent>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Serves HTTP on a connection accepted by `Deno.listen` or `Deno.listenTls`
+   * with a native HTTP implementation. HTTP/1.1 connections are kept alive,
+   * and HTTP/2 is used for TLS connections that negotiated it, see the
+   * `alpnProtocols` option of `Deno.listenTls`. The connection is taken over
+   * and can't be used directly anymore.
+   *
+   *       const listener = Deno.listen({ port: 8000 });
+   *       for await (const conn of listener) {
+   *         (async () => {
+   *           for await (const event of Deno.serveHttp(conn)) {
+   *             event.respondWith({ body: "Hello World\n" });
+   *           }
+   *         })();
+   *       }
+   */
+  export function serveHttp(conn: Conn): HttpConn;
+}
+
+interface RequestInit {
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * The client to send the request with. See `Deno.createHttpClient`. */
+  client?: Deno.HttpClient;
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Called each time a chunk of the request body has been handed to the
+   * network. `total` is the value of the `Content-Length` header or the size
+   * of a buffered body, and `null` if it isn't known in advance.
+   *
+   *       await fetch("https://example.com/upload", {
+   *         method: "POST",
+   *         body: await Deno.readFile("./video.mp4"),
+   *         onUploadProgress: ({ loaded }) => console.log(loaded),
+   *       });
+   */
+  onUploadProgress?: (progress: {
+    loaded: number;
+    total: number | null;
+  }) => void;
 }
@@ -16,6 +16,18 @@ export interface DatagramConn extends AsyncIterable<[Uint8Array, Addr]> {
 
   addr: Addr;
 
+  joinMulticastV4(address: string, networkInterface?: string): void;
+
+  joinMulticastV6(address: string, networkInterface?: number): void;
+
+  leaveMulticast(address: string, networkInterface?: string | number): void;
+
+  setBroadcast(broadcast: boolean): void;
+
+  setMulticastTTL(ttl: number): void;
+
+  setMulticastLoopback(loopback: boolean): void;
+
   [Symbol.asyncIterator](): AsyncIterableIterator<[Uint8Array, Addr]>;
 }
 
@@ -52,6 +64,14 @@ export class ConnImpl implements Conn {
   closeWrite(): void {
     netOps.shutdown(this.rid, netOps.ShutdownMode.Write);
   }
+
+  setNoDelay(noDelay = true): void {
+    netOps.setNoDelay(this.rid, noDelay);
+  }
+
+  setKeepAlive(keepAlive = true, initialDelay?: number): void {
+    netOps.setKeepAlive(this.rid, keepAlive, initialDelay);
+  }
 }
 
 export class ListenerImpl implements Listener {
@@ -114,6 +134,30 @@ export class DatagramImpl implements DatagramConn {
     await netOps.send(args as netOps.SendRequest, p);
   }
 
+  joinMulticastV4(address: string, networkInterface?: string): void {
+    netOps.joinMulticast(this.rid, address, networkInterface);
+  }
+
+  joinMulticastV6(address: string, networkInterface?: number): void {
+    netOps.joinMulticast(this.rid, address, networkInterface);
+  }
+
+  leaveMulticast(address: string, networkInterface?: string | number): void {
+    netOps.leaveMulticast(this.rid, address, networkInterface);
+  }
+
+  setBroadcast(broadcast: boolean): void {
+    netOps.setBroadcast(this.rid, broadcast);
+  }
+
+  setMulticastTTL(ttl: number): void {
+    netOps.setMulticastTTL(this.rid, ttl);
+  }
+
+  setMulticastLoopback(loopback: boolean): void {
+    netOps.setMulticastLoopback(this.rid, loopback);
+  }
+
   close(): void {
     close(this.rid);
   }
@@ -137,6 +181,8 @@ export interface Conn extends Reader, Writer, Closer {
   remoteAddr: Addr;
   rid: number;
   closeWrite(): void;
+  setNoDelay(noDelay?: boolean): void;
+  setKeepAlive(keepAlive?: boolean, initialDelay?: number): void;
 }
 
 export interface ListenOptions {
@@ -77,3 +77,54 @@ export async function connect(
     return stableConnect(options as ConnectOptions);
   }
 }
+
+export interface ResolveDnsOptions {
+  nameServer?: {
+    ipAddr: string;
+    port?: number;
+  };
+}
+
+export interface MXRecord {
+  preference: number;
+  exchange: string;
+}
+
+export interface SRVRecord {
+  priority: number;
+  weight: number;
+  port: number;
+  target: string;
+}
+
+export function resolveDns(
+  query: string,
+  recordType: "A" | "AAAA" | "CNAME" | "NS",
+  options?: ResolveDnsOptions
+): Promise<string[]>;
+export function resolveDns(
+  query: string,
+  recordType: "MX",
+  options?: ResolveDnsOptions
+): Promise<MXRecord[]>;
+export function resolveDns(
+  query: string,
+  recordType: "SRV",
+  options?: ResolveDnsOptions
+): Promise<SRVRecord[]>;
+export function resolveDns(
+  query: string,
+  recordType: "TXT",
+  options?: ResolveDnsOptions
+): Promise<string[][]>;
+export function resolveDns(
+  query: string,
+  recordType: netOps.RecordType,
+  options: ResolveDnsOptions = {}
+): Promise<unknown[]> {
+  return netOps.resolveDns({
+    query,
+    recordType,
+    nameServer: options.nameServer,
+  });
+}
@@ -0,0 +1,10 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+
+export function createObjectURL(type: string, data: Uint8Array): string {
+  return sendSync("op_create_object_url", { mediaType: type }, data);
+}
+
+export function revokeObjectURL(url: string): void {
+  sendSync("op_revoke_object_url", { url });
+}
@@ -1,11 +1,18 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
-import { sendAsync } from "./dispatch_json.ts";
+import { sendAsync, sendSync } from "./dispatch_json.ts";
 
 interface FetchRequest {
   url: string;
   method: string | null;
   headers: Array<[string, string]>;
+  hasBody: boolean;
+  clientRid: number | null;
+}
+
+export interface FetchRequestRids {
+  requestRid: number;
+  requestBodyRid: number | null;
 }
 
 export interface FetchResponse {
@@ -18,11 +25,35 @@ export interface FetchResponse {
 export function fetch(
   args: FetchRequest,
   body: ArrayBufferView | undefined
-): Promise<FetchResponse> {
+): FetchRequestRids {
   let zeroCopy = undefined;
   if (body) {
     zeroCopy = new Uint8Array(body.buffer, body.byteOffset, body.byteLength);
   }
 
-  return sendAsync("op_fetch", args, zeroCopy);
+  return sendSync("op_fetch", args, zeroCopy);
+}
+
+export function fetchSend(rid: number): Promise<FetchResponse> {
+  return sendAsync("op_fetch_send", { rid });
+}
+
+export interface CreateHttpClientOptions {
+  caData?: string;
+  proxy?: string;
+  clientCert?: string;
+  clientKey?: string;
+  timeout?: number;
+  http2?: boolean;
+}
+
+export function createHttpClient(options: CreateHttpClientOptions): number {
+  return sendSync("op_create_http_client", options);
+}
+
+export async function fetchRequestWrite(
+  rid: number,
+  chunk: Uint8Array
+): Promise<void> {
+  await sendAsync("op_fetch_request_write", { rid }, chunk);
 }
@@ -0,0 +1,52 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+
+export function serveHttp(rid: number): number {
+  return sendSync("op_serve_http", { rid });
+}
+
+export interface HttpRequest {
+  requestRid: number;
+  bodyRid: number | null;
+  method: string;
+  url: string;
+  headers: Array<[string, string]>;
+}
+
+interface NextRequestResponse {
+  request: HttpRequest | null;
+}
+
+export function nextRequest(rid: number): Promise<NextRequestResponse> {
+  return sendAsync("op_http_next_request", { rid });
+}
+
+interface RespondRequest {
+  rid: number;
+  status: number;
+  headers: Array<[string, string]>;
+  hasBody: boolean;
+}
+
+interface RespondResponse {
+  bodyRid: number | null;
+}
+
+export function respond(
+  args: RespondRequest,
+  body: Uint8Array | undefined
+): RespondResponse {
+  return sendSync("op_http_respond", args, body);
+}
+
+export async function responseWrite(
+  rid: number,
+  chunk: Uint8Array
+): Promise<void> {
+  await sendAsync("op_http_response_write", { rid }, chunk);
+}
+
+export function upgradeWebSocket(rid: number): Promise<number> {
+  return sendAsync("op_http_upgrade_websocket", { rid });
+}
@@ -27,6 +27,46 @@ export function shutdown(rid: number, how: ShutdownMode): Promise<void> {
   return Promise.resolve();
 }
 
+export function joinMulticast(
+  rid: number,
+  address: string,
+  networkInterface?: string | number
+): void {
+  sendSync("op_join_multicast", { rid, address, interface: networkInterface });
+}
+
+export function leaveMulticast(
+  rid: number,
+  address: string,
+  networkInterface?: string | number
+): void {
+  sendSync("op_leave_multicast", { rid, address, interface: networkInterface });
+}
+
+export function setBroadcast(rid: number, broadcast: boolean): void {
+  sendSync("op_set_broadcast", { rid, broadcast });
+}
+
+export function setMulticastTTL(rid: number, ttl: number): void {
+  sendSync("op_set_multicast_ttl", { rid, ttl });
+}
+
+export function setMulticastLoopback(rid: number, loopback: boolean): void {
+  sendSync("op_set_multicast_loopback", { rid, loopback });
+}
+
+export function setNoDelay(rid: number, noDelay: boolean): void {
+  sendSync("op_set_nodelay", { rid, noDelay });
+}
+
+export function setKeepAlive(
+  rid: number,
+  keepAlive: boolean,
+  initialDelay?: number
+): void {
+  sendSync("op_set_keepalive", { rid, keepAlive, initialDelay });
+}
+
 interface AcceptResponse {
   rid: number;
   localAddr: Addr;
@@ -86,3 +126,25 @@ export async function send(
 ): Promise<void> {
   await sendAsync("op_send", args, zeroCopy);
 }
+
+export type RecordType =
+  | "A"
+  | "AAAA"
+  | "CNAME"
+  | "MX"
+  | "NS"
+  | "SRV"
+  | "TXT";
+
+export interface ResolveDnsRequest {
+  query: string;
+  recordType: RecordType;
+  nameServer?: {
+    ipAddr: string;
+    port?: number;
+  };
+}
+
+export function resolveDns(args: ResolveDnsRequest): Promise<unknown[]> {
+  return sendAsync("op_resolve_dns", args);
+}
@@ -1,6 +1,7 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { sendSync, sendAsync } from "./dispatch_json.ts";
 import { assert } from "../util.ts";
+import { toByteArray } from "../web/base64.ts";
 import { build } from "../build.ts";
 
 export function kill(pid: number, signo: number): void {
@@ -30,6 +31,14 @@ interface RunRequest {
   stdinRid: number;
   stdoutRid: number;
   stderrRid: number;
+  pty?: { cols: number; rows: number };
+  detached?: boolean;
+  uid?: number;
+  gid?: number;
+  umask?: number;
+  rlimits?: { cpu?: number; memory?: number; openFiles?: number };
+  timeout?: number;
+  killGracePeriod?: number;
 }
 
 interface RunResponse {
@@ -38,9 +47,45 @@ interface RunResponse {
   stdinRid: number | null;
   stdoutRid: number | null;
   stderrRid: number | null;
+  ptyRid: number | null;
 }
 
-export function run(request: RunRequest): RunResponse {
+export function run(request: RunRequest, stdin?: Uint8Array): RunResponse {
   assert(request.cmd.length > 0);
-  return sendSync("op_run", request);
+  return sendSync("op_run", request, stdin);
+}
+
+export function ptyResize(rid: number, cols: number, rows: number): void {
+  sendSync("op_pty_resize", { rid, cols, rows });
+}
+
+interface RunSyncRequest {
+  cmd: string[];
+  cwd?: string;
+  env?: Array<[string, string]>;
+  stdin: string;
+  stdout: string;
+  stderr: string;
+  maxBuffer?: number;
+}
+
+interface RunSyncResponse {
+  code: number | null;
+  signal: number | null;
+  stdout: Uint8Array;
+  stderr: Uint8Array;
+}
+
+export function runSync(
+  request: RunSyncRequest,
+  stdin?: Uint8Array
+): RunSyncResponse {
+  assert(request.cmd.length > 0);
+  const res = sendSync("op_run_sync", request, stdin);
+  return {
+    code: res.code,
+    signal: res.signal,
+    stdout: toByteArray(res.stdout),
+    stderr: toByteArray(res.stderr),
+  };
 }
@@ -1,6 +1,6 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
-import { sendSync } from "./dispatch_json.ts";
+import { sendAsync, sendSync } from "./dispatch_json.ts";
 
 export interface Start {
   args: string[];
@@ -38,3 +38,7 @@ export interface Metrics {
 export function metrics(): Metrics {
   return sendSync("op_metrics");
 }
+
+export async function writeHeapSnapshot(path: string): Promise<void> {
+  await sendAsync("op_write_heap_snapshot", { path });
+}
@@ -1,11 +1,23 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 
-export interface ConnectTLSRequest {
+export interface ClientTLSOptions {
+  certFile?: string;
+  caCerts?: string[];
+  alpnProtocols?: string[];
+  certChain?: string;
+  privateKey?: string;
+}
+
+export interface ConnectTLSRequest extends ClientTLSOptions {
   transport: "tcp";
   hostname: string;
   port: number;
-  certFile?: string;
+}
+
+export interface TLSInfo {
+  alpnProtocol: string | null;
+  peerCertificates: string[];
 }
 
 interface EstablishTLSResponse {
@@ -20,6 +32,7 @@ interface EstablishTLSResponse {
     port: number;
     transport: "tcp";
   };
+  info: TLSInfo;
 }
 
 export function connectTls(
@@ -40,6 +53,7 @@ interface AcceptTLSResponse {
     port: number;
     transport: "tcp";
   };
+  info: TLSInfo;
 }
 
 export function acceptTLS(rid: number): Promise<AcceptTLSResponse> {
@@ -50,8 +64,14 @@ export interface ListenTLSRequest {
   port: number;
   hostname: string;
   transport: "tcp";
-  certFile: string;
-  keyFile: string;
+  certFile?: string;
+  keyFile?: string;
+  cert?: string;
+  key?: string;
+  sniCerts?: Record<string, { cert: string; key: string }>;
+  alpnProtocols?: string[];
+  clientCaCerts?: string[];
+  requireClientCert?: boolean;
 }
 
 interface ListenTLSResponse {
@@ -67,10 +87,9 @@ export function listenTls(args: ListenTLSRequest): ListenTLSResponse {
   return sendSync("op_listen_tls", args);
 }
 
-export interface StartTLSRequest {
+export interface StartTLSRequest extends ClientTLSOptions {
   rid: number;
   hostname: string;
-  certFile?: string;
 }
 
 export function startTls(args: StartTLSRequest): Promise<EstablishTLSResponse> {
@@ -2,8 +2,8 @@
 
 import { sendSync } from "./dispatch_json.ts";
 
-export function postMessage(data: Uint8Array): void {
-  sendSync("op_worker_post_message", {}, data);
+export function postMessage(data: Uint8Array, transfer: number[] = []): void {
+  sendSync("op_worker_post_message", { transfer }, data);
 }
 
 export function close(): void {
@@ -0,0 +1,57 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+
+export interface CreateResponse {
+  rid: number;
+  protocol: string;
+  extensions: string;
+}
+
+export type WebSocketEvent =
+  | { kind: "string"; data: string }
+  | { kind: "binary"; data: Uint8Array }
+  | { kind: "close"; code: number; reason: string }
+  | { kind: "closed" }
+  | { kind: "error"; message: string };
+
+export function checkPermission(url: string): void {
+  sendSync("op_ws_check_permission", { url });
+}
+
+export function create(
+  url: string,
+  protocols: string[]
+): Promise<CreateResponse> {
+  return sendAsync("op_ws_create", { url, protocols });
+}
+
+export async function send(
+  rid: number,
+  data: string | Uint8Array
+): Promise<void> {
+  if (typeof data === "string") {
+    await sendAsync("op_ws_send", { rid, text: data });
+  } else {
+    await sendAsync("op_ws_send", { rid }, data);
+  }
+}
+
+export async function close(
+  rid: number,
+  code?: number,
+  reason?: string
+): Promise<void> {
+  await sendAsync("op_ws_close", { rid, code, reason });
+}
+
+export async function nextEvent(rid: number): Promise<WebSocketEvent> {
+  const event = await sendAsync("op_ws_next_event", { rid });
+  if (event.kind === "binary") {
+    // The data of binary messages is copied straight into a new buffer.
+    const data = new Uint8Array(event.byteLength);
+    sendSync("op_ws_read_binary", { rid }, data);
+    return { kind: "binary", data };
+  }
+  return event;
+}
@@ -2,12 +2,23 @@
 /* eslint-disable @typescript-eslint/no-explicit-any */
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 
+export interface WorkerPermissions {
+  read?: "inherit" | boolean | string[];
+  write?: "inherit" | boolean | string[];
+  net?: "inherit" | boolean | string[];
+  env?: "inherit" | boolean;
+  run?: "inherit" | boolean;
+  plugin?: "inherit" | boolean;
+  hrtime?: "inherit" | boolean;
+}
+
 export function createWorker(
   specifier: string,
   hasSourceCode: boolean,
   sourceCode: string,
   useDenoNamespace: boolean,
-  name?: string
+  name?: string,
+  permissions?: WorkerPermissions
 ): { id: number } {
   return sendSync("op_create_worker", {
     specifier,
@@ -15,6 +26,7 @@ export function createWorker(
     sourceCode,
     name,
     useDenoNamespace,
+    permissions,
   });
 }
 
@@ -22,10 +34,46 @@ export function hostTerminateWorker(id: number): void {
   sendSync("op_host_terminate_worker", { id });
 }
 
-export function hostPostMessage(id: number, data: Uint8Array): void {
-  sendSync("op_host_post_message", { id }, data);
+export function hostPostMessage(
+  id: number,
+  data: Uint8Array,
+  transfer: number[] = []
+): void {
+  sendSync("op_host_post_message", { id, transfer }, data);
 }
 
 export function hostGetMessage(id: number): Promise<any> {
   return sendAsync("op_host_get_message", { id });
 }
+
+export function createMessageChannel(): { port1: number; port2: number } {
+  return sendSync("op_create_message_channel");
+}
+
+export function messagePortPostMessage(
+  rid: number,
+  data: Uint8Array,
+  transfer: number[] = []
+): void {
+  sendSync("op_message_port_post_message", { rid, transfer }, data);
+}
+
+export function messagePortRecvMessage(
+  rid: number
+): Promise<{ data: number[]; ports: number[] } | null> {
+  return sendAsync("op_message_port_recv_message", { rid });
+}
+
+export function broadcastSubscribe(name: string): number {
+  return sendSync("op_broadcast_subscribe", { name }).rid;
+}
+
+export function broadcastPostMessage(rid: number, data: Uint8Array): void {
+  sendSync("op_broadcast_post_message", { rid }, data);
+}
+
+export function broadcastRecvMessage(
+  rid: number
+): Promise<{ data: number[] } | null> {
+  return sendAsync("op_broadcast_recv_message", { rid });
+}
@@ -1,9 +1,16 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { File } from "./files.ts";
 import { close } from "./ops/resources.ts";
+import { read, write } from "./ops/io.ts";
 import { Closer, Reader, Writer } from "./io.ts";
 import { readAll } from "./buffer.ts";
-import { kill, runStatus as runStatusOp, run as runOp } from "./ops/process.ts";
+import {
+  kill,
+  ptyResize,
+  runStatus as runStatusOp,
+  run as runOp,
+  runSync as runSyncOp,
+} from "./ops/process.ts";
 
 export type ProcessStdio = "inherit" | "piped" | "null";
 
@@ -15,7 +22,51 @@ export interface RunOptions {
   env?: { [key: string]: string };
   stdout?: ProcessStdio | number;
   stderr?: ProcessStdio | number;
-  stdin?: ProcessStdio | number;
+  stdin?: ProcessStdio | number | Uint8Array;
+  pty?: PtySize;
+  detached?: boolean;
+  uid?: number;
+  gid?: number;
+  umask?: number;
+  rlimits?: ResourceLimits;
+  timeout?: number;
+  killGracePeriod?: number;
+}
+
+export interface ResourceLimits {
+  cpu?: number;
+  memory?: number;
+  openFiles?: number;
+}
+
+export interface KillOptions {
+  group?: boolean;
+}
+
+export interface PtySize {
+  cols: number;
+  rows: number;
+}
+
+export class Pty implements Reader, Writer, Closer {
+  // @internal
+  constructor(readonly rid: number) {}
+
+  read(p: Uint8Array): Promise<number | null> {
+    return read(this.rid, p);
+  }
+
+  write(p: Uint8Array): Promise<number> {
+    return write(this.rid, p);
+  }
+
+  close(): void {
+    close(this.rid);
+  }
+
+  resize(cols: number, rows: number): void {
+    ptyResize(this.rid, cols, rows);
+  }
 }
 
 async function runStatus(rid: number): Promise<ProcessStatus> {
@@ -36,6 +87,7 @@ export class Process {
   readonly stdin?: Writer & Closer;
   readonly stdout?: Reader & Closer;
   readonly stderr?: Reader & Closer;
+  readonly pty?: Pty;
 
   // @internal
   constructor(res: RunResponse) {
@@ -53,6 +105,10 @@ export class Process {
     if (res.stderrRid && res.stderrRid > 0) {
       this.stderr = new File(res.stderrRid);
     }
+
+    if (res.ptyRid && res.ptyRid > 0) {
+      this.pty = new Pty(res.ptyRid);
+    }
   }
 
   status(): Promise<ProcessStatus> {
@@ -85,8 +141,8 @@ export class Process {
     close(this.rid);
   }
 
-  kill(signo: number): void {
-    kill(this.pid, signo);
+  kill(signo: number, { group = false }: KillOptions = {}): void {
+    kill(group ? -this.pid : this.pid, signo);
   }
 }
 
@@ -106,25 +162,104 @@ interface RunResponse {
   stdinRid: number | null;
   stdoutRid: number | null;
   stderrRid: number | null;
+  ptyRid: number | null;
 }
 export function run({
   cmd,
   cwd = undefined,
   env = {},
-  stdout = "inherit",
-  stderr = "inherit",
-  stdin = "inherit",
+  stdout,
+  stderr,
+  stdin,
+  pty,
+  detached,
+  uid,
+  gid,
+  umask,
+  rlimits,
+  timeout,
+  killGracePeriod,
 }: RunOptions): Process {
-  const res = runOp({
-    cmd: cmd.map(String),
-    cwd,
-    env: Object.entries(env),
-    stdin: isRid(stdin) ? "" : stdin,
-    stdout: isRid(stdout) ? "" : stdout,
-    stderr: isRid(stderr) ? "" : stderr,
-    stdinRid: isRid(stdin) ? stdin : 0,
-    stdoutRid: isRid(stdout) ? stdout : 0,
-    stderrRid: isRid(stderr) ? stderr : 0,
-  }) as RunResponse;
+  if (pty) {
+    if (stdin !== undefined || stdout !== undefined || stderr !== undefined) {
+      throw new TypeError(
+        "Deno.run: 'pty' cannot be combined with 'stdin', 'stdout' or 'stderr'"
+      );
+    }
+  }
+  stdout = stdout ?? "inherit";
+  stderr = stderr ?? "inherit";
+  stdin = stdin ?? "inherit";
+  let stdinData: Uint8Array | undefined;
+  if (stdin instanceof Uint8Array) {
+    stdinData = stdin;
+    stdin = "piped";
+  }
+  const res = runOp(
+    {
+      cmd: cmd.map(String),
+      cwd,
+      env: Object.entries(env),
+      stdin: isRid(stdin) ? "" : stdin,
+      stdout: isRid(stdout) ? "" : stdout,
+      stderr: isRid(stderr) ? "" : stderr,
+      stdinRid: isRid(stdin) ? stdin : 0,
+      stdoutRid: isRid(stdout) ? stdout : 0,
+      stderrRid: isRid(stderr) ? stderr : 0,
+      pty,
+      detached,
+      uid,
+      gid,
+      umask,
+      rlimits,
+      timeout,
+      killGracePeriod,
+    },
+    stdinData
+  ) as RunResponse;
   return new Process(res);
 }
+
+export interface RunSyncOptions {
+  cmd: string[];
+  cwd?: string;
+  env?: { [key: string]: string };
+  stdin?: "inherit" | "null" | Uint8Array;
+  stdout?: "inherit" | "piped" | "null";
+  stderr?: "inherit" | "piped" | "null";
+  maxBuffer?: number;
+}
+
+export type RunSyncOutput = ProcessStatus & {
+  stdout: Uint8Array;
+  stderr: Uint8Array;
+};
+
+export function runSync({
+  cmd,
+  cwd = undefined,
+  env = {},
+  stdin = "null",
+  stdout = "piped",
+  stderr = "piped",
+  maxBuffer,
+}: RunSyncOptions): RunSyncOutput {
+  const stdinData = stdin instanceof Uint8Array ? stdin : undefined;
+  const res = runSyncOp(
+    {
+      cmd: cmd.map(String),
+      cwd,
+      env: Object.entries(env),
+      stdin: stdinData ? "piped" : (stdin as string),
+      stdout,
+      stderr,
+      maxBuffer,
+    },
+    stdinData
+  );
+  const output = { stdout: res.stdout, stderr: res.stderr };
+  if (res.signal !== null) {
+    return { success: false, signal: res.signal, ...output };
+  }
+  return { success: res.code === 0, code: res.code!, ...output };
+}
@@ -23,6 +23,11 @@ import * as denoUnstableNs from "./deno_unstable.ts";
 import * as webWorkerOps from "./ops/web_worker.ts";
 import { log, assert, immutableDefine } from "./util.ts";
 import { MessageEvent, ErrorEvent } from "./web/workers.ts";
+import {
+  getTransferList,
+  receivePorts,
+  transferPorts,
+} from "./web/message_channel.ts";
 import { TextEncoder } from "./web/text_encoding.ts";
 import * as runtime from "./runtime.ts";
 import { internalObject, internalSymbol } from "./internals.ts";
@@ -42,10 +47,13 @@ const encoder = new TextEncoder();
 export const onmessage: (e: { data: any }) => void = (): void => {};
 export const onerror: (e: { data: any }) => void = (): void => {};
 
-export function postMessage(data: any): void {
+export function postMessage(data: any, transferOrOptions?: any): void {
+  const transfer = getTransferList(transferOrOptions);
   const dataJson = JSON.stringify(data);
   const dataIntArray = encoder.encode(dataJson);
-  webWorkerOps.postMessage(dataIntArray);
+  transferPorts(transfer, (rids): void => {
+    webWorkerOps.postMessage(dataIntArray, rids);
+  });
 }
 
 let isClosing = false;
@@ -60,10 +68,14 @@ export function close(): void {
   webWorkerOps.close();
 }
 
-export async function workerMessageRecvCallback(data: string): Promise<void> {
+export async function workerMessageRecvCallback(
+  data: string,
+  ports: number[]
+): Promise<void> {
   const msgEvent = new MessageEvent("message", {
     cancelable: false,
     data,
+    ports: receivePorts(ports),
   });
 
   try {
@@ -0,0 +1,55 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import {
+  unitTest,
+  assert,
+  assertEquals,
+  assertStrContains,
+} from "./test_util.ts";
+
+unitTest(async function importDataUrlPercentEncoded(): Promise<void> {
+  const mod = await import(
+    "data:application/javascript,export%20default%20'hello'"
+  );
+  assertEquals(mod.default, "hello");
+});
+
+unitTest(async function importDataUrlBase64(): Promise<void> {
+  // "export const answer = 42;"
+  const mod = await import(
+    "data:application/javascript;base64,ZXhwb3J0IGNvbnN0IGFuc3dlciA9IDQyOw=="
+  );
+  assertEquals(mod.answer, 42);
+});
+
+unitTest(async function importBlobUrl(): Promise<void> {
+  const blob = new Blob(["export const answer = 42;"], {
+    type: "application/javascript",
+  });
+  const url = URL.createObjectURL(blob);
+  const mod = await import(url);
+  assertEquals(mod.answer, 42);
+  URL.revokeObjectURL(url);
+});
+
+unitTest(async function fetchBlobUrl(): Promise<void> {
+  const blob = new Blob(["Hello World"], { type: "text/plain" });
+  const url = URL.createObjectURL(blob);
+  const response = await fetch(url);
+  assertEquals(response.status, 200);
+  assertEquals(response.headers.get("content-type"), "text/plain");
+  assertEquals(await response.text(), "Hello World");
+  URL.revokeObjectURL(url);
+});
+
+unitTest(async function fetchRevokedBlobUrl(): Promise<void> {
+  const url = URL.createObjectURL(new Blob(["Hello World"]));
+  URL.revokeObjectURL(url);
+  let err;
+  try {
+    await fetch(url);
+  } catch (err_) {
+    err = err_;
+  }
+  assert(err instanceof TypeError);
+  assertStrContains(err.message, "No blob found");
+});
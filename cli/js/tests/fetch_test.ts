@@ -319,6 +319,152 @@ unitTest({ perms: { net: true } }, async function fetchUserAgent(): Promise<
   await response.text();
 });
 
+unitTest(
+  { perms: { net: true } },
+  async function fetchInitReadableStreamBody(): Promise<void> {
+    const encoder = new TextEncoder();
+    const stream = new ReadableStream<Uint8Array>({
+      start(controller): void {
+        controller.enqueue(encoder.encode("Hello "));
+        controller.enqueue(encoder.encode("World"));
+        controller.close();
+      },
+    });
+    const response = await fetch("http://localhost:4545/echo_server", {
+      method: "POST",
+      body: stream,
+    });
+    assertEquals(await response.text(), "Hello World");
+  }
+);
+
+unitTest(
+  { perms: { net: true, read: true } },
+  async function fetchInitReaderBody(): Promise<void> {
+    const file = await Deno.open("cli/tests/fixture.json");
+    const response = await fetch("http://localhost:4545/echo_server", {
+      method: "POST",
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      body: file as any,
+    });
+    file.close();
+    const expected = new TextDecoder().decode(
+      await Deno.readFile("cli/tests/fixture.json")
+    );
+    assertEquals(await response.text(), expected);
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchUploadProgress(): Promise<void> {
+    const data = new Uint8Array(200 * 1024).fill(65);
+    const progress: number[] = [];
+    const response = await fetch("http://localhost:4545/echo_server", {
+      method: "POST",
+      body: data,
+      onUploadProgress: ({ loaded, total }): void => {
+        assertEquals(total, data.byteLength);
+        progress.push(loaded);
+      },
+    });
+    assertEquals((await response.arrayBuffer()).byteLength, data.byteLength);
+    assertEquals(progress, [65536, 131072, 196608, 204800]);
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchAbortedSignal(): Promise<void> {
+    const controller = new AbortController();
+    controller.abort();
+    let err;
+    try {
+      await fetch("http://localhost:4545/cli/tests/fixture.json", {
+        signal: controller.signal,
+      });
+    } catch (err_) {
+      err = err_;
+    }
+    assert(err instanceof DOMException);
+    assertEquals(err.name, "AbortError");
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchAbortWhileSendingBody(): Promise<void> {
+    const controller = new AbortController();
+    // A body that never ends, so the request only completes when aborted.
+    const stream = new ReadableStream<Uint8Array>({
+      start(controller): void {
+        controller.enqueue(new TextEncoder().encode("Hello"));
+      },
+    });
+    setTimeout(() => controller.abort(), 100);
+    let err;
+    try {
+      await fetch("http://localhost:4545/echo_server", {
+        method: "POST",
+        body: stream,
+        signal: controller.signal,
+      });
+    } catch (err_) {
+      err = err_;
+    }
+    assert(err instanceof DOMException);
+    assertEquals(err.name, "AbortError");
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchWithHttpClient(): Promise<void> {
+    const client = Deno.createHttpClient();
+    for (let i = 0; i < 2; i++) {
+      const response = await fetch(
+        "http://localhost:4545/cli/tests/fixture.json",
+        { client }
+      );
+      const json = await response.json();
+      assertEquals(json.name, "deno");
+    }
+    client.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true, read: true } },
+  async function fetchHttpClientCaData(): Promise<void> {
+    const client = Deno.createHttpClient({
+      caData: await Deno.readTextFile("std/http/testdata/tls/RootCA.pem"),
+    });
+    const response = await fetch(
+      "https://localhost:5545/cli/tests/fixture.json",
+      { client }
+    );
+    const json = await response.json();
+    assertEquals(json.name, "deno");
+    client.close();
+  }
+);
+
+unitTest(function createHttpClientCertWithoutKey(): void {
+  assertThrows(
+    () => {
+      Deno.createHttpClient({ clientCert: "cert" });
+    },
+    TypeError,
+    "Both clientCert and clientKey have to be given"
+  );
+});
+
+unitTest(function createHttpClientProxyPerm(): void {
+  assertThrows(() => {
+    Deno.createHttpClient({ proxy: "http://localhost:4545" });
+  }, Deno.errors.PermissionDenied);
+});
+
 // TODO(ry) The following tests work but are flaky. There's a race condition
 // somewhere. Here is what one of these flaky failures looks like:
 //
@@ -0,0 +1,28 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { unitTest, assert, assertEquals } from "./test_util.ts";
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function writeHeapSnapshotSuccess(): Promise<void> {
+    const filename = Deno.makeTempDirSync() + "/test.heapsnapshot";
+    await Deno.writeHeapSnapshot(filename);
+    const snapshot = JSON.parse(await Deno.readTextFile(filename));
+    assert("snapshot" in snapshot);
+    assert(Array.isArray(snapshot.nodes));
+    assert(Array.isArray(snapshot.strings));
+  }
+);
+
+unitTest(
+  { perms: { write: false } },
+  async function writeHeapSnapshotPerm(): Promise<void> {
+    let err;
+    try {
+      await Deno.writeHeapSnapshot("test.heapsnapshot");
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof Deno.errors.PermissionDenied);
+    assertEquals(err.name, "PermissionDenied");
+  }
+);
@@ -0,0 +1,189 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, unitTest } from "./test_util.ts";
+import { BufReader } from "../../../std/io/bufio.ts";
+import { TextProtoReader } from "../../../std/textproto/mod.ts";
+
+unitTest({ perms: { net: true } }, async function httpServerBasic(): Promise<
+  void
+> {
+  const listener = Deno.listen({ port: 4501 });
+  const server = (async (): Promise<Deno.HttpConn> => {
+    const conn = await listener.accept();
+    const httpConn = Deno.serveHttp(conn);
+    const event = await httpConn.nextRequest();
+    assert(event);
+    const { request } = event;
+    assertEquals(request.method, "GET");
+    assertEquals(request.url, "http://127.0.0.1:4501/hello?foo=bar");
+    assertEquals(request.headers.get("x-foo"), "bar");
+    await event.respondWith({
+      status: 201,
+      headers: { "content-type": "text/plain" },
+      body: "Hello World",
+    });
+    return httpConn;
+  })();
+
+  const response = await fetch("http://127.0.0.1:4501/hello?foo=bar", {
+    headers: { "x-foo": "bar" },
+  });
+  assertEquals(response.status, 201);
+  assertEquals(response.headers.get("content-type"), "text/plain");
+  assertEquals(await response.text(), "Hello World");
+  (await server).close();
+  listener.close();
+});
+
+unitTest(
+  { perms: { net: true } },
+  async function httpServerStreamingBodies(): Promise<void> {
+    const listener = Deno.listen({ port: 4501 });
+    const server = (async (): Promise<Deno.HttpConn> => {
+      const conn = await listener.accept();
+      const httpConn = Deno.serveHttp(conn);
+      const event = await httpConn.nextRequest();
+      assert(event);
+      assertEquals(event.request.method, "POST");
+      // Echo the request body while it is still being received.
+      await event.respondWith({ body: event.request.body });
+      return httpConn;
+    })();
+
+    const encoder = new TextEncoder();
+    const stream = new ReadableStream<Uint8Array>({
+      start(controller): void {
+        controller.enqueue(encoder.encode("Hello "));
+        controller.enqueue(encoder.encode("World"));
+        controller.close();
+      },
+    });
+    const response = await fetch("http://127.0.0.1:4501/echo", {
+      method: "POST",
+      body: stream,
+    });
+    assertEquals(await response.text(), "Hello World");
+    (await server).close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function httpServerKeepAlive(): Promise<void> {
+    const listener = Deno.listen({ port: 4501 });
+    const server = (async (): Promise<Deno.HttpConn> => {
+      const conn = await listener.accept();
+      const httpConn = Deno.serveHttp(conn);
+      let i = 0;
+      for await (const event of httpConn) {
+        await event.respondWith({ body: `request ${i++}` });
+        if (i === 2) {
+          break;
+        }
+      }
+      return httpConn;
+    })();
+
+    // Requests sent with the same client reuse the connection.
+    const client = Deno.createHttpClient();
+    for (let i = 0; i < 2; i++) {
+      const response = await fetch("http://127.0.0.1:4501/", { client });
+      assertEquals(await response.text(), `request ${i}`);
+    }
+    client.close();
+    (await server).close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function httpServerClosedConn(): Promise<void> {
+    const listener = Deno.listen({ port: 4501 });
+    const client = await Deno.connect({ port: 4501 });
+    const conn = await listener.accept();
+    const httpConn = Deno.serveHttp(conn);
+    client.close();
+    assertEquals(await httpConn.nextRequest(), null);
+    httpConn.close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function httpServerWebSocketUpgrade(): Promise<void> {
+    const listener = Deno.listen({ port: 4501 });
+    const server = (async (): Promise<Deno.HttpConn> => {
+      const conn = await listener.accept();
+      const httpConn = Deno.serveHttp(conn);
+      const event = await httpConn.nextRequest();
+      assert(event);
+      const upgraded = await event.upgradeWebSocket();
+      // Echo whatever is sent over the upgraded connection.
+      const buf = new Uint8Array(5);
+      const nread = await upgraded.read(buf);
+      await upgraded.write(buf.subarray(0, nread!));
+      upgraded.close();
+      return httpConn;
+    })();
+
+    const client = await Deno.connect({ port: 4501 });
+    await client.write(
+      new TextEncoder().encode(
+        "GET / HTTP/1.1\r\n" +
+          "Host: 127.0.0.1:4501\r\n" +
+          "Upgrade: websocket\r\n" +
+          "Connection: Upgrade\r\n" +
+          "Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n" +
+          "Sec-WebSocket-Version: 13\r\n\r\n"
+      )
+    );
+    const reader = new BufReader(client);
+    const tpr = new TextProtoReader(reader);
+    assertEquals(await tpr.readLine(), "HTTP/1.1 101 Switching Protocols");
+    const headers = await tpr.readMIMEHeader();
+    assert(headers);
+    assertEquals(
+      headers.get("sec-websocket-accept"),
+      "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+    await client.write(new TextEncoder().encode("hello"));
+    const buf = new Uint8Array(5);
+    assertEquals(await reader.readFull(buf), buf);
+    assertEquals(new TextDecoder().decode(buf), "hello");
+    client.close();
+    (await server).close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true, read: true } },
+  async function httpServerHttp2(): Promise<void> {
+    const listener = Deno.listenTls({
+      port: 4502,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      alpnProtocols: ["h2", "http/1.1"],
+    });
+    const server = (async (): Promise<Deno.HttpConn> => {
+      const conn = await listener.accept();
+      const httpConn = Deno.serveHttp(conn);
+      const event = await httpConn.nextRequest();
+      assert(event);
+      assertEquals(event.request.url, "https://localhost:4502/");
+      await event.respondWith({ body: "Hello HTTP/2" });
+      return httpConn;
+    })();
+
+    const client = Deno.createHttpClient({
+      caData: await Deno.readTextFile("cli/tests/tls/RootCA.pem"),
+    });
+    const response = await fetch("https://localhost:4502/", { client });
+    assertEquals(await response.text(), "Hello HTTP/2");
+    client.close();
+    (await server).close();
+    listener.close();
+  }
+);
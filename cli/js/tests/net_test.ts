@@ -525,3 +525,217 @@ unitTest(
     await resolvable;
   }
 );
+
+// Record data served by the stub DNS server, keyed by record type.
+const DNS_RECORDS: Record<number, Uint8Array[]> = {
+  // A
+  1: [new Uint8Array([127, 0, 0, 1]), new Uint8Array([10, 0, 0, 1])],
+  // NS
+  2: [encodeDnsName("ns1.example.com")],
+  // CNAME
+  5: [encodeDnsName("alias.example.com")],
+  // MX
+  15: [new Uint8Array([0, 10, ...encodeDnsName("mail.example.com")])],
+  // TXT
+  16: [new Uint8Array([5, ...new TextEncoder().encode("hello")])],
+  // AAAA
+  28: [new Uint8Array([...new Array(15).fill(0), 1])],
+  // SRV with priority 1, weight 2 and port 8080
+  33: [new Uint8Array([0, 1, 0, 2, 31, 144, ...encodeDnsName("srv.test")])],
+};
+
+function encodeDnsName(name: string): Uint8Array {
+  const bytes: number[] = [];
+  for (const label of name.split(".")) {
+    bytes.push(label.length, ...new TextEncoder().encode(label));
+  }
+  bytes.push(0);
+  return new Uint8Array(bytes);
+}
+
+// Answers a single DNS query with the records in `DNS_RECORDS`.
+async function serveDnsQuery(socket: Deno.DatagramConn): Promise<void> {
+  const [query, addr] = await socket.receive();
+  // Skip the header and the name of the question.
+  let offset = 12;
+  while (query[offset] !== 0) {
+    offset += query[offset] + 1;
+  }
+  const questionEnd = offset + 5;
+  const recordType = (query[offset + 1] << 8) | query[offset + 2];
+  const records = DNS_RECORDS[recordType] ?? [];
+
+  const response = [
+    ...query.subarray(0, 2),
+    0x81,
+    0x80,
+    0,
+    1,
+    0,
+    records.length,
+    0,
+    0,
+    0,
+    0,
+    ...query.subarray(12, questionEnd),
+  ];
+  for (const rdata of records) {
+    // Refer to the name in the question, and use a TTL of 60 seconds.
+    response.push(0xc0, 12, 0, recordType, 0, 1, 0, 0, 0, 60);
+    response.push(rdata.length >> 8, rdata.length & 0xff, ...rdata);
+  }
+  await socket.send(new Uint8Array(response), addr);
+}
+
+unitTest(
+  { perms: { net: true } },
+  async function netResolveDns(): Promise<void> {
+    const socket = Deno.listenDatagram({
+      hostname: "127.0.0.1",
+      port: 4553,
+      transport: "udp",
+    });
+    const nameServer = { ipAddr: "127.0.0.1", port: 4553 };
+    const resolve = async <T>(result: Promise<T>): Promise<T> => {
+      await serveDnsQuery(socket);
+      return result;
+    };
+
+    assertEquals(
+      await resolve(Deno.resolveDns("example.com", "A", { nameServer })),
+      ["127.0.0.1", "10.0.0.1"]
+    );
+    assertEquals(
+      await resolve(Deno.resolveDns("example.com", "AAAA", { nameServer })),
+      ["::1"]
+    );
+    assertEquals(
+      await resolve(Deno.resolveDns("example.com", "CNAME", { nameServer })),
+      ["alias.example.com."]
+    );
+    assertEquals(
+      await resolve(Deno.resolveDns("example.com", "NS", { nameServer })),
+      ["ns1.example.com."]
+    );
+    assertEquals(
+      await resolve(Deno.resolveDns("example.com", "MX", { nameServer })),
+      [{ preference: 10, exchange: "mail.example.com." }]
+    );
+    assertEquals(
+      await resolve(Deno.resolveDns("example.com", "SRV", { nameServer })),
+      [{ priority: 1, weight: 2, port: 8080, target: "srv.test." }]
+    );
+    assertEquals(
+      await resolve(Deno.resolveDns("example.com", "TXT", { nameServer })),
+      [["hello"]]
+    );
+    socket.close();
+  }
+);
+
+unitTest(async function netResolveDnsPermissionDenied(): Promise<void> {
+  let err;
+  try {
+    await Deno.resolveDns("example.com", "A", {
+      nameServer: { ipAddr: "127.0.0.1", port: 4553 },
+    });
+  } catch (e) {
+    err = e;
+  }
+  assert(err instanceof Deno.errors.PermissionDenied);
+});
+
+unitTest(
+  { perms: { net: true } },
+  async function netResolveDnsInvalidRecordType(): Promise<void> {
+    let err;
+    try {
+      // @ts-ignore
+      await Deno.resolveDns("example.com", "PTR");
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof TypeError);
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  function netUdpMulticastOptions(): void {
+    const socket = Deno.listenDatagram({
+      hostname: "0.0.0.0",
+      port: 4500,
+      transport: "udp",
+    });
+    socket.setBroadcast(true);
+    socket.setMulticastTTL(2);
+    socket.setMulticastLoopback(true);
+    socket.joinMulticastV4("224.0.0.251");
+    socket.leaveMulticast("224.0.0.251");
+
+    let err;
+    try {
+      socket.joinMulticastV4("not an address");
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof TypeError);
+    socket.close();
+  }
+);
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { net: true } },
+  async function netUdpMulticastLoopback(): Promise<void> {
+    const socket = Deno.listenDatagram({
+      hostname: "0.0.0.0",
+      port: 4500,
+      transport: "udp",
+    });
+    socket.joinMulticastV4("224.0.0.251");
+    socket.setMulticastLoopback(true);
+    socket.setMulticastTTL(1);
+
+    const sent = new Uint8Array([1, 2, 3]);
+    await socket.send(sent, {
+      hostname: "224.0.0.251",
+      port: 4500,
+      transport: "udp",
+    });
+    const [recvd, remote] = await socket.receive();
+    assertEquals(recvd, sent);
+    assertEquals(remote.transport, "udp");
+    socket.leaveMulticast("224.0.0.251");
+    socket.close();
+  }
+);
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { net: true } },
+  function netUdpv6MulticastTTL(): void {
+    const socket = Deno.listenDatagram({
+      hostname: "::1",
+      port: 4500,
+      transport: "udp",
+    });
+    socket.setMulticastTTL(2);
+    socket.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function netTcpSetNoDelayAndKeepAlive(): Promise<void> {
+    const listener = Deno.listen({ port: 4500 });
+    const acceptPromise = listener.accept();
+    const client = await Deno.connect({ port: 4500 });
+    const server = await acceptPromise;
+    client.setNoDelay();
+    client.setKeepAlive(true, 60 * 1000);
+    server.setNoDelay(false);
+    server.setKeepAlive(false);
+    client.close();
+    server.close();
+    listener.close();
+  }
+);
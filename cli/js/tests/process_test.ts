@@ -3,6 +3,7 @@ import {
   assert,
   assertEquals,
   assertStrContains,
+  assertThrows,
   unitTest,
 } from "./test_util.ts";
 const { kill, run, readFile, open, makeTempDir, writeFile } = Deno;
@@ -278,6 +279,174 @@ unitTest(
   }
 );
 
+unitTest(
+  { perms: { run: true } },
+  async function runStdinBuffer(): Promise<void> {
+    const p = run({
+      cmd: ["python", "-c", "import sys; print(sys.stdin.read().upper())"],
+      stdin: new TextEncoder().encode("hello"),
+      stdout: "piped",
+    });
+    assert(!p.stdin);
+    const output = new TextDecoder().decode(await p.output());
+    assertStrContains(output, "HELLO");
+    const status = await p.status();
+    assertEquals(status.code, 0);
+    p.close();
+  }
+);
+
+unitTest(
+  { perms: { run: true } },
+  async function runRedirectStdinFromChild(): Promise<void> {
+    const source = run({
+      cmd: ["python", "-c", "print('piped through')"],
+      stdout: "piped",
+    });
+    const sink = run({
+      cmd: ["python", "-c", "import sys; print(sys.stdin.read().upper())"],
+      stdin: source.stdout!.rid,
+      stdout: "piped",
+    });
+    const output = new TextDecoder().decode(await sink.output());
+    assertStrContains(output, "PIPED THROUGH");
+    assertEquals((await source.status()).code, 0);
+    assertEquals((await sink.status()).code, 0);
+
+    // The stdout of the first process was handed over to the second one.
+    assertThrows(() => {
+      source.stdout!.close();
+    }, Deno.errors.BadResource);
+    source.close();
+    sink.close();
+  }
+);
+
+unitTest(
+  { perms: { run: true, net: true } },
+  async function runRedirectStdoutToSocket(): Promise<void> {
+    const listener = Deno.listen({ port: 4502 });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connect({ port: 4502 }),
+    ]);
+    const p = run({
+      cmd: ["python", "-c", "print('over the socket')"],
+      stdout: clientConn.rid,
+    });
+    const received = new TextDecoder().decode(await Deno.readAll(serverConn));
+    assertStrContains(received, "over the socket");
+    assertEquals((await p.status()).code, 0);
+    p.close();
+    serverConn.close();
+    listener.close();
+  }
+);
+
+unitTest(function runSyncPermissions(): void {
+  let caughtError = false;
+  try {
+    Deno.runSync({ cmd: ["python", "-c", "print('hello world')"] });
+  } catch (e) {
+    caughtError = true;
+    assert(e instanceof Deno.errors.PermissionDenied);
+  }
+  assert(caughtError);
+});
+
+unitTest({ perms: { run: true } }, function runSyncOutput(): void {
+  const { success, code, stdout, stderr } = Deno.runSync({
+    cmd: [
+      "python",
+      "-c",
+      "import sys; sys.stdout.write('out'); sys.stderr.write('err'); " +
+        "sys.exit(3)",
+    ],
+  });
+  assertEquals(success, false);
+  assertEquals(code, 3);
+  const decoder = new TextDecoder();
+  assertEquals(decoder.decode(stdout), "out");
+  assertEquals(decoder.decode(stderr), "err");
+});
+
+unitTest({ perms: { run: true } }, function runSyncStdin(): void {
+  const { success, stdout, stderr } = Deno.runSync({
+    cmd: ["python", "-c", "import sys; sys.stdout.write(sys.stdin.read())"],
+    stdin: new TextEncoder().encode("echo"),
+    stderr: "null",
+  });
+  assert(success);
+  assertEquals(new TextDecoder().decode(stdout), "echo");
+  assertEquals(stderr.byteLength, 0);
+});
+
+unitTest({ perms: { run: true } }, function runSyncMaxBuffer(): void {
+  let err;
+  try {
+    Deno.runSync({
+      cmd: ["python", "-c", "print('x' * 1000000)"],
+      maxBuffer: 1024,
+    });
+  } catch (e) {
+    err = e;
+  }
+  assert(err instanceof Error);
+  assertStrContains(err.message, "stdout exceeded maxBuffer of 1024 bytes");
+});
+
+unitTest(
+  { perms: { run: true, net: true } },
+  async function runRedirectStdinAndStdoutToSocket(): Promise<void> {
+    const listener = Deno.listen({ port: 4503 });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connect({ port: 4503 }),
+    ]);
+    const p = run({
+      cmd: [
+        "python",
+        "-c",
+        "import sys; sys.stdout.write(sys.stdin.readline().upper())",
+      ],
+      stdin: clientConn.rid,
+      stdout: clientConn.rid,
+    });
+    await serverConn.write(new TextEncoder().encode("echo\n"));
+    const received = new TextDecoder().decode(await Deno.readAll(serverConn));
+    assertEquals(received, "ECHO\n");
+    assertEquals((await p.status()).code, 0);
+    p.close();
+    serverConn.close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { run: true, net: true } },
+  async function runSpawnFailureKeepsStdioResource(): Promise<void> {
+    const listener = Deno.listen({ port: 4504 });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connect({ port: 4504 }),
+    ]);
+    assertThrows(() => {
+      run({
+        cmd: ["this-command-does-not-exist"],
+        stdout: clientConn.rid,
+      });
+    }, Deno.errors.NotFound);
+
+    // The connection is only handed over once the child was spawned.
+    await clientConn.write(new TextEncoder().encode("still open"));
+    clientConn.close();
+    const received = new TextDecoder().decode(await Deno.readAll(serverConn));
+    assertEquals(received, "still open");
+    serverConn.close();
+    listener.close();
+  }
+);
+
 unitTest({ perms: { run: true } }, async function runEnv(): Promise<void> {
   const p = run({
     cmd: [
@@ -317,6 +486,67 @@ unitTest({ perms: { run: true } }, async function runClose(): Promise<void> {
   p.stderr!.close();
 });
 
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { run: true } },
+  async function runPty(): Promise<void> {
+    const p = run({
+      cmd: [
+        "python",
+        "-c",
+        "import os, sys; s = os.get_terminal_size(0); " +
+          "print(sys.stdout.isatty(), s.columns, s.lines)",
+      ],
+      pty: { cols: 100, rows: 30 },
+    });
+    assert(!p.stdin);
+    assert(!p.stdout);
+    assert(!p.stderr);
+    const output = new TextDecoder().decode(await Deno.readAll(p.pty!));
+    assertStrContains(output, "True 100 30");
+    const status = await p.status();
+    assertEquals(status.code, 0);
+    p.pty!.close();
+    p.close();
+  }
+);
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { run: true } },
+  async function runPtyResize(): Promise<void> {
+    const p = run({
+      cmd: [
+        "python",
+        "-c",
+        "import os; input(); s = os.get_terminal_size(0); " +
+          "print('size', s.columns, s.lines)",
+      ],
+      pty: { cols: 80, rows: 24 },
+    });
+    p.pty!.resize(120, 40);
+    await p.pty!.write(new TextEncoder().encode("\n"));
+    const output = new TextDecoder().decode(await Deno.readAll(p.pty!));
+    assertStrContains(output, "size 120 40");
+    const status = await p.status();
+    assertEquals(status.code, 0);
+    p.pty!.close();
+    p.close();
+  }
+);
+
+unitTest({ perms: { run: true } }, function runPtyWithStdio(): void {
+  let err;
+  try {
+    run({
+      cmd: ["python", "-c", "print('hello')"],
+      stdout: "piped",
+      pty: { cols: 80, rows: 24 },
+    });
+  } catch (e) {
+    err = e;
+  }
+  assert(err instanceof TypeError);
+});
+
 unitTest(function signalNumbers(): void {
   if (Deno.build.os === "darwin") {
     assertEquals(Deno.Signal.SIGSTOP, 17);
@@ -362,6 +592,105 @@ if (Deno.build.os !== "windows") {
     p.close();
   });
 
+  unitTest({ perms: { run: true } }, async function runTimeout(): Promise<
+    void
+  > {
+    const p = run({
+      cmd: ["python", "-c", "from time import sleep; sleep(10000)"],
+      timeout: 100,
+    });
+    const status = await p.status();
+    assertEquals(status.success, false);
+    assertEquals(status.signal, Deno.Signal.SIGTERM);
+    p.close();
+  });
+
+  unitTest(
+    { perms: { run: true } },
+    async function runTimeoutEscalatesToKill(): Promise<void> {
+      const p = run({
+        cmd: [
+          "python",
+          "-c",
+          "import signal, time; " +
+            "signal.signal(signal.SIGTERM, signal.SIG_IGN); time.sleep(10000)",
+        ],
+        timeout: 500,
+        killGracePeriod: 100,
+      });
+      const status = await p.status();
+      assertEquals(status.success, false);
+      assertEquals(status.signal, Deno.Signal.SIGKILL);
+      p.close();
+    }
+  );
+
+  unitTest(
+    { perms: { run: true } },
+    async function runTimeoutStopsWhenDetachedProcessIsClosed(): Promise<
+      void
+    > {
+      const p = run({
+        cmd: ["python", "-c", "import time; time.sleep(10000)"],
+        detached: true,
+        timeout: 100,
+      });
+      const pid = p.pid;
+      p.close();
+      await new Promise((resolve): number => setTimeout(resolve, 500));
+      // The group wasn't signalled by the timeout, so it's still around.
+      kill(-pid, Deno.Signal.SIGKILL);
+    }
+  );
+
+  unitTest(
+    { perms: { run: true } },
+    async function runUmaskAndRlimits(): Promise<void> {
+      const p = run({
+        cmd: [
+          "python",
+          "-c",
+          "import os, resource; " +
+            "print(os.umask(0), resource.getrlimit(resource.RLIMIT_NOFILE))",
+        ],
+        stdout: "piped",
+        umask: 0o077,
+        rlimits: { openFiles: 64 },
+      });
+      const output = new TextDecoder().decode(await p.output());
+      assertEquals(output.trim(), "63 (64, 64)");
+      p.close();
+    }
+  );
+
+  unitTest(
+    { perms: { run: true } },
+    async function runDetachedKillGroup(): Promise<void> {
+      const p = run({
+        cmd: [
+          "python",
+          "-c",
+          "import os, subprocess, sys; pid = os.getpid(); " +
+            "print(os.getsid(0) == pid, os.getpgid(0) == pid); " +
+            "sys.stdout.flush(); " +
+            "subprocess.call(['python', '-c', 'import time; time.sleep(1e4)'])",
+        ],
+        stdout: "piped",
+        detached: true,
+      });
+      const buf = new Uint8Array(64);
+      const n = await p.stdout!.read(buf);
+      const output = new TextDecoder().decode(buf.subarray(0, n!));
+      assertEquals(output.trim(), "True True");
+
+      p.kill(Deno.Signal.SIGKILL, { group: true });
+      const status = await p.status();
+      assertEquals(status.signal, Deno.Signal.SIGKILL);
+      p.stdout!.close();
+      p.close();
+    }
+  );
+
   unitTest({ perms: { run: true } }, function killFailed(): void {
     const p = run({
       cmd: ["python", "-c", "from time import sleep; sleep(10000)"],
@@ -2,6 +2,7 @@
 import {
   assert,
   assertEquals,
+  assertThrows,
   createResolvable,
   unitTest,
 } from "./test_util.ts";
@@ -260,3 +261,125 @@ unitTest(
     conn.close();
   }
 );
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsAlpnAndPemStrings(): Promise<void> {
+    const listener = Deno.listenTls({
+      hostname: "localhost",
+      port: 4500,
+      cert: await Deno.readTextFile("cli/tests/tls/localhost.crt"),
+      key: await Deno.readTextFile("cli/tests/tls/localhost.key"),
+      alpnProtocols: ["h2", "http/1.1"],
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname: "localhost",
+        port: 4500,
+        caCerts: [await Deno.readTextFile("cli/tests/tls/RootCA.pem")],
+        alpnProtocols: ["http/1.1"],
+      }),
+    ]);
+    assertEquals(serverConn.alpnProtocol, "http/1.1");
+    assertEquals(clientConn.alpnProtocol, "http/1.1");
+    assertEquals(serverConn.peerCertificates, []);
+    assertEquals(clientConn.peerCertificates.length, 1);
+    assert(
+      clientConn.peerCertificates[0].startsWith("-----BEGIN CERTIFICATE-----")
+    );
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsSniCerts(): Promise<void> {
+    const cert = await Deno.readTextFile("cli/tests/tls/localhost.crt");
+    const key = await Deno.readTextFile("cli/tests/tls/localhost.key");
+    const listener = Deno.listenTls({
+      hostname: "localhost",
+      port: 4500,
+      sniCerts: { localhost: { cert, key } },
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname: "localhost",
+        port: 4500,
+        certFile: "cli/tests/tls/RootCA.pem",
+      }),
+    ]);
+    assertEquals(clientConn.alpnProtocol, null);
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsClientAuth(): Promise<void> {
+    const rootCA = await Deno.readTextFile("cli/tests/tls/RootCA.pem");
+    const cert = await Deno.readTextFile("cli/tests/tls/localhost.crt");
+    const key = await Deno.readTextFile("cli/tests/tls/localhost.key");
+    const listener = Deno.listenTls({
+      hostname: "localhost",
+      port: 4500,
+      cert,
+      key,
+      clientCaCerts: [rootCA],
+    });
+
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname: "localhost",
+        port: 4500,
+        caCerts: [rootCA],
+        certChain: cert,
+        privateKey: key,
+      }),
+    ]);
+    assertEquals(serverConn.peerCertificates.length, 1);
+    serverConn.close();
+    clientConn.close();
+
+    // Clients without a certificate are rejected.
+    const accept = listener.accept();
+    const conn = await Deno.connectTls({
+      hostname: "localhost",
+      port: 4500,
+      caCerts: [rootCA],
+    }).catch(() => null);
+    let err;
+    try {
+      await accept;
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof Error);
+    conn?.close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsCertWithoutKey(): Promise<void> {
+    const cert = await Deno.readTextFile("cli/tests/tls/localhost.crt");
+    assertThrows(() => {
+      Deno.listenTls({ hostname: "localhost", port: 4500, cert });
+    }, TypeError);
+
+    let err;
+    try {
+      await Deno.connectTls({ port: 4500, certChain: cert });
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof TypeError);
+  }
+);
@@ -14,6 +14,7 @@ import "./chown_test.ts";
 import "./console_test.ts";
 import "./copy_file_test.ts";
 import "./custom_event_test.ts";
+import "./data_blob_url_test.ts";
 import "./dir_test.ts";
 import "./dispatch_minimal_test.ts";
 import "./dispatch_json_test.ts";
@@ -30,6 +31,8 @@ import "./fs_events_test.ts";
 import "./get_random_values_test.ts";
 import "./globals_test.ts";
 import "./headers_test.ts";
+import "./heap_snapshot_test.ts";
+import "./http_test.ts";
 import "./internals_test.ts";
 import "./io_test.ts";
 import "./link_test.ts";
@@ -66,6 +69,7 @@ import "./umask_test.ts";
 import "./url_test.ts";
 import "./url_search_params_test.ts";
 import "./utime_test.ts";
+import "./websocket_test.ts";
 import "./write_file_test.ts";
 import "./write_text_file_test.ts";
 import "./performance_test.ts";
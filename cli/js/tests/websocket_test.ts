@@ -0,0 +1,153 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import {
+  assert,
+  assertEquals,
+  assertThrows,
+  createResolvable,
+  unitTest,
+} from "./test_util.ts";
+import { BufReader, BufWriter } from "../../../std/io/bufio.ts";
+import { TextProtoReader } from "../../../std/textproto/mod.ts";
+import {
+  createSecAccept,
+  createWebSocket,
+  isWebSocketCloseEvent,
+  WebSocket as ServerWebSocket,
+} from "../../../std/ws/mod.ts";
+
+// Accepts a single WebSocket connection, selecting `protocol` if the client
+// offered it.
+async function acceptWebSocket(
+  listener: Deno.Listener,
+  protocol?: string
+): Promise<ServerWebSocket> {
+  const conn = await listener.accept();
+  const bufReader = new BufReader(conn);
+  const bufWriter = new BufWriter(conn);
+  const tpr = new TextProtoReader(bufReader);
+  const requestLine = await tpr.readLine();
+  assert(requestLine?.startsWith("GET "));
+  const headers = await tpr.readMIMEHeader();
+  assert(headers);
+  let response =
+    "HTTP/1.1 101 Switching Protocols\r\n" +
+    "Upgrade: websocket\r\n" +
+    "Connection: Upgrade\r\n" +
+    `Sec-WebSocket-Accept: ${createSecAccept(
+      headers.get("sec-websocket-key")!
+    )}\r\n`;
+  const offered = (headers.get("sec-websocket-protocol") ?? "").split(", ");
+  if (protocol && offered.includes(protocol)) {
+    response += `Sec-WebSocket-Protocol: ${protocol}\r\n`;
+  }
+  await bufWriter.write(new TextEncoder().encode(response + "\r\n"));
+  await bufWriter.flush();
+  return createWebSocket({ conn, bufReader, bufWriter });
+}
+
+unitTest(function websocketInvalidUrl(): void {
+  for (const url of ["http://localhost:4501", "ws://localhost/#hash", "?"]) {
+    const err = assertThrows(() => new WebSocket(url), DOMException);
+    assertEquals(err.name, "SyntaxError");
+  }
+});
+
+unitTest(function websocketPermissionDenied(): void {
+  assertThrows(() => {
+    new WebSocket("ws://localhost:4501");
+  }, Deno.errors.PermissionDenied);
+});
+
+unitTest(
+  { perms: { net: true } },
+  async function websocketEcho(): Promise<void> {
+    const listener = Deno.listen({ port: 4501 });
+    const server = (async (): Promise<void> => {
+      const sock = await acceptWebSocket(listener, "echo");
+      for await (const event of sock) {
+        if (isWebSocketCloseEvent(event)) {
+          break;
+        }
+        if (typeof event === "string" || event instanceof Uint8Array) {
+          await sock.send(event);
+        }
+      }
+    })();
+
+    const ws = new WebSocket("ws://localhost:4501/", ["chat", "echo"]);
+    assertEquals(ws.readyState, WebSocket.CONNECTING);
+    assertThrows(() => ws.send("too early"), DOMException);
+    ws.binaryType = "arraybuffer";
+    const messages: unknown[] = [];
+    const closed = createResolvable<CloseEvent>();
+    ws.onopen = (): void => {
+      assertEquals(ws.protocol, "echo");
+      ws.send("Hello");
+      ws.send(new Uint8Array([1, 2, 3]));
+    };
+    ws.onmessage = (e: MessageEvent): void => {
+      messages.push(e.data);
+      if (messages.length === 2) {
+        ws.close(3000, "done");
+      }
+    };
+    ws.addEventListener("close", (e) => closed.resolve(e as CloseEvent));
+    const event = await closed;
+    assertEquals(ws.readyState, WebSocket.CLOSED);
+    assert(event.wasClean);
+    assertEquals(event.code, 3000);
+    assertEquals(event.reason, "done");
+    const [text, binary] = messages;
+    assertEquals(text, "Hello");
+    assert(binary instanceof ArrayBuffer);
+    assertEquals(new Uint8Array(binary), new Uint8Array([1, 2, 3]));
+    await server;
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function websocketServerClose(): Promise<void> {
+    const listener = Deno.listen({ port: 4501 });
+    const server = (async (): Promise<void> => {
+      const sock = await acceptWebSocket(listener);
+      await sock.send(new Uint8Array([4, 5, 6]));
+      await sock.close(1000, "bye");
+    })();
+
+    const ws = new WebSocket("ws://localhost:4501/");
+    const message = createResolvable<MessageEvent>();
+    const closed = createResolvable<CloseEvent>();
+    ws.onmessage = (e): void => message.resolve(e);
+    ws.onclose = (e): void => closed.resolve(e);
+    const { data } = await message;
+    assert(data instanceof Blob);
+    assertEquals(
+      new Uint8Array(await data.arrayBuffer()),
+      new Uint8Array([4, 5, 6])
+    );
+    const event = await closed;
+    assertEquals(ws.protocol, "");
+    assertEquals(event.code, 1000);
+    assertEquals(event.reason, "bye");
+    await server;
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function websocketConnectionRefused(): Promise<void> {
+    const ws = new WebSocket("ws://localhost:4501/");
+    const errored = createResolvable<void>();
+    const closed = createResolvable<CloseEvent>();
+    ws.onerror = (): void => errored.resolve();
+    ws.onclose = (e): void => closed.resolve(e);
+    await errored;
+    const event = await closed;
+    assertEquals(event.code, 1006);
+    assert(!event.wasClean);
+    assertEquals(ws.readyState, WebSocket.CLOSED);
+  }
+);
@@ -1,14 +1,43 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import * as tlsOps from "./ops/tls.ts";
 import { Listener, Conn, ConnImpl, ListenerImpl } from "./net.ts";
+import { Addr } from "./ops/net.ts";
+
+export interface TlsConn extends Conn {
+  readonly alpnProtocol: string | null;
+  readonly peerCertificates: string[];
+}
+
+export class TlsConnImpl extends ConnImpl implements TlsConn {
+  readonly alpnProtocol: string | null;
+  readonly peerCertificates: string[];
+
+  constructor(
+    rid: number,
+    remoteAddr: Addr,
+    localAddr: Addr,
+    info: tlsOps.TLSInfo
+  ) {
+    super(rid, remoteAddr, localAddr);
+    this.alpnProtocol = info.alpnProtocol;
+    this.peerCertificates = info.peerCertificates;
+  }
+}
+
+interface ClientTlsOptions {
+  certFile?: string;
+  caCerts?: string[];
+  alpnProtocols?: string[];
+  certChain?: string;
+  privateKey?: string;
+}
 
 // TODO(ry) There are many configuration options to add...
 // https://docs.rs/rustls/0.16.0/rustls/struct.ClientConfig.html
-interface ConnectTlsOptions {
+interface ConnectTlsOptions extends ClientTlsOptions {
   transport?: "tcp";
   port: number;
   hostname?: string;
-  certFile?: string;
 }
 
 export async function connectTls({
@@ -16,20 +45,41 @@ export async function connectTls({
   hostname = "127.0.0.1",
   transport = "tcp",
   certFile = undefined,
-}: ConnectTlsOptions): Promise<Conn> {
+  caCerts,
+  alpnProtocols,
+  certChain,
+  privateKey,
+}: ConnectTlsOptions): Promise<TlsConn> {
   const res = await tlsOps.connectTls({
     port,
     hostname,
     transport,
     certFile,
+    caCerts,
+    alpnProtocols,
+    certChain,
+    privateKey,
   });
-  return new ConnImpl(res.rid, res.remoteAddr!, res.localAddr!);
+  return new TlsConnImpl(res.rid, res.remoteAddr, res.localAddr, res.info);
+}
+
+export interface TlsListener extends Listener, AsyncIterable<TlsConn> {
+  accept(): Promise<TlsConn>;
+  [Symbol.asyncIterator](): AsyncIterableIterator<TlsConn>;
 }
 
-class TLSListenerImpl extends ListenerImpl {
-  async accept(): Promise<Conn> {
+class TLSListenerImpl extends ListenerImpl implements TlsListener {
+  async accept(): Promise<TlsConn> {
     const res = await tlsOps.acceptTLS(this.rid);
-    return new ConnImpl(res.rid, res.remoteAddr, res.localAddr);
+    return new TlsConnImpl(res.rid, res.remoteAddr, res.localAddr, res.info);
+  }
+
+  async next(): Promise<IteratorResult<TlsConn>> {
+    return (await super.next()) as IteratorResult<TlsConn>;
+  }
+
+  [Symbol.asyncIterator](): AsyncIterableIterator<TlsConn> {
+    return this;
   }
 }
 
@@ -37,8 +87,14 @@ export interface ListenTlsOptions {
   port: number;
   hostname?: string;
   transport?: "tcp";
-  certFile: string;
-  keyFile: string;
+  certFile?: string;
+  keyFile?: string;
+  cert?: string;
+  key?: string;
+  sniCerts?: Record<string, { cert: string; key: string }>;
+  alpnProtocols?: string[];
+  clientCaCerts?: string[];
+  requireClientCert?: boolean;
 }
 
 export function listenTls({
@@ -47,30 +103,52 @@ export function listenTls({
   keyFile,
   hostname = "0.0.0.0",
   transport = "tcp",
-}: ListenTlsOptions): Listener {
+  cert,
+  key,
+  sniCerts,
+  alpnProtocols,
+  clientCaCerts,
+  requireClientCert,
+}: ListenTlsOptions): TlsListener {
   const res = tlsOps.listenTls({
     port,
     certFile,
     keyFile,
     hostname,
     transport,
+    cert,
+    key,
+    sniCerts,
+    alpnProtocols,
+    clientCaCerts,
+    requireClientCert,
   });
   return new TLSListenerImpl(res.rid, res.localAddr);
 }
 
-interface StartTlsOptions {
+interface StartTlsOptions extends ClientTlsOptions {
   hostname?: string;
-  certFile?: string;
 }
 
 export async function startTls(
   conn: Conn,
-  { hostname = "127.0.0.1", certFile = undefined }: StartTlsOptions = {}
-): Promise<Conn> {
+  {
+    hostname = "127.0.0.1",
+    certFile = undefined,
+    caCerts,
+    alpnProtocols,
+    certChain,
+    privateKey,
+  }: StartTlsOptions = {}
+): Promise<TlsConn> {
   const res = await tlsOps.startTls({
     rid: conn.rid,
     hostname,
     certFile,
+    caCerts,
+    alpnProtocols,
+    certChain,
+    privateKey,
   });
-  return new ConnImpl(res.rid, res.remoteAddr!, res.localAddr!);
+  return new TlsConnImpl(res.rid, res.remoteAddr, res.localAddr, res.info);
 }
@@ -75,6 +75,8 @@ function bufferFromStream(stream: ReadableStreamReader): Promise<ArrayBuffer> {
             parts.push(encoder.encode(value));
           } else if (value instanceof ArrayBuffer) {
             parts.push(new Uint8Array(value));
+          } else if (value instanceof Uint8Array) {
+            parts.push(value);
           } else if (!value) {
             // noop for undefined
           } else {
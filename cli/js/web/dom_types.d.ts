@@ -267,6 +267,11 @@ export interface RequestInit {
   referrerPolicy?: ReferrerPolicy;
   signal?: AbortSignal | null;
   window?: any;
+  client?: { readonly rid: number };
+  onUploadProgress?: (progress: {
+    loaded: number;
+    total: number | null;
+  }) => void;
 }
 
 export interface ResponseInit {
@@ -8,8 +8,52 @@ import * as io from "../io.ts";
 import { read } from "../ops/io.ts";
 import { close } from "../ops/resources.ts";
 import { Buffer } from "../buffer.ts";
-import { fetch as opFetch, FetchResponse } from "../ops/fetch.ts";
+import {
+  createHttpClient as opCreateHttpClient,
+  CreateHttpClientOptions,
+  fetch as opFetch,
+  fetchRequestWrite,
+  fetchSend,
+  FetchResponse,
+} from "../ops/fetch.ts";
 import { DomFileImpl } from "./dom_file.ts";
+import { DOMExceptionImpl as DOMException } from "./dom_exception.ts";
+import { ReadableStreamImpl } from "./streams/readable_stream.ts";
+import { errors } from "../errors.ts";
+
+// Size of the chunks in which request bodies are streamed.
+const REQUEST_BODY_CHUNK_SIZE = 64 * 1024;
+
+export type StreamBody = ReadableStream<Uint8Array> | io.Reader;
+
+interface UploadProgress {
+  loaded: number;
+  total: number | null;
+}
+
+/** A client for `fetch`, created by `Deno.createHttpClient`. Requests sent
+ * with the same client share its options and connection pool. */
+export class HttpClient {
+  constructor(readonly rid: number) {}
+
+  close(): void {
+    close(this.rid);
+  }
+}
+
+export function createHttpClient(
+  options: CreateHttpClientOptions = {}
+): HttpClient {
+  return new HttpClient(opCreateHttpClient(options));
+}
+
+function isReader(value: unknown): value is io.Reader {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    typeof (value as io.Reader).read === "function"
+  );
+}
 
 function getHeaderValueParams(value: string): Map<string, string> {
   const params = new Map();
@@ -28,6 +72,18 @@ function hasHeaderValueOf(s: string, value: string): boolean {
   return new RegExp(`^${value}[\t\s]*;?`).test(s);
 }
 
+// Called once a response body has been read to the end or closed, keyed by
+// the rid of the body.
+const bodyDoneHandlers = new Map<number, () => void>();
+
+function bodyDone(rid: number): void {
+  const handler = bodyDoneHandlers.get(rid);
+  if (handler) {
+    bodyDoneHandlers.delete(rid);
+    handler();
+  }
+}
+
 class Body
   implements domTypes.Body, ReadableStream<Uint8Array>, io.Reader, io.Closer {
   #bodyUsed = false;
@@ -220,12 +276,17 @@ class Body
     return decoder.decode(ab);
   }
 
-  read(p: Uint8Array): Promise<number | null> {
+  async read(p: Uint8Array): Promise<number | null> {
     this.#bodyUsed = true;
-    return read(this.#rid, p);
+    const nread = await read(this.#rid, p);
+    if (nread === null) {
+      bodyDone(this.#rid);
+    }
+    return nread;
   }
 
   close(): Promise<void> {
+    bodyDone(this.#rid);
     close(this.#rid);
     return Promise.resolve();
   }
@@ -464,24 +525,175 @@ export class Response implements domTypes.Response {
   }
 }
 
-function sendFetchReq(
+// Closes a resource that might already have been closed by the runtime.
+export function closeIfOpen(rid: number): void {
+  try {
+    close(rid);
+  } catch (e) {
+    if (!(e instanceof errors.BadResource)) {
+      throw e;
+    }
+  }
+}
+
+/** Splits a body into the chunks it is streamed in. */
+export async function* bodyChunks(
+  body: ArrayBufferView | StreamBody
+): AsyncIterableIterator<Uint8Array> {
+  if (body instanceof ReadableStreamImpl) {
+    const reader = body.getReader();
+    while (true) {
+      const { done, value } = await reader.read();
+      if (done) {
+        break;
+      }
+      if (!(value instanceof Uint8Array)) {
+        throw new TypeError("Body stream must yield Uint8Arrays.");
+      }
+      yield value;
+    }
+  } else if (isReader(body)) {
+    const buf = new Uint8Array(REQUEST_BODY_CHUNK_SIZE);
+    while (true) {
+      const nread = await body.read(buf);
+      if (nread === null) {
+        break;
+      }
+      // The chunk is copied when it is written, so `buf` can be reused.
+      yield buf.subarray(0, nread);
+    }
+  } else {
+    const view = body as ArrayBufferView;
+    const bytes = new Uint8Array(view.buffer, view.byteOffset, view.byteLength);
+    for (let i = 0; i < bytes.byteLength; i += REQUEST_BODY_CHUNK_SIZE) {
+      yield bytes.subarray(i, i + REQUEST_BODY_CHUNK_SIZE);
+    }
+  }
+}
+
+interface RequestBodyState {
+  error?: Error;
+}
+
+/** Writes a streamed request body. Errors of the body source abort the
+ * request and are stored in `state`, so they can be reported instead of the
+ * generic abort error. */
+async function writeRequestBody(
+  requestRid: number,
+  requestBodyRid: number,
+  body: ArrayBufferView | StreamBody,
+  state: RequestBodyState,
+  total: number | null,
+  onUploadProgress?: (progress: UploadProgress) => void
+): Promise<void> {
+  let loaded = 0;
+  try {
+    for await (const chunk of bodyChunks(body)) {
+      try {
+        await fetchRequestWrite(requestBodyRid, chunk);
+      } catch {
+        // The request failed or has already been completed without reading
+        // the whole body.
+        return;
+      }
+      loaded += chunk.byteLength;
+      if (onUploadProgress) {
+        onUploadProgress({ loaded, total });
+      }
+    }
+  } catch (e) {
+    state.error = e;
+    closeIfOpen(requestRid);
+  } finally {
+    closeIfOpen(requestBodyRid);
+  }
+}
+
+async function sendFetchReq(
   url: string,
   method: string | null,
   headers: Headers | null,
-  body: ArrayBufferView | undefined
+  body: ArrayBufferView | StreamBody | undefined,
+  clientRid: number | null,
+  signal: AbortSignal | null,
+  onUploadProgress?: (progress: UploadProgress) => void
 ): Promise<FetchResponse> {
+  if (signal?.aborted) {
+    throw new DOMException("Ongoing fetch was aborted.", "AbortError");
+  }
+
   let headerArray: Array<[string, string]> = [];
   if (headers) {
     headerArray = Array.from(headers.entries());
   }
 
+  // Buffers are sent in one piece unless upload progress is requested.
+  const isBuffer =
+    body != null && !isReader(body) && !(body instanceof ReadableStreamImpl);
+  const hasBody = body != null && !(isBuffer && !onUploadProgress);
+
   const args = {
     method,
     url,
     headers: headerArray,
+    hasBody,
+    clientRid,
   };
 
-  return opFetch(args, body);
+  const { requestRid, requestBodyRid } = opFetch(
+    args,
+    hasBody ? undefined : (body as ArrayBufferView | undefined)
+  );
+
+  const onAbort = (): void => {
+    closeIfOpen(requestRid);
+    if (requestBodyRid !== null) {
+      closeIfOpen(requestBodyRid);
+    }
+  };
+  signal?.addEventListener("abort", onAbort);
+
+  const bodyState: RequestBodyState = {};
+  try {
+    const responsePromise = fetchSend(requestRid);
+    if (requestBodyRid !== null) {
+      const contentLength = headers?.get("content-length");
+      let total: number | null = null;
+      if (contentLength) {
+        total = Number(contentLength);
+      } else if (isBuffer) {
+        total = (body as ArrayBufferView).byteLength;
+      }
+      // The body is written while waiting for the response, which may
+      // arrive before the whole body has been sent.
+      writeRequestBody(
+        requestRid,
+        requestBodyRid,
+        body!,
+        bodyState,
+        total,
+        onUploadProgress
+      );
+    }
+    const response = await responsePromise;
+    if (signal) {
+      const onBodyAbort = (): void => {
+        closeIfOpen(response.bodyRid);
+      };
+      signal.addEventListener("abort", onBodyAbort);
+      bodyDoneHandlers.set(response.bodyRid, (): void => {
+        signal.removeEventListener("abort", onBodyAbort);
+      });
+    }
+    return response;
+  } catch (e) {
+    if (signal?.aborted) {
+      throw new DOMException("Ongoing fetch was aborted.", "AbortError");
+    }
+    throw bodyState.error ?? e;
+  } finally {
+    signal?.removeEventListener("abort", onAbort);
+  }
 }
 
 export async function fetch(
@@ -491,7 +703,10 @@ export async function fetch(
   let url: string;
   let method: string | null = null;
   let headers: Headers | null = null;
-  let body: ArrayBufferView | undefined;
+  let body: ArrayBufferView | StreamBody | undefined;
+  let clientRid: number | null = null;
+  let signal: AbortSignal | null = null;
+  let onUploadProgress: ((progress: UploadProgress) => void) | undefined;
   let redirected = false;
   let remRedirectCount = 20; // TODO: use a better way to handle
 
@@ -499,6 +714,9 @@ export async function fetch(
     url = typeof input === "string" ? (input as string) : (input as URL).href;
     if (init != null) {
       method = init.method || null;
+      clientRid = init.client?.rid ?? null;
+      signal = init.signal ?? null;
+      onUploadProgress = init.onUploadProgress;
       if (init.headers) {
         headers =
           init.headers instanceof Headers
@@ -567,8 +785,12 @@ export async function fetch(
           payload += `\r\n--${boundary}--`;
           body = new TextEncoder().encode(payload);
           contentType = "multipart/form-data; boundary=" + boundary;
+        } else if (
+          init.body instanceof ReadableStreamImpl ||
+          isReader(init.body)
+        ) {
+          body = init.body;
         } else {
-          // TODO: ReadableStream
           notImplemented();
         }
         if (contentType && !headers.has("content-type")) {
@@ -588,7 +810,15 @@ export async function fetch(
   }
 
   while (remRedirectCount) {
-    const fetchResponse = await sendFetchReq(url, method, headers, body);
+    const fetchResponse = await sendFetchReq(
+      url,
+      method,
+      headers,
+      body,
+      clientRid,
+      signal,
+      onUploadProgress
+    );
 
     const response = new Response(
       url,
@@ -601,6 +831,7 @@ export async function fetch(
     if ([301, 302, 303, 307, 308].includes(response.status)) {
       // We won't use body of received response, so close it now
       // otherwise it will be kept in resource table.
+      bodyDone(fetchResponse.bodyRid);
       close(fetchResponse.bodyRid);
       // We're in a redirect status
       switch ((init && init.redirect) || "follow") {
@@ -626,6 +857,11 @@ export async function fetch(
               url.split("//")[1].split("/")[0] +
               redirectUrl; // TODO: handle relative redirection more gracefully
           }
+          if (body instanceof ReadableStreamImpl || isReader(body)) {
+            throw new TypeError(
+              "Cannot follow a redirect with a streamed request body."
+            );
+          }
           url = redirectUrl;
           redirected = true;
           remRedirectCount--;
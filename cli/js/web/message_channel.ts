@@ -0,0 +1,301 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import {
+  broadcastPostMessage,
+  broadcastRecvMessage,
+  broadcastSubscribe,
+  createMessageChannel,
+  messagePortPostMessage,
+  messagePortRecvMessage,
+} from "../ops/worker_host.ts";
+import { close } from "../ops/resources.ts";
+import { DOMExceptionImpl as DOMException } from "./dom_exception.ts";
+import { EventTargetImpl as EventTarget } from "./event_target.ts";
+import { MessageEvent, decodeMessage, encodeMessage } from "./workers.ts";
+
+const illegalConstructorKey = Symbol("illegalConstructorKey");
+const portRid = Symbol("portRid");
+const detachPort = Symbol("detachPort");
+
+type MessageHandler = ((this: any, ev: MessageEvent) => any) | null;
+
+/** Normalizes the second argument of `postMessage()`, which may either be a
+ * transfer list or an options bag. */
+export function getTransferList(transferOrOptions?: any): unknown[] {
+  if (transferOrOptions == null) {
+    return [];
+  }
+  if (Array.isArray(transferOrOptions)) {
+    return transferOrOptions;
+  }
+  const transfer = transferOrOptions.transfer ?? [];
+  if (!Array.isArray(transfer)) {
+    throw new TypeError("Transfer list must be an array");
+  }
+  return transfer;
+}
+
+/** Sends the message ports in `transfer` along with a message. `send` is
+ * given their resource ids and the ports are only detached once it returns,
+ * so they stay usable if sending throws. Message ports are the only
+ * transferable objects. */
+export function transferPorts(
+  transfer: unknown[],
+  send: (rids: number[]) => void
+): void {
+  const ports = transfer.map((item, i): MessagePortImpl => {
+    if (!(item instanceof MessagePortImpl)) {
+      throw new DOMException(
+        "Value in transfer list is not transferable",
+        "DataCloneError"
+      );
+    }
+    if (transfer.indexOf(item) !== i) {
+      throw new DOMException(
+        "Duplicate port in transfer list",
+        "DataCloneError"
+      );
+    }
+    return item;
+  });
+  const rids = ports.map((port): number => {
+    const rid = port[portRid];
+    if (rid === null) {
+      throw new DOMException(
+        "Port is closed or already transferred",
+        "DataCloneError"
+      );
+    }
+    return rid;
+  });
+  send(rids);
+  for (const port of ports) {
+    port[detachPort]();
+  }
+}
+
+/** Wraps the resource ids of ports received with a message. */
+export function receivePorts(rids: number[]): MessagePortImpl[] {
+  return rids.map(
+    (rid): MessagePortImpl => new MessagePortImpl(illegalConstructorKey, rid)
+  );
+}
+
+/** Closes the ports received with a message that is not dispatched, so their
+ * peers see the channel end. */
+export function closePorts(rids: number[]): void {
+  for (const rid of rids) {
+    close(rid);
+  }
+}
+
+function dispatchMessage(
+  target: EventTarget & {
+    onmessage: MessageHandler;
+    onmessageerror: MessageHandler;
+  },
+  msg: { data: number[]; ports?: number[] }
+): void {
+  const ports = msg.ports ?? [];
+  let data;
+  try {
+    data = decodeMessage(new Uint8Array(msg.data));
+  } catch (e) {
+    closePorts(ports);
+    const errorEvent = new MessageEvent("messageerror", { cancelable: false });
+    if (target.onmessageerror) {
+      target.onmessageerror(errorEvent);
+    }
+    target.dispatchEvent(errorEvent);
+    return;
+  }
+
+  const event = new MessageEvent("message", {
+    cancelable: false,
+    data,
+    ports: receivePorts(ports),
+  });
+  if (target.onmessage) {
+    target.onmessage(event);
+  }
+  target.dispatchEvent(event);
+}
+
+export class MessagePortImpl extends EventTarget implements MessagePort {
+  #rid: number | null;
+  #started = false;
+  #onmessage: MessageHandler = null;
+  onmessageerror: MessageHandler = null;
+
+  constructor(key: symbol, rid: number) {
+    super();
+    if (key !== illegalConstructorKey) {
+      throw new TypeError("Illegal constructor.");
+    }
+    this.#rid = rid;
+  }
+
+  get onmessage(): MessageHandler {
+    return this.#onmessage;
+  }
+
+  set onmessage(handler: MessageHandler) {
+    this.#onmessage = handler;
+    // Assigning a handler implicitly starts the port, like in browsers.
+    this.start();
+  }
+
+  /** Sends `message` to the entangled port, along with the ports in the
+   * transfer list. Messages posted after either side is
+   * closed are dropped, closing the ports sent with them. */
+  postMessage(message: any, transferOrOptions?: any): void {
+    const transfer = getTransferList(transferOrOptions);
+    if (transfer.includes(this)) {
+      throw new DOMException(
+        "Port can't be transferred through itself",
+        "DataCloneError"
+      );
+    }
+    const data = encodeMessage(message);
+    transferPorts(transfer, (rids): void => {
+      if (this.#rid !== null) {
+        messagePortPostMessage(this.#rid, data, rids);
+      } else {
+        closePorts(rids);
+      }
+    });
+  }
+
+  /** Starts dispatching messages received by the port. The port keeps the
+   * event loop alive until it is closed. */
+  start(): void {
+    if (this.#started || this.#rid === null) {
+      return;
+    }
+    this.#started = true;
+    this.#poll(this.#rid);
+  }
+
+  close(): void {
+    if (this.#rid !== null) {
+      close(this.#rid);
+      this.#rid = null;
+    }
+  }
+
+  #poll = async (rid: number): Promise<void> => {
+    while (this.#rid === rid) {
+      const msg = await messagePortRecvMessage(rid);
+      if (msg === null) {
+        return;
+      }
+      if (this.#rid !== rid) {
+        closePorts(msg.ports);
+        return;
+      }
+      dispatchMessage(this, msg);
+    }
+  };
+
+  get [portRid](): number | null {
+    return this.#rid;
+  }
+
+  /** Forgets the port after its resource was sent to another port or worker. */
+  [detachPort](): void {
+    this.#rid = null;
+  }
+}
+
+export class MessageChannelImpl implements MessageChannel {
+  readonly port1: MessagePortImpl;
+  readonly port2: MessagePortImpl;
+
+  constructor() {
+    const { port1, port2 } = createMessageChannel();
+    this.port1 = new MessagePortImpl(illegalConstructorKey, port1);
+    this.port2 = new MessagePortImpl(illegalConstructorKey, port2);
+  }
+}
+
+export class BroadcastChannelImpl extends EventTarget
+  implements BroadcastChannel {
+  readonly #name: string;
+  #rid: number | null;
+  #started = false;
+  #onmessage: MessageHandler = null;
+  onmessageerror: MessageHandler = null;
+
+  constructor(name: string) {
+    super();
+    if (arguments.length === 0) {
+      throw new TypeError("BroadcastChannel name is required.");
+    }
+    this.#name = String(name);
+    this.#rid = broadcastSubscribe(this.#name);
+  }
+
+  get name(): string {
+    return this.#name;
+  }
+
+  get onmessage(): MessageHandler {
+    return this.#onmessage;
+  }
+
+  set onmessage(handler: MessageHandler) {
+    this.#onmessage = handler;
+    this.#start();
+  }
+
+  addEventListener(
+    type: string,
+    callback: EventListenerOrEventListenerObject | null,
+    options?: AddEventListenerOptions | boolean
+  ): void {
+    super.addEventListener(type, callback, options);
+    if (type === "message") {
+      this.#start();
+    }
+  }
+
+  /** Sends `message` to every other `BroadcastChannel` with the same name,
+   * in this or any other worker. */
+  postMessage(message: any): void {
+    if (this.#rid === null) {
+      throw new DOMException(
+        "BroadcastChannel is closed",
+        "InvalidStateError"
+      );
+    }
+    broadcastPostMessage(this.#rid, encodeMessage(message));
+  }
+
+  close(): void {
+    if (this.#rid !== null) {
+      close(this.#rid);
+      this.#rid = null;
+    }
+  }
+
+  /** Messages are only received once there is someone to dispatch them to,
+   * as a pending receive keeps the event loop alive until the channel is
+   * closed. */
+  #start = (): void => {
+    if (this.#started || this.#rid === null) {
+      return;
+    }
+    this.#started = true;
+    this.#poll(this.#rid);
+  };
+
+  #poll = async (rid: number): Promise<void> => {
+    while (this.#rid === rid) {
+      const msg = await broadcastRecvMessage(rid);
+      if (msg === null || this.#rid !== rid) {
+        return;
+      }
+      dispatchMessage(this, msg);
+    }
+  };
+}
@@ -1,6 +1,7 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { build } from "../build.ts";
-import { getRandomValues } from "../ops/get_random_values.ts";
+import * as blobOps from "../ops/blob.ts";
+import { DenoBlob, bytesSymbol as blobBytesSymbol } from "./blob.ts";
 import { customInspect } from "./console.ts";
 import { urls } from "./url_search_params.ts";
 
@@ -99,15 +100,6 @@ function parse(url: string, isBase = true): URLParts | undefined {
   return parts as URLParts;
 }
 
-// Based on https://github.com/kelektiv/node-uuid
-// TODO(kevinkassimo): Use deno_std version once possible.
-function generateUUID(): string {
-  return "00000000-0000-4000-8000-000000000000".replace(/[0]/g, (): string =>
-    // random integer from 0 to 15 as a hex digit.
-    (getRandomValues(new Uint8Array(1))[0] % 16).toString(16)
-  );
-}
-
 // Keep it outside of URL to avoid any attempts of access.
 export const blobURLMap = new Map<string, Blob>();
 
@@ -453,8 +445,11 @@ export class URLImpl implements URL {
 
   // TODO(kevinkassimo): implement MediaSource version in the future.
   static createObjectURL(b: Blob): string {
-    const origin = "http://deno-opaque-origin";
-    const key = `blob:${origin}/${generateUUID()}`;
+    // The bytes are registered with the runtime, so the URL can be imported
+    // and fetched.
+    const bytes =
+      b instanceof DenoBlob ? b[blobBytesSymbol] : new Uint8Array();
+    const key = blobOps.createObjectURL(b.type, bytes);
     blobURLMap.set(key, b);
     return key;
   }
@@ -472,5 +467,6 @@ export class URLImpl implements URL {
     // Origin match check seems irrelevant for now, unless we implement
     // persisten storage for per globalThis.location.origin at some point.
     blobURLMap.delete(url);
+    blobOps.revokeObjectURL(url);
   }
 }
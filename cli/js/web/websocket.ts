@@ -0,0 +1,338 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import * as wsOps from "../ops/websocket.ts";
+import { close } from "../ops/resources.ts";
+import { DenoBlob } from "./blob.ts";
+import { DOMExceptionImpl as DOMException } from "./dom_exception.ts";
+import { EventImpl as Event } from "./event.ts";
+import { EventTargetImpl as EventTarget } from "./event_target.ts";
+import { TextEncoder } from "./text_encoding.ts";
+import { URLImpl as URL } from "./url.ts";
+import { ErrorEvent, MessageEvent } from "./workers.ts";
+
+const CONNECTING = 0;
+const OPEN = 1;
+const CLOSING = 2;
+const CLOSED = 3;
+
+// Close code used when the connection was closed without a closing handshake.
+const ABNORMAL_CLOSURE = 1006;
+
+export interface CloseEventInit extends EventInit {
+  code?: number;
+  reason?: string;
+  wasClean?: boolean;
+}
+
+export class CloseEventImpl extends Event implements CloseEvent {
+  readonly code: number;
+  readonly reason: string;
+  readonly wasClean: boolean;
+
+  constructor(type: string, eventInitDict?: CloseEventInit) {
+    super(type, {
+      bubbles: eventInitDict?.bubbles ?? false,
+      cancelable: eventInitDict?.cancelable ?? false,
+      composed: eventInitDict?.composed ?? false,
+    });
+
+    this.code = eventInitDict?.code ?? 0;
+    this.reason = eventInitDict?.reason ?? "";
+    this.wasClean = eventInitDict?.wasClean ?? false;
+  }
+}
+
+export class WebSocketImpl extends EventTarget implements WebSocket {
+  static readonly CONNECTING = CONNECTING;
+  static readonly OPEN = OPEN;
+  static readonly CLOSING = CLOSING;
+  static readonly CLOSED = CLOSED;
+
+  readonly CONNECTING = CONNECTING;
+  readonly OPEN = OPEN;
+  readonly CLOSING = CLOSING;
+  readonly CLOSED = CLOSED;
+
+  #rid: number | null = null;
+  #readyState = CONNECTING;
+  #url: string;
+  #protocol = "";
+  #extensions = "";
+  #binaryType: BinaryType = "blob";
+  #bufferedAmount = 0;
+  // Messages are sent one after another so that they arrive in order.
+  #sendQueue: Promise<void> = Promise.resolve();
+
+  onopen: ((ev: Event) => any) | null = null;
+  onmessage: ((ev: MessageEvent) => any) | null = null;
+  onerror: ((ev: Event | ErrorEvent) => any) | null = null;
+  onclose: ((ev: CloseEvent) => any) | null = null;
+
+  constructor(url: string, protocols: string | string[] = []) {
+    super();
+    let wsURL: URL;
+    try {
+      wsURL = new URL(url);
+    } catch {
+      throw new DOMException(`Invalid URL "${url}".`, "SyntaxError");
+    }
+    if (wsURL.protocol !== "ws:" && wsURL.protocol !== "wss:") {
+      throw new DOMException(
+        `The URL's scheme must be 'ws' or 'wss', got '${wsURL.protocol}'.`,
+        "SyntaxError"
+      );
+    }
+    if (wsURL.hash !== "") {
+      throw new DOMException(
+        "The URL must not contain a fragment.",
+        "SyntaxError"
+      );
+    }
+    if (typeof protocols === "string") {
+      protocols = [protocols];
+    }
+    if (new Set(protocols).size !== protocols.length) {
+      throw new DOMException(
+        "The subprotocols must not contain duplicates.",
+        "SyntaxError"
+      );
+    }
+
+    this.#url = wsURL.href;
+    wsOps.checkPermission(this.#url);
+
+    wsOps.create(this.#url, protocols).then(
+      ({ rid, protocol, extensions }) => {
+        this.#rid = rid;
+        if (this.#readyState === CLOSING) {
+          // `close()` was called before the connection was established.
+          this.#fail();
+          return;
+        }
+        this.#protocol = protocol;
+        this.#extensions = extensions;
+        this.#readyState = OPEN;
+        this.#dispatch(new Event("open"));
+        this.#eventLoop();
+      },
+      (err: Error) => {
+        this.#fail(err.message);
+      }
+    );
+  }
+
+  get readyState(): number {
+    return this.#readyState;
+  }
+
+  get url(): string {
+    return this.#url;
+  }
+
+  get protocol(): string {
+    return this.#protocol;
+  }
+
+  get extensions(): string {
+    return this.#extensions;
+  }
+
+  get bufferedAmount(): number {
+    return this.#bufferedAmount;
+  }
+
+  get binaryType(): BinaryType {
+    return this.#binaryType;
+  }
+
+  set binaryType(value: BinaryType) {
+    if (value === "blob" || value === "arraybuffer") {
+      this.#binaryType = value;
+    }
+  }
+
+  send(data: string | ArrayBufferLike | ArrayBufferView | Blob): void {
+    if (this.#readyState === CONNECTING) {
+      throw new DOMException(
+        "The WebSocket is still connecting.",
+        "InvalidStateError"
+      );
+    }
+    if (this.#readyState !== OPEN) {
+      return;
+    }
+
+    let payload: string | Uint8Array | Promise<ArrayBuffer>;
+    let size: number;
+    if (typeof data === "string") {
+      payload = data;
+      size = new TextEncoder().encode(data).byteLength;
+    } else if (ArrayBuffer.isView(data)) {
+      payload = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+      size = data.byteLength;
+    } else if (data instanceof DenoBlob) {
+      payload = data.arrayBuffer();
+      size = data.size;
+    } else {
+      payload = new Uint8Array(data as ArrayBufferLike);
+      size = payload.byteLength;
+    }
+
+    const rid = this.#rid!;
+    this.#bufferedAmount += size;
+    this.#sendQueue = this.#sendQueue
+      .then(async () => {
+        const message = await payload;
+        await wsOps.send(
+          rid,
+          message instanceof ArrayBuffer ? new Uint8Array(message) : message
+        );
+      })
+      .catch(() => {
+        // Failures surface through the event loop as an error event.
+      })
+      .finally(() => {
+        this.#bufferedAmount -= size;
+      });
+  }
+
+  close(code?: number, reason?: string): void {
+    if (
+      code !== undefined &&
+      !(code === 1000 || (code >= 3000 && code <= 4999))
+    ) {
+      throw new DOMException(
+        "The close code must be either 1000 or in the range of 3000 to 4999.",
+        "InvalidAccessError"
+      );
+    }
+    if (
+      reason !== undefined &&
+      new TextEncoder().encode(reason).byteLength > 123
+    ) {
+      throw new DOMException(
+        "The close reason may not be longer than 123 bytes.",
+        "SyntaxError"
+      );
+    }
+
+    if (this.#readyState === CONNECTING) {
+      this.#readyState = CLOSING;
+    } else if (this.#readyState === OPEN) {
+      this.#readyState = CLOSING;
+      const rid = this.#rid!;
+      this.#sendQueue = this.#sendQueue.then(() =>
+        wsOps.close(rid, code, reason).catch(() => {
+          // Failures surface through the event loop as an error event.
+        })
+      );
+    }
+  }
+
+  #dispatch = (event: Event): void => {
+    let handler: ((ev: any) => any) | null = null;
+    switch (event.type) {
+      case "open":
+        handler = this.onopen;
+        break;
+      case "message":
+        handler = this.onmessage;
+        break;
+      case "error":
+        handler = this.onerror;
+        break;
+      case "close":
+        handler = this.onclose;
+        break;
+    }
+    if (handler) {
+      handler.call(this, event);
+    }
+    this.dispatchEvent(event);
+  };
+
+  #closeResource = (): void => {
+    if (this.#rid !== null) {
+      try {
+        close(this.#rid);
+      } catch {
+        // The resource is already closed.
+      }
+    }
+  };
+
+  // Closes the connection without a closing handshake.
+  #fail = (message?: string): void => {
+    this.#closeResource();
+    this.#readyState = CLOSED;
+    this.#dispatch(new ErrorEvent("error", { message }));
+    this.#dispatch(
+      new CloseEventImpl("close", {
+        code: ABNORMAL_CLOSURE,
+        wasClean: false,
+      })
+    );
+  };
+
+  #eventLoop = async (): Promise<void> => {
+    const rid = this.#rid!;
+    while (this.#readyState !== CLOSED) {
+      let event: wsOps.WebSocketEvent;
+      try {
+        event = await wsOps.nextEvent(rid);
+      } catch (e) {
+        this.#fail(e.message);
+        break;
+      }
+      switch (event.kind) {
+        case "string": {
+          this.#dispatch(
+            new MessageEvent("message", {
+              data: event.data,
+              origin: this.#url,
+            })
+          );
+          break;
+        }
+        case "binary": {
+          const bytes = event.data;
+          this.#dispatch(
+            new MessageEvent("message", {
+              data:
+                this.#binaryType === "blob"
+                  ? new DenoBlob([bytes])
+                  : bytes.buffer,
+              origin: this.#url,
+            })
+          );
+          break;
+        }
+        case "close": {
+          if (this.#readyState === OPEN) {
+            // The server started the closing handshake, send the reply.
+            this.#readyState = CLOSING;
+            await wsOps.close(rid).catch(() => {});
+          }
+          this.#closeResource();
+          this.#readyState = CLOSED;
+          this.#dispatch(
+            new CloseEventImpl("close", {
+              code: event.code,
+              reason: event.reason,
+              wasClean: true,
+            })
+          );
+          break;
+        }
+        case "closed": {
+          this.#fail();
+          break;
+        }
+        case "error": {
+          this.#fail(event.message);
+          break;
+        }
+      }
+    }
+  };
+}
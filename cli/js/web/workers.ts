@@ -5,6 +5,7 @@ import {
   hostTerminateWorker,
   hostPostMessage,
   hostGetMessage,
+  WorkerPermissions,
 } from "../ops/worker_host.ts";
 import { log } from "../util.ts";
 import { TextDecoder, TextEncoder } from "./text_encoding.ts";
@@ -13,6 +14,13 @@ import { blobURLMap } from "./web/url.ts";
 */
 import { EventImpl as Event } from "./event.ts";
 import { EventTargetImpl as EventTarget } from "./event_target.ts";
+import {
+  MessagePortImpl,
+  closePorts,
+  getTransferList,
+  receivePorts,
+  transferPorts,
+} from "./message_channel.ts";
 
 const encoder = new TextEncoder();
 const decoder = new TextDecoder();
@@ -21,12 +29,14 @@ export interface MessageEventInit extends EventInit {
   data?: any;
   origin?: string;
   lastEventId?: string;
+  ports?: MessagePortImpl[];
 }
 
 export class MessageEvent extends Event {
   readonly data: any;
   readonly origin: string;
   readonly lastEventId: string;
+  readonly ports: ReadonlyArray<MessagePortImpl>;
 
   constructor(type: string, eventInitDict?: MessageEventInit) {
     super(type, {
@@ -38,6 +48,7 @@ export class MessageEvent extends Event {
     this.data = eventInitDict?.data ?? null;
     this.origin = eventInitDict?.origin ?? "";
     this.lastEventId = eventInitDict?.lastEventId ?? "";
+    this.ports = eventInitDict?.ports ?? [];
   }
 }
 
@@ -71,12 +82,12 @@ export class ErrorEvent extends Event {
   }
 }
 
-function encodeMessage(data: any): Uint8Array {
+export function encodeMessage(data: any): Uint8Array {
   const dataJson = JSON.stringify(data);
   return encoder.encode(dataJson);
 }
 
-function decodeMessage(dataIntArray: Uint8Array): any {
+export function decodeMessage(dataIntArray: Uint8Array): any {
   const dataJson = decoder.decode(dataIntArray);
   return JSON.parse(dataJson);
 }
@@ -91,6 +102,7 @@ interface WorkerHostError {
 interface WorkerHostMessage {
   type: "terminalError" | "error" | "msg";
   data?: any;
+  ports?: number[];
   error?: WorkerHostError;
 }
 
@@ -98,14 +110,14 @@ export interface Worker {
   onerror?: (e: ErrorEvent) => void;
   onmessage?: (e: MessageEvent) => void;
   onmessageerror?: (e: MessageEvent) => void;
-  postMessage(data: any): void;
+  postMessage(data: any, transferOrOptions?: any): void;
   terminate(): void;
 }
 
 export interface WorkerOptions {
   type?: "classic" | "module";
   name?: string;
-  deno?: boolean;
+  deno?: boolean | { permissions?: "inherit" | WorkerPermissions };
 }
 
 export class WorkerImpl extends EventTarget implements Worker {
@@ -148,23 +160,30 @@ export class WorkerImpl extends EventTarget implements Worker {
     */
 
     const useDenoNamespace = options ? !!options.deno : false;
+    let permissions: WorkerPermissions | undefined;
+    if (typeof options?.deno === "object") {
+      const requested = options.deno.permissions;
+      permissions = requested === "inherit" ? undefined : requested;
+    }
 
     const { id } = createWorker(
       specifier,
       hasSourceCode,
       sourceCode,
       useDenoNamespace,
-      options?.name
+      options?.name,
+      permissions
     );
     this.#id = id;
     this.#poll();
   }
 
-  #handleMessage = (msgData: any): void => {
+  #handleMessage = (msgData: any, ports: number[]): void => {
     let data;
     try {
       data = decodeMessage(new Uint8Array(msgData));
     } catch (e) {
+      closePorts(ports);
       const msgErrorEvent = new MessageEvent("messageerror", {
         cancelable: false,
         data,
@@ -178,6 +197,7 @@ export class WorkerImpl extends EventTarget implements Worker {
     const msgEvent = new MessageEvent("message", {
       cancelable: false,
       data,
+      ports: receivePorts(ports),
     });
 
     if (this.onmessage) {
@@ -216,6 +236,7 @@ export class WorkerImpl extends EventTarget implements Worker {
 
       // If terminate was called then we ignore all messages
       if (this.#terminated) {
+        closePorts(event.ports ?? []);
         return;
       }
 
@@ -230,7 +251,7 @@ export class WorkerImpl extends EventTarget implements Worker {
       }
 
       if (type === "msg") {
-        this.#handleMessage(event.data);
+        this.#handleMessage(event.data, event.ports!);
         continue;
       }
 
@@ -252,17 +273,17 @@ export class WorkerImpl extends EventTarget implements Worker {
   };
 
   postMessage(message: any, transferOrOptions?: any): void {
-    if (transferOrOptions) {
-      throw new Error(
-        "Not yet implemented: `transfer` and `options` are not supported."
-      );
-    }
+    const transfer = getTransferList(transferOrOptions);
+    const data = encodeMessage(message);
 
-    if (this.#terminated) {
-      return;
-    }
+    transferPorts(transfer, (rids): void => {
+      if (this.#terminated) {
+        closePorts(rids);
+        return;
+      }
 
-    hostPostMessage(this.#id, encodeMessage(message));
+      hostPostMessage(this.#id, data, rids);
+    });
   }
 
   terminate(): void {
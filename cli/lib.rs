@@ -21,8 +21,11 @@ extern crate serde_derive;
 extern crate tokio;
 extern crate url;
 
+mod auth_tokens;
+mod blob_store;
 mod checksum;
 pub mod colors;
+mod cpu_profiler;
 pub mod deno_dir;
 pub mod diagnostics;
 mod disk_cache;
@@ -34,6 +37,7 @@ pub mod fmt_errors;
 mod fs;
 pub mod global_state;
 mod global_timer;
+mod heap_snapshot;
 pub mod http_cache;
 mod http_util;
 mod import_map;
@@ -42,10 +46,12 @@ pub mod installer;
 mod js;
 mod lockfile;
 mod metrics;
+mod module_graph;
 pub mod msg;
 pub mod op_error;
 pub mod ops;
 pub mod permissions;
+mod registry_mirror;
 mod repl;
 pub mod resolve_addr;
 pub mod signal;
@@ -59,6 +65,7 @@ mod tokio_util;
 mod tsc;
 mod upgrade;
 pub mod version;
+mod wasm;
 mod web_worker;
 pub mod worker;
 
@@ -392,6 +399,8 @@ async fn doc_command(
   flags: Flags,
   source_file: Option<String>,
   json: bool,
+  lint: bool,
+  maybe_diff: Option<(String, String)>,
   maybe_filter: Option<String>,
 ) -> Result<(), ErrBox> {
   let global_state = GlobalState::new(flags.clone())?;
@@ -418,24 +427,80 @@ async fn doc_command(
   let loader = Box::new(global_state.file_fetcher.clone());
   let doc_parser = doc::DocParser::new(loader);
 
+  if let Some((old, new)) = maybe_diff {
+    let old_specifier = ModuleSpecifier::resolve_url_or_path(&old)?;
+    let new_specifier = ModuleSpecifier::resolve_url_or_path(&new)?;
+    let old_nodes = doc_parser
+      .parse_with_reexports(&old_specifier.to_string())
+      .await?;
+    let new_nodes = doc_parser
+      .parse_with_reexports(&new_specifier.to_string())
+      .await?;
+    let changes = doc::diff::diff(&old_nodes, &new_nodes);
+
+    if json {
+      let writer = std::io::BufWriter::new(std::io::stdout());
+      serde_json::to_writer_pretty(writer, &changes)?;
+    } else {
+      let output = doc::diff::format(&changes);
+      write_to_stdout_ignore_sigpipe(output.as_bytes())?;
+    }
+
+    if changes.iter().any(|change| change.breaking) {
+      std::process::exit(1);
+    }
+    return Ok(());
+  }
+
   let parse_result = if source_file == "--builtin" {
-    doc_parser.parse_source("lib.deno.d.ts", get_types(flags.unstable).as_str())
+    doc_parser
+      .parse_source("lib.deno.d.ts", get_types(flags.unstable).as_str())
+      .map(|nodes| (nodes, vec![]))
   } else {
     let module_specifier =
       ModuleSpecifier::resolve_url_or_path(&source_file).unwrap();
-    doc_parser
-      .parse_with_reexports(&module_specifier.to_string())
-      .await
+    let file_name = module_specifier.to_string();
+    let nodes = doc_parser.parse_with_reexports(&file_name).await;
+    match nodes {
+      Ok(nodes) if lint => doc_parser
+        .parse_private_types(&file_name)
+        .await
+        .map(|private_types| (nodes, private_types)),
+      Ok(nodes) => Ok((nodes, vec![])),
+      Err(e) => Err(e),
+    }
   };
 
-  let doc_nodes = match parse_result {
-    Ok(nodes) => nodes,
+  let (doc_nodes, private_types) = match parse_result {
+    Ok(result) => result,
     Err(e) => {
       eprintln!("{}", e);
       std::process::exit(1);
     }
   };
 
+  if lint {
+    let diagnostics = doc::lint::lint(&doc_nodes, &private_types);
+    if json {
+      let writer = std::io::BufWriter::new(std::io::stdout());
+      serde_json::to_writer_pretty(writer, &diagnostics)?;
+    } else {
+      for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic);
+      }
+    }
+    if !diagnostics.is_empty() {
+      eprintln!(
+        "{} Found {} problem{}",
+        colors::red_bold("error:".to_string()),
+        diagnostics.len(),
+        if diagnostics.len() == 1 { "" } else { "s" }
+      );
+      std::process::exit(1);
+    }
+    return Ok(());
+  }
+
   if json {
     let writer = std::io::BufWriter::new(std::io::stdout());
     serde_json::to_writer_pretty(writer, &doc_nodes).map_err(ErrBox::from)
@@ -569,8 +634,12 @@ pub fn main() {
     DenoSubcommand::Doc {
       source_file,
       json,
+      lint,
+      diff,
       filter,
-    } => doc_command(flags, source_file, json, filter).boxed_local(),
+    } => {
+      doc_command(flags, source_file, json, lint, diff, filter).boxed_local()
+    }
     DenoSubcommand::Eval {
       code,
       as_typescript,
@@ -52,8 +52,7 @@ impl Lockfile {
     }
     assert!(!self.need_read);
     Ok(if let Some(lockfile_checksum) = self.map.get(&m.name) {
-      let compiled_checksum = crate::checksum::gen2(&m.code);
-      lockfile_checksum == &compiled_checksum
+      lockfile_checksum == &checksum(m)
     } else {
       false
     })
@@ -64,7 +63,13 @@ impl Lockfile {
     if m.name.starts_with("file:") {
       return false;
     }
-    let checksum = crate::checksum::gen2(&m.code);
-    self.map.insert(m.name.clone(), checksum).is_none()
+    self.map.insert(m.name.clone(), checksum(m)).is_none()
+  }
+}
+
+fn checksum(m: &CompiledModule) -> String {
+  match &m.binary {
+    Some(binary) => crate::checksum::gen(vec![m.code.as_bytes(), binary]),
+    None => crate::checksum::gen2(&m.code),
   }
 }
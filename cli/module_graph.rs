@@ -0,0 +1,215 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Discovers the static module graph of a root module and fetches all of its
+//! modules concurrently, before the isolate starts loading them one by one.
+//!
+//! Fetched files end up in the in-process cache of `SourceFileFetcher`, so
+//! the compiler and module loader don't have to go to the network again.
+//! Failures are not reported here; they surface with proper context once
+//! the module loader or compiler requests the file.
+
+use crate::colors;
+use crate::file_fetcher::SourceFile;
+use crate::file_fetcher::SourceFileFetcher;
+use crate::import_map::ImportMap;
+use crate::msg::MediaType;
+use crate::swc_util::analyze_dependencies;
+use deno_core::ErrBox;
+use deno_core::ModuleSpecifier;
+use futures::future::FutureExt;
+use futures::stream::FuturesUnordered;
+use futures::stream::StreamExt;
+use log::info;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::str;
+
+/// Maximum number of modules that are fetched at the same time.
+const MAX_CONCURRENT_FETCHES: usize = 16;
+
+type FetchFuture =
+  Pin<Box<dyn Future<Output = (ModuleSpecifier, Result<SourceFile, ErrBox>)>>>;
+
+pub struct ModuleGraphLoader {
+  file_fetcher: SourceFileFetcher,
+  maybe_import_map: Option<ImportMap>,
+  visited: HashSet<ModuleSpecifier>,
+  queue: VecDeque<(ModuleSpecifier, Option<ModuleSpecifier>)>,
+  pending: FuturesUnordered<FetchFuture>,
+  downloads: usize,
+}
+
+impl ModuleGraphLoader {
+  pub fn new(
+    file_fetcher: SourceFileFetcher,
+    maybe_import_map: Option<ImportMap>,
+  ) -> Self {
+    Self {
+      file_fetcher,
+      maybe_import_map,
+      visited: HashSet::new(),
+      queue: VecDeque::new(),
+      pending: FuturesUnordered::new(),
+      downloads: 0,
+    }
+  }
+
+  /// Fetches `module_specifier` and all of its static dependencies,
+  /// keeping at most `MAX_CONCURRENT_FETCHES` requests in flight.
+  pub async fn prefetch(
+    mut self,
+    module_specifier: ModuleSpecifier,
+    maybe_referrer: Option<ModuleSpecifier>,
+  ) {
+    self.enqueue(module_specifier, maybe_referrer);
+
+    loop {
+      while self.pending.len() < MAX_CONCURRENT_FETCHES {
+        match self.queue.pop_front() {
+          Some((specifier, maybe_referrer)) => {
+            self.start_fetch(specifier, maybe_referrer)
+          }
+          None => break,
+        }
+      }
+
+      match self.pending.next().await {
+        Some((_, Ok(source_file))) => self.visit(source_file),
+        Some((specifier, Err(err))) => {
+          debug!("Failed to prefetch {}: {}", specifier, err);
+        }
+        None => break,
+      }
+    }
+
+    if self.downloads > 0 {
+      info!(
+        "{} {} remote module(s)",
+        colors::green("Downloaded".to_string()),
+        self.downloads
+      );
+    }
+  }
+
+  fn enqueue(
+    &mut self,
+    specifier: ModuleSpecifier,
+    maybe_referrer: Option<ModuleSpecifier>,
+  ) {
+    if self.visited.insert(specifier.clone()) {
+      self.queue.push_back((specifier, maybe_referrer));
+    }
+  }
+
+  fn start_fetch(
+    &mut self,
+    specifier: ModuleSpecifier,
+    maybe_referrer: Option<ModuleSpecifier>,
+  ) {
+    if self.file_fetcher.needs_download(&specifier) {
+      self.downloads += 1;
+    }
+
+    let file_fetcher = self.file_fetcher.clone();
+    let fut = async move {
+      let result = file_fetcher
+        .fetch_source_file(&specifier, maybe_referrer)
+        .await;
+      (specifier, result)
+    };
+    self.pending.push(fut.boxed_local());
+  }
+
+  /// Enqueues all static imports of `source_file`, as well as the type
+  /// definitions the compiler will look for.
+  fn visit(&mut self, source_file: SourceFile) {
+    let referrer = ModuleSpecifier::from(source_file.url.clone());
+
+    if let Some(types_url) = source_file.types_url {
+      self.enqueue(types_url.into(), Some(referrer.clone()));
+    }
+
+    match source_file.media_type {
+      MediaType::JavaScript | MediaType::TypeScript => {}
+      _ => return,
+    }
+    let source_code = match str::from_utf8(&source_file.source_code) {
+      Ok(source_code) => source_code,
+      Err(_) => return,
+    };
+    let dependencies = match analyze_dependencies(source_code, false) {
+      Ok(dependencies) => dependencies,
+      Err(_) => return,
+    };
+
+    for import in dependencies {
+      if let Ok(specifier) = self.resolve(&import, &referrer) {
+        self.enqueue(specifier, Some(referrer.clone()));
+      }
+    }
+  }
+
+  fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &ModuleSpecifier,
+  ) -> Result<ModuleSpecifier, ErrBox> {
+    if let Some(import_map) = &self.maybe_import_map {
+      if let Some(resolved) =
+        import_map.resolve(specifier, &referrer.to_string())?
+      {
+        return Ok(resolved);
+      }
+    }
+    let resolved =
+      ModuleSpecifier::resolve_import(specifier, &referrer.to_string())?;
+    Ok(resolved)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http_cache::CachePolicy;
+  use crate::http_cache::HttpCache;
+  use crate::registry_mirror::RegistryMirrors;
+  use tempfile::TempDir;
+
+  #[tokio::test]
+  async fn test_prefetch_remote_graph() {
+    let http_server_guard = crate::test_util::http_server();
+    let temp_dir = TempDir::new().unwrap();
+    let file_fetcher = SourceFileFetcher::new(
+      HttpCache::new(&temp_dir.path().join("deps")),
+      true,
+      vec![],
+      false,
+      false,
+      CachePolicy::Default,
+      RegistryMirrors::default(),
+      None,
+    )
+    .unwrap();
+    let module_specifier = ModuleSpecifier::resolve_url(
+      "http://localhost:4545/cli/tests/subdir/mod1.ts",
+    )
+    .unwrap();
+
+    ModuleGraphLoader::new(file_fetcher.clone(), None)
+      .prefetch(module_specifier, None)
+      .await;
+
+    for url in &[
+      "http://localhost:4545/cli/tests/subdir/mod1.ts",
+      "http://localhost:4545/cli/tests/subdir/subdir2/mod2.ts",
+      "http://localhost:4545/cli/tests/print_hello.ts",
+    ] {
+      let specifier = ModuleSpecifier::resolve_url(url).unwrap();
+      assert!(file_fetcher.fetch_cached_source_file(&specifier).is_some());
+    }
+
+    drop(http_server_guard);
+  }
+}
@@ -22,6 +22,8 @@ use std::env::VarError;
 use std::error::Error;
 use std::fmt;
 use std::io;
+use trust_dns_resolver::error::ResolveError;
+use trust_dns_resolver::error::ResolveErrorKind;
 
 // Warning! The values in this enum are duplicated in js/errors.ts
 // Update carefully!
@@ -381,6 +383,28 @@ impl From<&notify::Error> for OpError {
   }
 }
 
+impl From<ResolveError> for OpError {
+  fn from(error: ResolveError) -> Self {
+    OpError::from(&error)
+  }
+}
+
+impl From<&ResolveError> for OpError {
+  fn from(error: &ResolveError) -> Self {
+    let kind = match error.kind() {
+      ResolveErrorKind::Io(ref e) => return e.into(),
+      ResolveErrorKind::NoRecordsFound { .. } => ErrorKind::NotFound,
+      ResolveErrorKind::Timeout => ErrorKind::TimedOut,
+      _ => ErrorKind::Other,
+    };
+
+    Self {
+      kind,
+      msg: error.to_string(),
+    }
+  }
+}
+
 impl From<ErrBox> for OpError {
   fn from(error: ErrBox) -> Self {
     #[cfg(unix)]
@@ -417,6 +441,7 @@ impl From<ErrBox> for OpError {
       })
       .or_else(|| error.downcast_ref::<dlopen::Error>().map(|e| e.into()))
       .or_else(|| error.downcast_ref::<notify::Error>().map(|e| e.into()))
+      .or_else(|| error.downcast_ref::<ResolveError>().map(|e| e.into()))
       .or_else(|| unix_error_kind(&error))
       .unwrap_or_else(|| {
         panic!("Can't downcast {:?} to OpError", error);
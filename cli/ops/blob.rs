@@ -0,0 +1,59 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::blob_store::Blob;
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_create_object_url",
+    s.stateful_json_op(op_create_object_url),
+  );
+  i.register_op(
+    "op_revoke_object_url",
+    s.stateful_json_op(op_revoke_object_url),
+  );
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateObjectUrlArgs {
+  media_type: String,
+}
+
+fn op_create_object_url(
+  state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CreateObjectUrlArgs = serde_json::from_value(args)?;
+  let data = match zero_copy {
+    Some(buf) => buf.to_vec(),
+    None => vec![],
+  };
+  let blob = Blob {
+    media_type: args.media_type,
+    data,
+  };
+  let global_state = state.borrow().global_state.clone();
+  let url = global_state.file_fetcher.blob_store.insert(blob);
+  Ok(JsonOp::Sync(json!(url)))
+}
+
+#[derive(Deserialize)]
+struct RevokeObjectUrlArgs {
+  url: String,
+}
+
+fn op_revoke_object_url(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RevokeObjectUrlArgs = serde_json::from_value(args)?;
+  let global_state = state.borrow().global_state.clone();
+  global_state.file_fetcher.blob_store.remove(&args.url);
+  Ok(JsonOp::Sync(json!({})))
+}
@@ -3,8 +3,11 @@ use super::dispatch_json::Deserialize;
 use super::dispatch_json::JsonOp;
 use super::dispatch_json::Value;
 use crate::futures::future::try_join_all;
+use crate::msg;
 use crate::op_error::OpError;
 use crate::state::State;
+use crate::swc_util;
+use crate::wasm;
 use deno_core::CoreIsolate;
 use deno_core::ModuleLoader;
 use deno_core::ModuleSpecifier;
@@ -89,7 +92,7 @@ fn op_fetch_source_files(
               "http" | "https" => {
                 let specifier_url = resolved_specifier.as_url();
                 match specifier_url.scheme() {
-                  "http" | "https" => {},
+                  "http" | "https" | "data" => {},
                   _ => {
                     let e = OpError::permission_denied("Remote module are not allowed to statically import local modules. Use dynamic import instead.".to_string());
                     return Err(e.into());
@@ -124,7 +127,25 @@ fn op_fetch_source_files(
           }
           _ => f,
         };
-        let source_code = String::from_utf8(file.source_code).map_err(|_| OpError::invalid_utf8())?;
+        // WebAssembly modules are type checked against declarations
+        // generated from the binary.
+        if file.media_type == msg::MediaType::Wasm {
+          let url = format!("{}.d.ts", file.url);
+          return Ok::<_, OpError>(json!({
+            "url": url,
+            "filename": url,
+            "mediaType": msg::MediaType::TypeScript as i32,
+            "sourceCode": wasm::wasm_declaration(&file)?,
+          }));
+        }
+        let mut source_code = String::from_utf8(file.source_code).map_err(|_| OpError::invalid_utf8())?;
+        if let msg::MediaType::JavaScript
+        | msg::MediaType::JSX
+        | msg::MediaType::TypeScript
+        | msg::MediaType::TSX = file.media_type
+        {
+          source_code = swc_util::strip_json_import_assertions(&source_code);
+        }
         Ok::<_, OpError>(json!({
           "url": file.url.to_string(),
           "filename": file.filename.to_str().unwrap(),
@@ -1,28 +1,78 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use super::dispatch_json::{Deserialize, JsonOp, Value};
 use super::io::{StreamResource, StreamResourceHolder};
-use crate::http_util::{create_http_client, HttpBody};
+use crate::http_util::{
+  create_http_client_with_options, HttpBody, HttpClientOptions, ProxyConfig,
+};
 use crate::op_error::OpError;
 use crate::state::State;
 use deno_core::CoreIsolate;
+use deno_core::ResourceTable;
 use deno_core::ZeroCopyBuf;
+use futures::channel::mpsc;
+use futures::future::AbortHandle;
+use futures::future::Abortable;
+use futures::future::Aborted;
 use futures::future::FutureExt;
+use futures::SinkExt;
 use http::header::HeaderName;
 use http::header::HeaderValue;
 use http::Method;
 use std::convert::From;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_fetch", s.stateful_json_op2(op_fetch));
+  i.register_op("op_fetch_send", s.stateful_json_op2(op_fetch_send));
+  i.register_op(
+    "op_fetch_request_write",
+    s.stateful_json_op2(op_fetch_request_write),
+  );
+  i.register_op(
+    "op_create_http_client",
+    s.stateful_json_op2(op_create_http_client),
+  );
+}
+
+type FetchFuture =
+  Pin<Box<dyn Future<Output = Result<Result<Value, OpError>, Aborted>>>>;
+
+/// A request that has been created by `op_fetch` but not sent yet. Closing
+/// the resource aborts the request, even if `op_fetch_send` is already
+/// waiting for the response.
+struct FetchRequestResource {
+  future: Option<FetchFuture>,
+  abort_handle: AbortHandle,
+}
+
+impl Drop for FetchRequestResource {
+  fn drop(&mut self) {
+    self.abort_handle.abort();
+  }
+}
+
+/// The sending half of a streamed request body. Closing the resource ends
+/// the body.
+struct FetchRequestBodyResource {
+  sender: mpsc::Sender<Result<Vec<u8>, std::io::Error>>,
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct FetchArgs {
   method: Option<String>,
   url: String,
   headers: Vec<(String, String)>,
+  #[serde(default)]
+  has_body: bool,
+  client_rid: Option<u32>,
 }
 
+/// Creates a request. The request is sent by `op_fetch_send`; if `hasBody`
+/// is set, the body has to be written with `op_fetch_request_write` in the
+/// meantime, otherwise it is taken from `data`.
 pub fn op_fetch(
   isolate: &mut CoreIsolate,
   state: &State,
@@ -32,8 +82,16 @@ pub fn op_fetch(
   let args: FetchArgs = serde_json::from_value(args)?;
   let url = args.url;
 
-  let client =
-    create_http_client(state.borrow().global_state.flags.ca_file.clone())?;
+  let client = match args.client_rid {
+    Some(rid) => {
+      let resource_table = isolate.resource_table.borrow();
+      let resource = resource_table
+        .get::<HttpClientResource>(rid)
+        .ok_or_else(OpError::bad_resource_id)?;
+      resource.client.clone()
+    }
+    None => state.borrow().global_state.http_client.clone(),
+  };
 
   let method = match args.method {
     Some(method_str) => Method::from_bytes(method_str.as_bytes())
@@ -43,6 +101,19 @@ pub fn op_fetch(
 
   let url_ = url::Url::parse(&url).map_err(OpError::from)?;
 
+  // Blob URLs are served from memory and don't need any permission.
+  if url_.scheme() == "blob" {
+    let json_res =
+      fetch_blob(&mut isolate.resource_table.borrow_mut(), state, &url)?;
+    let future = futures::future::ok(json_res);
+    let request_rid =
+      add_request(&mut isolate.resource_table.borrow_mut(), future);
+    return Ok(JsonOp::Sync(json!({
+      "requestRid": request_rid,
+      "requestBodyRid": null,
+    })));
+  }
+
   // Check scheme before asking for net permission
   let scheme = url_.scheme();
   if scheme != "http" && scheme != "https" {
@@ -56,7 +127,16 @@ pub fn op_fetch(
 
   let mut request = client.request(method, url_);
 
-  if let Some(buf) = data {
+  let mut request_body_rid = None;
+  if args.has_body {
+    let (sender, receiver) = mpsc::channel(1);
+    request = request.body(reqwest::Body::wrap_stream(receiver));
+    let rid = isolate.resource_table.borrow_mut().add(
+      "fetchRequestBody",
+      Box::new(FetchRequestBodyResource { sender }),
+    );
+    request_body_rid = Some(rid);
+  } else if let Some(buf) = data {
     request = request.body(Vec::from(&*buf));
   }
 
@@ -96,5 +176,197 @@ pub fn op_fetch(
     Ok(json_res)
   };
 
+  let request_rid =
+    add_request(&mut isolate.resource_table.borrow_mut(), future);
+  Ok(JsonOp::Sync(json!({
+    "requestRid": request_rid,
+    "requestBodyRid": request_body_rid,
+  })))
+}
+
+fn add_request(
+  resource_table: &mut ResourceTable,
+  future: impl Future<Output = Result<Value, OpError>> + 'static,
+) -> u32 {
+  let (abort_handle, abort_registration) = AbortHandle::new_pair();
+  let future = Abortable::new(future, abort_registration).boxed_local();
+  resource_table.add(
+    "fetchRequest",
+    Box::new(FetchRequestResource {
+      future: Some(future),
+      abort_handle,
+    }),
+  )
+}
+
+fn fetch_blob(
+  resource_table: &mut ResourceTable,
+  state: &State,
+  url: &str,
+) -> Result<Value, OpError> {
+  let global_state = state.borrow().global_state.clone();
+  let blob = match global_state.file_fetcher.blob_store.get(url) {
+    Some(blob) => blob,
+    None => {
+      return Err(OpError::type_error(format!(
+        "No blob found for URL \"{}\"",
+        url
+      )))
+    }
+  };
+
+  let headers = vec![
+    ("content-type".to_string(), blob.media_type),
+    ("content-length".to_string(), blob.data.len().to_string()),
+  ];
+  let rid = resource_table.add(
+    "blobBody",
+    Box::new(StreamResourceHolder::new(StreamResource::BlobBody(
+      std::io::Cursor::new(blob.data),
+    ))),
+  );
+
+  Ok(json!({
+    "bodyRid": rid,
+    "status": 200,
+    "statusText": "OK",
+    "headers": headers
+  }))
+}
+
+#[derive(Deserialize)]
+struct RidArgs {
+  rid: u32,
+}
+
+/// Sends a request created by `op_fetch` and resolves once the response
+/// headers have been received.
+pub fn op_fetch_send(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RidArgs = serde_json::from_value(args)?;
+  let rid = args.rid;
+
+  let future = {
+    let mut resource_table = isolate.resource_table.borrow_mut();
+    let resource = resource_table
+      .get_mut::<FetchRequestResource>(rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    resource
+      .future
+      .take()
+      .ok_or_else(OpError::bad_resource_id)?
+  };
+
+  let resource_table = isolate.resource_table.clone();
+  let future = async move {
+    let result = future.await;
+    // The resource is already gone if the request was aborted.
+    resource_table.borrow_mut().close(rid);
+    match result {
+      Ok(result) => result,
+      Err(Aborted) => {
+        Err(OpError::other("The request has been aborted".to_string()))
+      }
+    }
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+/// Writes a chunk of a streamed request body. Resolves once the chunk has
+/// been handed to the HTTP client, which provides backpressure.
+pub fn op_fetch_request_write(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RidArgs = serde_json::from_value(args)?;
+  let chunk = match data {
+    Some(buf) => buf.to_vec(),
+    None => return Err(OpError::type_error("Missing chunk".to_string())),
+  };
+
+  let mut sender = {
+    let resource_table = isolate.resource_table.borrow();
+    let resource = resource_table
+      .get::<FetchRequestBodyResource>(args.rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    resource.sender.clone()
+  };
+
+  let future = async move {
+    sender.send(Ok(chunk)).await.map_err(|_| {
+      OpError::other("The request body is no longer being sent".to_string())
+    })?;
+    Ok(json!({}))
+  };
+
   Ok(JsonOp::Async(future.boxed_local()))
 }
+
+/// A client created by `op_create_http_client`. Requests sent with the same
+/// client share its connection pool.
+struct HttpClientResource {
+  client: reqwest::Client,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct CreateHttpClientArgs {
+  ca_data: Option<String>,
+  proxy: Option<String>,
+  client_cert: Option<String>,
+  client_key: Option<String>,
+  timeout: Option<u64>,
+  http2: Option<bool>,
+}
+
+pub fn op_create_http_client(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.createHttpClient");
+  let args: CreateHttpClientArgs = serde_json::from_value(args)?;
+
+  let proxy_config = match args.proxy {
+    Some(proxy) => {
+      let url = url::Url::parse(&proxy).map_err(|_| {
+        OpError::type_error(format!("Invalid proxy URL \"{}\"", proxy))
+      })?;
+      ProxyConfig::from_proxy(url)
+    }
+    None => ProxyConfig::from_env(),
+  };
+  for proxy in proxy_config.proxies() {
+    state.check_net_url(proxy)?;
+  }
+
+  if args.client_cert.is_some() != args.client_key.is_some() {
+    return Err(OpError::type_error(
+      "Both clientCert and clientKey have to be given".to_string(),
+    ));
+  }
+
+  let client = create_http_client_with_options(HttpClientOptions {
+    ca_file: state.borrow().global_state.flags.ca_file.clone(),
+    ca_data: args.ca_data,
+    proxy: Some(proxy_config),
+    client_cert: args.client_cert,
+    client_key: args.client_key,
+    timeout: args.timeout.map(Duration::from_millis),
+    http2: args.http2,
+  })?;
+
+  let rid = isolate
+    .resource_table
+    .borrow_mut()
+    .add("httpClient", Box::new(HttpClientResource { client }));
+  Ok(JsonOp::Sync(json!(rid)))
+}
@@ -0,0 +1,491 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use super::io::{StreamResource, StreamResourceHolder};
+use crate::op_error::OpError;
+use crate::state::State;
+use bytes::Bytes;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use futures::channel::mpsc;
+use futures::channel::oneshot;
+use futures::future::AbortHandle;
+use futures::future::Abortable;
+use futures::future::FutureExt;
+use futures::ready;
+use futures::SinkExt;
+use futures::StreamExt;
+use hyper::body::HttpBody as _;
+use hyper::header::HeaderName;
+use hyper::header::HeaderValue;
+use hyper::header::CONNECTION;
+use hyper::header::HOST;
+use hyper::header::SEC_WEBSOCKET_ACCEPT;
+use hyper::header::SEC_WEBSOCKET_KEY;
+use hyper::header::UPGRADE;
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::upgrade::OnUpgrade;
+use hyper::Body;
+use hyper::Request;
+use hyper::Response;
+use hyper::StatusCode;
+use std::cmp::min;
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio_rustls::rustls::Session;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op("op_serve_http", s.stateful_json_op2(op_serve_http));
+  i.register_op(
+    "op_http_next_request",
+    s.stateful_json_op2(op_http_next_request),
+  );
+  i.register_op("op_http_respond", s.stateful_json_op2(op_http_respond));
+  i.register_op(
+    "op_http_response_write",
+    s.stateful_json_op2(op_http_response_write),
+  );
+  i.register_op(
+    "op_http_upgrade_websocket",
+    s.stateful_json_op2(op_http_upgrade_websocket),
+  );
+}
+
+type NextRequest = (Request<Body>, oneshot::Sender<Response<Body>>);
+
+/// A connection that is served by hyper. hyper runs in a separate task and
+/// passes the requests it parses through `requests`. Closing the resource
+/// closes the connection.
+struct HttpConnResource {
+  requests: Option<mpsc::Receiver<NextRequest>>,
+  scheme: &'static str,
+  abort_handle: AbortHandle,
+}
+
+impl Drop for HttpConnResource {
+  fn drop(&mut self) {
+    self.abort_handle.abort();
+  }
+}
+
+/// A request that hasn't been responded to yet. If the resource is closed
+/// without a response, the client receives a 500 response.
+struct HttpRequestResource {
+  response_tx: Option<oneshot::Sender<Response<Body>>>,
+  on_upgrade: Option<OnUpgrade>,
+  websocket_key: Option<String>,
+}
+
+/// The sending half of a streamed response body. Closing the resource ends
+/// the body.
+struct HttpResponseBodyResource {
+  sender: mpsc::Sender<Result<Vec<u8>, io::Error>>,
+}
+
+/// Wraps a hyper request `Body` so that it can be read with `op_read`.
+pub struct HttpRequestBody {
+  body: Body,
+  chunk: Bytes,
+}
+
+impl AsyncRead for HttpRequestBody {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+    buf: &mut [u8],
+  ) -> Poll<Result<usize, io::Error>> {
+    let inner = self.get_mut();
+    while inner.chunk.is_empty() {
+      match ready!(inner.body.poll_next_unpin(cx)) {
+        Some(Ok(chunk)) => inner.chunk = chunk,
+        Some(Err(e)) => {
+          return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)))
+        }
+        None => return Poll::Ready(Ok(0)),
+      }
+    }
+    let n = min(buf.len(), inner.chunk.len());
+    buf[..n].copy_from_slice(&inner.chunk.split_to(n));
+    Poll::Ready(Ok(n))
+  }
+}
+
+#[derive(Deserialize)]
+struct RidArgs {
+  rid: u32,
+}
+
+/// Takes over a TCP or TLS connection and serves it with hyper. HTTP/2 is
+/// used if it has been negotiated with ALPN.
+pub fn op_serve_http(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.serveHttp");
+  let args: RidArgs = serde_json::from_value(args)?;
+
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  match resource_table.get::<StreamResourceHolder>(args.rid) {
+    Some(holder) => match holder.resource {
+      StreamResource::TcpStream(Some(_))
+      | StreamResource::ServerTlsStream(_) => {}
+      _ => return Err(OpError::bad_resource_id()),
+    },
+    None => return Err(OpError::bad_resource_id()),
+  }
+  let mut holder = resource_table
+    .remove::<StreamResourceHolder>(args.rid)
+    .unwrap();
+  let resource =
+    std::mem::replace(&mut holder.resource, StreamResource::TcpStream(None));
+
+  let (sender, receiver) = mpsc::channel(1);
+  let (scheme, abort_handle) = match resource {
+    StreamResource::TcpStream(Some(stream)) => {
+      ("http", serve_connection(stream, false, sender))
+    }
+    StreamResource::ServerTlsStream(stream) => {
+      let http2 = stream.get_ref().1.get_alpn_protocol() == Some(&b"h2"[..]);
+      ("https", serve_connection(stream, http2, sender))
+    }
+    _ => unreachable!(),
+  };
+
+  let rid = resource_table.add(
+    "httpConn",
+    Box::new(HttpConnResource {
+      requests: Some(receiver),
+      scheme,
+      abort_handle,
+    }),
+  );
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+fn serve_connection<I>(
+  io: I,
+  http2: bool,
+  sender: mpsc::Sender<NextRequest>,
+) -> AbortHandle
+where
+  I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+  let service = service_fn(move |request: Request<Body>| {
+    let mut sender = sender.clone();
+    async move {
+      let (response_tx, response_rx) = oneshot::channel();
+      sender.send((request, response_tx)).await?;
+      let response = response_rx.await.unwrap_or_else(|_| {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        response
+      });
+      Ok::<_, mpsc::SendError>(response)
+    }
+  });
+
+  let mut http = Http::new();
+  http.http2_only(http2);
+  let conn = http.serve_connection(io, service).with_upgrades();
+
+  let (abort_handle, abort_registration) = AbortHandle::new_pair();
+  tokio::spawn(Abortable::new(conn, abort_registration).map(|result| {
+    if let Ok(Err(e)) = result {
+      debug!("HTTP connection error: {}", e);
+    }
+  }));
+  abort_handle
+}
+
+/// Resolves with the next request of a connection. `request` is `null` once
+/// the connection has been closed.
+pub fn op_http_next_request(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RidArgs = serde_json::from_value(args)?;
+  let rid = args.rid;
+
+  let (mut requests, scheme) = {
+    let mut resource_table = isolate.resource_table.borrow_mut();
+    let conn = resource_table
+      .get_mut::<HttpConnResource>(rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    let requests = conn
+      .requests
+      .take()
+      .ok_or_else(OpError::resource_unavailable)?;
+    (requests, conn.scheme)
+  };
+
+  let resource_table = isolate.resource_table.clone();
+  let future = async move {
+    let next = requests.next().await;
+    let mut resource_table = resource_table.borrow_mut();
+    // The connection might have been closed in the meantime.
+    if let Some(conn) = resource_table.get_mut::<HttpConnResource>(rid) {
+      conn.requests = Some(requests);
+    }
+
+    let (request, response_tx) = match next {
+      Some(next) => next,
+      None => return Ok(json!({ "request": null })),
+    };
+    let (parts, body) = request.into_parts();
+
+    let url = {
+      let host = parts
+        .uri
+        .authority()
+        .map(|authority| authority.as_str())
+        .or_else(|| parts.headers.get(HOST).and_then(|v| v.to_str().ok()))
+        .unwrap_or("localhost");
+      let path = parts
+        .uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/");
+      format!("{}://{}{}", scheme, host, path)
+    };
+
+    let mut headers = Vec::new();
+    for (key, value) in parts.headers.iter() {
+      let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+      headers.push((key.to_string(), value));
+    }
+
+    let is_websocket = parts
+      .headers
+      .get(UPGRADE)
+      .and_then(|v| v.to_str().ok())
+      .map(|v| v.eq_ignore_ascii_case("websocket"))
+      .unwrap_or(false);
+
+    let mut websocket_key = None;
+    let mut on_upgrade = None;
+    let mut body_rid = None;
+    if is_websocket {
+      websocket_key = parts
+        .headers
+        .get(SEC_WEBSOCKET_KEY)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+      on_upgrade = Some(body.on_upgrade());
+    } else if !body.is_end_stream() {
+      let rid = resource_table.add(
+        "httpRequestBody",
+        Box::new(StreamResourceHolder::new(StreamResource::HttpRequestBody(
+          Box::new(HttpRequestBody {
+            body,
+            chunk: Bytes::new(),
+          }),
+        ))),
+      );
+      body_rid = Some(rid);
+    }
+
+    let request_rid = resource_table.add(
+      "httpRequest",
+      Box::new(HttpRequestResource {
+        response_tx: Some(response_tx),
+        on_upgrade,
+        websocket_key,
+      }),
+    );
+
+    Ok(json!({
+      "request": {
+        "requestRid": request_rid,
+        "bodyRid": body_rid,
+        "method": parts.method.as_str(),
+        "url": url,
+        "headers": headers,
+      }
+    }))
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RespondArgs {
+  rid: u32,
+  status: u16,
+  headers: Vec<(String, String)>,
+  #[serde(default)]
+  has_body: bool,
+}
+
+/// Sends the response to a request. If `hasBody` is set, the body has to be
+/// written with `op_http_response_write`, otherwise it is taken from `data`.
+pub fn op_http_respond(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RespondArgs = serde_json::from_value(args)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+
+  let response_tx = resource_table
+    .get_mut::<HttpRequestResource>(args.rid)
+    .and_then(|request| request.response_tx.take())
+    .ok_or_else(OpError::bad_resource_id)?;
+  resource_table.close(args.rid);
+
+  let mut builder = Response::builder().status(args.status);
+  for (key, value) in args.headers {
+    let name = HeaderName::from_bytes(key.as_bytes())
+      .map_err(|e| OpError::type_error(e.to_string()))?;
+    let value = HeaderValue::from_str(&value)
+      .map_err(|e| OpError::type_error(e.to_string()))?;
+    builder = builder.header(name, value);
+  }
+
+  let mut body_rid = None;
+  let body = if args.has_body {
+    let (sender, receiver) = mpsc::channel(1);
+    let rid = resource_table.add(
+      "httpResponseBody",
+      Box::new(HttpResponseBodyResource { sender }),
+    );
+    body_rid = Some(rid);
+    Body::wrap_stream(receiver)
+  } else {
+    match data {
+      Some(buf) => Body::from(buf.to_vec()),
+      None => Body::empty(),
+    }
+  };
+
+  let response = builder
+    .body(body)
+    .map_err(|e| OpError::type_error(e.to_string()))?;
+  // The client might have gone away already, which is not an error.
+  let _ = response_tx.send(response);
+
+  Ok(JsonOp::Sync(json!({ "bodyRid": body_rid })))
+}
+
+/// Writes a chunk of a streamed response body. Resolves once the chunk has
+/// been handed to hyper, which provides backpressure.
+pub fn op_http_response_write(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RidArgs = serde_json::from_value(args)?;
+  let chunk = match data {
+    Some(buf) => buf.to_vec(),
+    None => return Err(OpError::type_error("Missing chunk".to_string())),
+  };
+
+  let mut sender = {
+    let resource_table = isolate.resource_table.borrow();
+    let resource = resource_table
+      .get::<HttpResponseBodyResource>(args.rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    resource.sender.clone()
+  };
+
+  let future = async move {
+    sender.send(Ok(chunk)).await.map_err(|_| {
+      OpError::other("The response body is no longer being sent".to_string())
+    })?;
+    Ok(json!({}))
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+/// Accepts a WebSocket handshake and resolves with a resource of the raw
+/// upgraded connection, which WebSocket frames can be read from and written
+/// to.
+pub fn op_http_upgrade_websocket(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RidArgs = serde_json::from_value(args)?;
+
+  let (response_tx, on_upgrade, websocket_key) = {
+    let mut resource_table = isolate.resource_table.borrow_mut();
+    let request = resource_table
+      .get_mut::<HttpRequestResource>(args.rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    let websocket_key = match request.websocket_key.take() {
+      Some(key) => key,
+      None => {
+        return Err(OpError::type_error(
+          "Request is not a WebSocket handshake".to_string(),
+        ))
+      }
+    };
+    let response_tx = request.response_tx.take().unwrap();
+    let on_upgrade = request.on_upgrade.take().unwrap();
+    resource_table.close(args.rid);
+    (response_tx, on_upgrade, websocket_key)
+  };
+
+  let response = Response::builder()
+    .status(StatusCode::SWITCHING_PROTOCOLS)
+    .header(UPGRADE, "websocket")
+    .header(CONNECTION, "Upgrade")
+    .header(SEC_WEBSOCKET_ACCEPT, websocket_accept(&websocket_key))
+    .body(Body::empty())
+    .unwrap();
+  response_tx.send(response).map_err(|_| {
+    OpError::other("The connection has been closed".to_string())
+  })?;
+
+  let resource_table = isolate.resource_table.clone();
+  let future = async move {
+    let upgraded = on_upgrade
+      .await
+      .map_err(|e| OpError::other(e.to_string()))?;
+    let rid = resource_table.borrow_mut().add(
+      "httpUpgraded",
+      Box::new(StreamResourceHolder::new(StreamResource::HttpUpgraded(
+        Box::new(upgraded),
+      ))),
+    );
+    Ok(json!(rid))
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+/// Computes the `Sec-WebSocket-Accept` header for a `Sec-WebSocket-Key`, see
+/// https://tools.ietf.org/html/rfc6455#section-4.2.2
+fn websocket_accept(key: &str) -> String {
+  const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+  let digest = ring::digest::digest(
+    &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+    format!("{}{}", key, GUID).as_bytes(),
+  );
+  base64::encode(digest.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_websocket_accept() {
+    assert_eq!(
+      websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="),
+      "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+  }
+}
@@ -1,4 +1,7 @@
 use super::dispatch_minimal::MinimalOp;
+use super::http::HttpRequestBody;
+#[cfg(unix)]
+use super::process::PtyMaster;
 use crate::http_util::HttpBody;
 use crate::op_error::OpError;
 use crate::state::State;
@@ -21,6 +24,11 @@ use tokio_rustls::server::TlsStream as ServerTlsStream;
 #[cfg(not(windows))]
 use std::os::unix::io::FromRawFd;
 
+#[cfg(unix)]
+use nix::fcntl::{fcntl, FcntlArg, OFlag};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+
 #[cfg(windows)]
 use std::os::windows::io::FromRawHandle;
 
@@ -158,9 +166,14 @@ pub enum StreamResource {
   ServerTlsStream(Box<ServerTlsStream<TcpStream>>),
   ClientTlsStream(Box<ClientTlsStream<TcpStream>>),
   HttpBody(Box<HttpBody>),
+  BlobBody(std::io::Cursor<Vec<u8>>),
+  HttpRequestBody(Box<HttpRequestBody>),
+  HttpUpgraded(Box<hyper::upgrade::Upgraded>),
   ChildStdin(tokio::process::ChildStdin),
   ChildStdout(tokio::process::ChildStdout),
   ChildStderr(tokio::process::ChildStderr),
+  #[cfg(unix)]
+  Pty(Box<PtyMaster>),
 }
 
 trait UnpinAsyncRead: AsyncRead + Unpin {}
@@ -198,6 +211,11 @@ impl DenoAsyncRead for StreamResource {
       ChildStdout(f) => f,
       ChildStderr(f) => f,
       HttpBody(f) => f,
+      BlobBody(f) => f,
+      HttpRequestBody(f) => f,
+      HttpUpgraded(f) => f,
+      #[cfg(unix)]
+      Pty(f) => f,
       _ => return Err(OpError::bad_resource_id()).into(),
     };
     let v = ready!(Pin::new(f).poll_read(cx, buf))?;
@@ -298,7 +316,10 @@ impl DenoAsyncWrite for StreamResource {
       UnixStream(f) => f,
       ClientTlsStream(f) => f,
       ServerTlsStream(f) => f,
+      HttpUpgraded(f) => f,
       ChildStdin(f) => f,
+      #[cfg(unix)]
+      Pty(f) => f,
       _ => return Err(OpError::bad_resource_id()).into(),
     };
 
@@ -316,7 +337,10 @@ impl DenoAsyncWrite for StreamResource {
       UnixStream(f) => f,
       ClientTlsStream(f) => f,
       ServerTlsStream(f) => f,
+      HttpUpgraded(f) => f,
       ChildStdin(f) => f,
+      #[cfg(unix)]
+      Pty(f) => f,
       _ => return Err(OpError::bad_resource_id()).into(),
     };
 
@@ -450,3 +474,135 @@ where
     Err(OpError::bad_resource_id())
   }
 }
+
+/// How a resource is connected to one of the stdio streams of a subprocess.
+pub enum SubprocessStdio {
+  /// The child shares the underlying handle with the parent.
+  Shared(std::process::Stdio),
+  /// The child takes over the underlying handle, so the resource has to be
+  /// closed once the child has been spawned.
+  Moved(std::process::Stdio),
+  /// The resource has no handle the child could use, so once the child has
+  /// been spawned the parent takes it out of the resource table and pumps it
+  /// through a pipe with `pump_to_writer` or `pump_from_reader`.
+  Stream,
+}
+
+/// Prepares the resource `rid` to be used as stdio of a subprocess.
+///
+/// Files are shared with the child and stay open in the parent. Any other
+/// stream resource is handed over to the child, because a socket or pipe
+/// can't be read by two processes at once. On Unix, resources backed by a
+/// file descriptor are given to the child as is. The resource table is left
+/// untouched, so nothing is lost if the child can't be spawned.
+pub fn subprocess_stdio(
+  resource_table: &mut ResourceTable,
+  rid: u32,
+) -> Result<SubprocessStdio, OpError> {
+  let shared = std_file_resource(resource_table, rid, move |r| match r {
+    Ok(std_file) => Ok(Some(std_file.try_clone()?.into())),
+    Err(StreamResource::Stdin(..)) => Ok(Some(std::process::Stdio::inherit())),
+    Err(_) => Ok(None),
+  })?;
+  if let Some(stdio) = shared {
+    return Ok(SubprocessStdio::Shared(stdio));
+  }
+
+  #[cfg(unix)]
+  {
+    let resource_holder = resource_table
+      .get::<StreamResourceHolder>(rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    if let Some(fd) = resource_holder.resource.raw_fd() {
+      let fd = fcntl(fd, FcntlArg::F_DUPFD_CLOEXEC(0))?;
+      let file = unsafe { std::fs::File::from_raw_fd(fd) };
+      return Ok(SubprocessStdio::Moved(file.into()));
+    }
+  }
+
+  Ok(SubprocessStdio::Stream)
+}
+
+/// Switches the descriptor of a resource handed over to a subprocess in or
+/// out of non-blocking mode. The event loop needs it, but most programs don't
+/// expect it of their stdio, and the mode is shared with the duplicate the
+/// child gets.
+#[cfg(unix)]
+pub fn set_subprocess_stdio_nonblocking(
+  resource_table: &mut ResourceTable,
+  rid: u32,
+  nonblocking: bool,
+) -> Result<(), OpError> {
+  let fd = resource_table
+    .get::<StreamResourceHolder>(rid)
+    .and_then(|resource_holder| resource_holder.resource.raw_fd());
+  if let Some(fd) = fd {
+    let mut flags = OFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFL)?);
+    flags.set(OFlag::O_NONBLOCK, nonblocking);
+    fcntl(fd, FcntlArg::F_SETFL(flags))?;
+  }
+  Ok(())
+}
+
+#[cfg(unix)]
+impl StreamResource {
+  fn raw_fd(&self) -> Option<RawFd> {
+    use StreamResource::*;
+    match self {
+      TcpStream(Some(s)) => Some(s.as_raw_fd()),
+      UnixStream(s) => Some(s.as_raw_fd()),
+      ChildStdin(s) => Some(s.as_raw_fd()),
+      ChildStdout(s) => Some(s.as_raw_fd()),
+      ChildStderr(s) => Some(s.as_raw_fd()),
+      Pty(s) => Some(s.as_raw_fd()),
+      _ => None,
+    }
+  }
+}
+
+/// Copies everything read from a stream resource into `writer`, which is
+/// shut down afterwards.
+pub async fn pump_to_writer<W: AsyncWrite + Unpin>(
+  mut source: Box<StreamResourceHolder>,
+  mut writer: W,
+) -> Result<(), OpError> {
+  let mut buf = [0u8; 16 * 1024];
+  loop {
+    let nread = poll_fn(|cx| source.resource.poll_read(cx, &mut buf)).await?;
+    if nread == 0 {
+      break;
+    }
+    let mut pos = 0;
+    while pos < nread {
+      let nwritten =
+        poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, &buf[pos..nread]))
+          .await?;
+      pos += nwritten;
+    }
+  }
+  poll_fn(|cx| Pin::new(&mut writer).poll_shutdown(cx)).await?;
+  Ok(())
+}
+
+/// Copies everything read from `reader` into a stream resource, which is
+/// closed afterwards.
+pub async fn pump_from_reader<R: AsyncRead + Unpin>(
+  mut reader: R,
+  mut sink: Box<StreamResourceHolder>,
+) -> Result<(), OpError> {
+  let mut buf = [0u8; 16 * 1024];
+  loop {
+    let nread =
+      poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buf)).await?;
+    if nread == 0 {
+      break;
+    }
+    let mut pos = 0;
+    while pos < nread {
+      let nwritten =
+        poll_fn(|cx| sink.resource.poll_write(cx, &buf[pos..nread])).await?;
+      pos += nwritten;
+    }
+  }
+  poll_fn(|cx| sink.resource.poll_flush(cx)).await
+}
@@ -8,11 +8,13 @@ pub use dispatch_json::JsonResult;
 pub use dispatch_minimal::minimal_op;
 pub use dispatch_minimal::MinimalOp;
 
+pub mod blob;
 pub mod compiler;
 pub mod errors;
 pub mod fetch;
 pub mod fs;
 pub mod fs_events;
+pub mod http;
 pub mod io;
 pub mod net;
 #[cfg(unix)]
@@ -31,4 +33,5 @@ pub mod timers;
 pub mod tls;
 pub mod tty;
 pub mod web_worker;
+pub mod websocket;
 pub mod worker_host;
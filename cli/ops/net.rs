@@ -10,13 +10,24 @@ use deno_core::ZeroCopyBuf;
 use futures::future::poll_fn;
 use futures::future::FutureExt;
 use std::convert::From;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
 use std::net::Shutdown;
 use std::net::SocketAddr;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::net::TcpStream;
 use tokio::net::UdpSocket;
+use trust_dns_resolver::config::NameServerConfigGroup;
+use trust_dns_resolver::config::ResolverConfig;
+use trust_dns_resolver::config::ResolverOpts;
+use trust_dns_resolver::proto::rr::RData;
+use trust_dns_resolver::proto::rr::RecordType;
+use trust_dns_resolver::proto::xfer::DnsRequestOptions;
+use trust_dns_resolver::system_conf;
+use trust_dns_resolver::TokioAsyncResolver;
 
 #[cfg(unix)]
 use super::net_unix;
@@ -28,6 +39,23 @@ pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_listen", s.stateful_json_op2(op_listen));
   i.register_op("op_receive", s.stateful_json_op2(op_receive));
   i.register_op("op_send", s.stateful_json_op2(op_send));
+  i.register_op("op_resolve_dns", s.stateful_json_op2(op_resolve_dns));
+  i.register_op("op_join_multicast", s.stateful_json_op2(op_join_multicast));
+  i.register_op(
+    "op_leave_multicast",
+    s.stateful_json_op2(op_leave_multicast),
+  );
+  i.register_op("op_set_broadcast", s.stateful_json_op2(op_set_broadcast));
+  i.register_op(
+    "op_set_multicast_ttl",
+    s.stateful_json_op2(op_set_multicast_ttl),
+  );
+  i.register_op(
+    "op_set_multicast_loopback",
+    s.stateful_json_op2(op_set_multicast_loopback),
+  );
+  i.register_op("op_set_nodelay", s.stateful_json_op2(op_set_nodelay));
+  i.register_op("op_set_keepalive", s.stateful_json_op2(op_set_keepalive));
 }
 
 #[derive(Deserialize)]
@@ -378,6 +406,80 @@ fn op_shutdown(
   Ok(JsonOp::Sync(json!({})))
 }
 
+/// Returns the TCP stream underlying a plain or TLS connection.
+fn get_tcp_stream(
+  resource_table: &ResourceTable,
+  rid: u32,
+) -> Result<&TcpStream, OpError> {
+  let resource_holder = resource_table
+    .get::<StreamResourceHolder>(rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  match resource_holder.resource {
+    StreamResource::TcpStream(Some(ref stream)) => Ok(stream),
+    StreamResource::ServerTlsStream(ref stream) => Ok(stream.get_ref().0),
+    StreamResource::ClientTlsStream(ref stream) => Ok(stream.get_ref().0),
+    _ => Err(OpError::bad_resource_id()),
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetNoDelayArgs {
+  rid: u32,
+  no_delay: bool,
+}
+
+fn op_set_nodelay(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.Conn.setNoDelay");
+  let args: SetNoDelayArgs = serde_json::from_value(args)?;
+  let resource_table = isolate.resource_table.borrow();
+  let stream = get_tcp_stream(&resource_table, args.rid)?;
+  stream.set_nodelay(args.no_delay)?;
+  Ok(JsonOp::Sync(json!({})))
+}
+
+// Idle time before the first keepalive probe, if none is given. This matches
+// the default on Linux.
+const DEFAULT_KEEPALIVE_DELAY: Duration = Duration::from_secs(7200);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetKeepAliveArgs {
+  rid: u32,
+  keep_alive: bool,
+  /// Idle time before the first keepalive probe, in milliseconds.
+  initial_delay: Option<u64>,
+}
+
+fn op_set_keepalive(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.Conn.setKeepAlive");
+  let args: SetKeepAliveArgs = serde_json::from_value(args)?;
+  let keepalive = if args.keep_alive {
+    // Keepalive times are set in whole seconds.
+    let delay = args
+      .initial_delay
+      .map(|ms| Duration::from_secs(std::cmp::max(1, (ms + 999) / 1000)))
+      .unwrap_or(DEFAULT_KEEPALIVE_DELAY);
+    Some(delay)
+  } else {
+    None
+  };
+  let resource_table = isolate.resource_table.borrow();
+  let stream = get_tcp_stream(&resource_table, args.rid)?;
+  stream.set_keepalive(keepalive)?;
+  Ok(JsonOp::Sync(json!({})))
+}
+
 #[allow(dead_code)]
 struct TcpListenerResource {
   listener: TcpListener,
@@ -431,6 +533,214 @@ struct UdpSocketResource {
   socket: UdpSocket,
 }
 
+fn get_udp_socket(
+  resource_table: &ResourceTable,
+  rid: u32,
+) -> Result<&UdpSocket, OpError> {
+  resource_table
+    .get::<UdpSocketResource>(rid)
+    .map(|resource| &resource.socket)
+    .ok_or_else(OpError::bad_resource_id)
+}
+
+/// The interface to join a multicast group on. IPv4 interfaces are given by
+/// address and IPv6 interfaces by index.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MulticastInterface {
+  V4(String),
+  V6(u32),
+}
+
+#[derive(Deserialize)]
+struct MulticastArgs {
+  rid: u32,
+  address: String,
+  interface: Option<MulticastInterface>,
+}
+
+fn update_multicast_membership(
+  isolate: &mut CoreIsolate,
+  args: Value,
+  join: bool,
+) -> Result<JsonOp, OpError> {
+  let args: MulticastArgs = serde_json::from_value(args)?;
+  let address: IpAddr = args.address.parse().map_err(|_| {
+    OpError::type_error(format!(
+      "Invalid multicast address \"{}\"",
+      args.address
+    ))
+  })?;
+  let resource_table = isolate.resource_table.borrow();
+  let socket = get_udp_socket(&resource_table, args.rid)?;
+
+  match address {
+    IpAddr::V4(address) => {
+      let interface = match args.interface {
+        None => Ipv4Addr::UNSPECIFIED,
+        Some(MulticastInterface::V4(interface)) => {
+          interface.parse().map_err(|_| {
+            OpError::type_error(format!(
+              "Invalid network interface \"{}\"",
+              interface
+            ))
+          })?
+        }
+        Some(MulticastInterface::V6(_)) => {
+          return Err(OpError::type_error(
+            "IPv4 network interfaces must be given by address".to_string(),
+          ))
+        }
+      };
+      if join {
+        socket.join_multicast_v4(address, interface)?;
+      } else {
+        socket.leave_multicast_v4(address, interface)?;
+      }
+    }
+    IpAddr::V6(address) => {
+      let interface = match args.interface {
+        None => 0,
+        Some(MulticastInterface::V6(index)) => index,
+        Some(MulticastInterface::V4(_)) => {
+          return Err(OpError::type_error(
+            "IPv6 network interfaces must be given by index".to_string(),
+          ))
+        }
+      };
+      if join {
+        socket.join_multicast_v6(&address, interface)?;
+      } else {
+        socket.leave_multicast_v6(&address, interface)?;
+      }
+    }
+  }
+
+  Ok(JsonOp::Sync(json!({})))
+}
+
+fn op_join_multicast(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.DatagramConn.joinMulticast");
+  update_multicast_membership(isolate, args, true)
+}
+
+fn op_leave_multicast(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.DatagramConn.leaveMulticast");
+  update_multicast_membership(isolate, args, false)
+}
+
+#[derive(Deserialize)]
+struct SetBroadcastArgs {
+  rid: u32,
+  broadcast: bool,
+}
+
+fn op_set_broadcast(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.DatagramConn.setBroadcast");
+  let args: SetBroadcastArgs = serde_json::from_value(args)?;
+  let resource_table = isolate.resource_table.borrow();
+  let socket = get_udp_socket(&resource_table, args.rid)?;
+  socket.set_broadcast(args.broadcast)?;
+  Ok(JsonOp::Sync(json!({})))
+}
+
+#[derive(Deserialize)]
+struct SetMulticastTtlArgs {
+  rid: u32,
+  ttl: u32,
+}
+
+fn op_set_multicast_ttl(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.DatagramConn.setMulticastTTL");
+  let args: SetMulticastTtlArgs = serde_json::from_value(args)?;
+  let resource_table = isolate.resource_table.borrow();
+  let socket = get_udp_socket(&resource_table, args.rid)?;
+  if socket.local_addr()?.is_ipv4() {
+    socket.set_multicast_ttl_v4(args.ttl)?;
+  } else {
+    set_multicast_hops_v6(socket, args.ttl)?;
+  }
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// IPv6 sockets limit multicast datagrams by hop count instead of TTL, which
+/// tokio doesn't expose.
+#[cfg(unix)]
+fn set_multicast_hops_v6(socket: &UdpSocket, hops: u32) -> Result<(), OpError> {
+  use std::os::unix::io::AsRawFd;
+  let hops = hops as libc::c_int;
+  let result = unsafe {
+    libc::setsockopt(
+      socket.as_raw_fd(),
+      libc::IPPROTO_IPV6,
+      libc::IPV6_MULTICAST_HOPS,
+      &hops as *const libc::c_int as *const libc::c_void,
+      std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+    )
+  };
+  if result == -1 {
+    return Err(std::io::Error::last_os_error().into());
+  }
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_multicast_hops_v6(
+  _socket: &UdpSocket,
+  _hops: u32,
+) -> Result<(), OpError> {
+  Err(OpError::other(
+    "Setting the multicast TTL of IPv6 sockets is not supported on this platform"
+      .to_string(),
+  ))
+}
+
+#[derive(Deserialize)]
+struct SetMulticastLoopbackArgs {
+  rid: u32,
+  loopback: bool,
+}
+
+/// Sets whether multicast datagrams are looped back, for the address family
+/// the socket is bound to.
+fn op_set_multicast_loopback(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.DatagramConn.setMulticastLoopback");
+  let args: SetMulticastLoopbackArgs = serde_json::from_value(args)?;
+  let resource_table = isolate.resource_table.borrow();
+  let socket = get_udp_socket(&resource_table, args.rid)?;
+  if socket.local_addr()?.is_ipv4() {
+    socket.set_multicast_loop_v4(args.loopback)?;
+  } else {
+    socket.set_multicast_loop_v6(args.loopback)?;
+  }
+  Ok(JsonOp::Sync(json!({})))
+}
+
 #[derive(Deserialize)]
 struct IpListenArgs {
   hostname: String,
@@ -552,3 +862,111 @@ fn op_listen(
     _ => Err(OpError::other("Wrong argument format!".to_owned())),
   }
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolveDnsArgs {
+  query: String,
+  record_type: String,
+  name_server: Option<NameServer>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NameServer {
+  ip_addr: String,
+  #[serde(default = "default_dns_port")]
+  port: u16,
+}
+
+fn default_dns_port() -> u16 {
+  53
+}
+
+/// Looks up DNS records of the given type. Without an explicit name server
+/// the system configuration is used, and every configured name server must
+/// be allowed by the net permission.
+fn op_resolve_dns(
+  _isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.resolveDns");
+  let args: ResolveDnsArgs = serde_json::from_value(args)?;
+
+  let record_type = match args.record_type.as_str() {
+    "A" => RecordType::A,
+    "AAAA" => RecordType::AAAA,
+    "CNAME" => RecordType::CNAME,
+    "MX" => RecordType::MX,
+    "NS" => RecordType::NS,
+    "SRV" => RecordType::SRV,
+    "TXT" => RecordType::TXT,
+    _ => {
+      return Err(OpError::type_error(format!(
+        "Unsupported record type \"{}\"",
+        args.record_type
+      )))
+    }
+  };
+
+  let (config, opts) = match &args.name_server {
+    Some(name_server) => {
+      let ip: IpAddr = name_server.ip_addr.parse().map_err(|_| {
+        OpError::type_error(format!(
+          "Invalid name server address \"{}\"",
+          name_server.ip_addr
+        ))
+      })?;
+      let group =
+        NameServerConfigGroup::from_ips_clear(&[ip], name_server.port);
+      (
+        ResolverConfig::from_parts(None, vec![], group),
+        ResolverOpts::default(),
+      )
+    }
+    None => system_conf::read_system_conf()?,
+  };
+  for name_server in config.name_servers() {
+    let addr = name_server.socket_addr;
+    state.check_net(&addr.ip().to_string(), addr.port())?;
+  }
+
+  let op = async move {
+    let resolver = TokioAsyncResolver::tokio(config, opts).await?;
+    let lookup = resolver
+      .lookup(
+        args.query.as_str(),
+        record_type,
+        DnsRequestOptions::default(),
+      )
+      .await?;
+    let records = lookup
+      .iter()
+      .filter_map(|rdata| match rdata {
+        RData::A(ip) => Some(json!(ip.to_string())),
+        RData::AAAA(ip) => Some(json!(ip.to_string())),
+        RData::CNAME(name) | RData::NS(name) => Some(json!(name.to_string())),
+        RData::MX(mx) => Some(json!({
+          "preference": mx.preference(),
+          "exchange": mx.exchange().to_string(),
+        })),
+        RData::SRV(srv) => Some(json!({
+          "priority": srv.priority(),
+          "weight": srv.weight(),
+          "port": srv.port(),
+          "target": srv.target().to_string(),
+        })),
+        RData::TXT(txt) => Some(json!(txt
+          .iter()
+          .map(|data| String::from_utf8_lossy(data).into_owned())
+          .collect::<Vec<_>>())),
+        // Only the record types above can be requested.
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    Ok(json!(records))
+  };
+  Ok(JsonOp::Async(op.boxed_local()))
+}
@@ -143,11 +143,13 @@ struct Exit {
 }
 
 fn op_exit(
-  _s: &State,
+  state: &State,
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
   let args: Exit = serde_json::from_value(args)?;
+  // Worker destructors don't run on `process::exit()`.
+  state.stop_cpu_profiler();
   std::process::exit(args.code)
 }
 
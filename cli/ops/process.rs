@@ -1,6 +1,11 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use super::dispatch_json::{Deserialize, JsonOp, Value};
-use super::io::{std_file_resource, StreamResource, StreamResourceHolder};
+#[cfg(unix)]
+use super::io::set_subprocess_stdio_nonblocking;
+use super::io::{
+  pump_from_reader, pump_to_writer, subprocess_stdio, StreamResource,
+  StreamResourceHolder, SubprocessStdio,
+};
 use crate::op_error::OpError;
 use crate::signal::kill;
 use crate::state::State;
@@ -10,26 +15,62 @@ use deno_core::ZeroCopyBuf;
 use futures::future::poll_fn;
 use futures::future::FutureExt;
 use futures::TryFutureExt;
+use std::collections::HashMap;
 use std::convert::From;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::process::Command;
 
+#[cfg(unix)]
+use futures::ready;
+#[cfg(unix)]
+use nix::fcntl::{fcntl, FcntlArg, FdFlag, OFlag};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
+#[cfg(unix)]
+use std::pin::Pin;
+#[cfg(unix)]
+use std::task::{Context, Poll};
+#[cfg(unix)]
+use std::time::Duration;
+#[cfg(unix)]
+use tokio::io::{AsyncRead, AsyncWrite, PollEvented};
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_run", s.stateful_json_op2(op_run));
   i.register_op("op_run_status", s.stateful_json_op2(op_run_status));
+  i.register_op("op_run_sync", s.stateful_json_op(op_run_sync));
   i.register_op("op_kill", s.stateful_json_op(op_kill));
+  i.register_op("op_pty_resize", s.stateful_json_op2(op_pty_resize));
 }
 
-fn clone_file(
+/// Connects the resource `rid` to a stdio stream of a subprocess. Resources
+/// the child takes over are added to `handed_over`, to be taken out of the
+/// resource table once it has been spawned. Returns whether the resource has
+/// to be pumped through a pipe.
+fn resource_stdio(
   rid: u32,
   resource_table: &mut ResourceTable,
-) -> Result<std::fs::File, OpError> {
-  std_file_resource(resource_table, rid, move |r| match r {
-    Ok(std_file) => std_file.try_clone().map_err(OpError::from),
-    Err(_) => Err(OpError::bad_resource_id()),
-  })
+  handed_over: &mut Vec<u32>,
+) -> Result<(std::process::Stdio, bool), OpError> {
+  let (stdio, pumped) = match subprocess_stdio(resource_table, rid)? {
+    SubprocessStdio::Shared(stdio) => return Ok((stdio, false)),
+    SubprocessStdio::Moved(stdio) => (stdio, false),
+    SubprocessStdio::Stream => (std::process::Stdio::piped(), true),
+  };
+  if !handed_over.contains(&rid) {
+    handed_over.push(rid);
+  } else if pumped {
+    // A descriptor can serve as several stdio streams of the child, but a
+    // resource can only be pumped into one of them.
+    return Err(OpError::other(
+      "Resource can't be pumped into more than one stdio stream".to_string(),
+    ));
+  }
+  Ok((stdio, pumped))
 }
 
 fn subprocess_stdio_map(s: &str) -> std::process::Stdio {
@@ -53,17 +94,90 @@ struct RunArgs {
   stdin_rid: u32,
   stdout_rid: u32,
   stderr_rid: u32,
+  pty: Option<PtySize>,
+  #[serde(default)]
+  detached: bool,
+  uid: Option<u32>,
+  gid: Option<u32>,
+  umask: Option<u32>,
+  rlimits: Option<ResourceLimits>,
+  timeout: Option<u64>,
+  kill_grace_period: Option<u64>,
+}
+
+/// Time in milliseconds between SIGTERM and SIGKILL when a process times out.
+#[cfg(unix)]
+const DEFAULT_KILL_GRACE_PERIOD: u64 = 5000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(unix), allow(dead_code))]
+struct ResourceLimits {
+  /// CPU time in seconds.
+  cpu: Option<u64>,
+  /// Size of the address space in bytes.
+  memory: Option<u64>,
+  open_files: Option<u64>,
+}
+
+#[cfg(unix)]
+impl ResourceLimits {
+  /// Sets both the soft and the hard limits of the calling process. Runs
+  /// between fork and exec, so it must not allocate.
+  fn apply(&self) -> std::io::Result<()> {
+    let rlimit = |value: u64| libc::rlimit {
+      rlim_cur: value as libc::rlim_t,
+      rlim_max: value as libc::rlim_t,
+    };
+    let check = |result: libc::c_int| {
+      if result == -1 {
+        Err(std::io::Error::last_os_error())
+      } else {
+        Ok(())
+      }
+    };
+    if let Some(cpu) = self.cpu {
+      check(unsafe { libc::setrlimit(libc::RLIMIT_CPU, &rlimit(cpu)) })?;
+    }
+    if let Some(memory) = self.memory {
+      check(unsafe { libc::setrlimit(libc::RLIMIT_AS, &rlimit(memory)) })?;
+    }
+    if let Some(open_files) = self.open_files {
+      check(unsafe {
+        libc::setrlimit(libc::RLIMIT_NOFILE, &rlimit(open_files))
+      })?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(not(unix), allow(dead_code))]
+struct PtySize {
+  cols: u16,
+  rows: u16,
 }
 
 struct ChildResource {
   child: tokio::process::Child,
+  /// Set once the child has been reaped, after which its pid may be reused
+  /// and must not be signalled anymore.
+  exited: Arc<AtomicBool>,
+  /// Set once the child is closed, which stops its timeout.
+  closed: Arc<AtomicBool>,
+}
+
+impl Drop for ChildResource {
+  fn drop(&mut self) {
+    self.closed.store(true, Ordering::SeqCst);
+  }
 }
 
 fn op_run(
   isolate: &mut CoreIsolate,
   state: &State,
   args: Value,
-  _zero_copy: Option<ZeroCopyBuf>,
+  zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
   let run_args: RunArgs = serde_json::from_value(args)?;
 
@@ -84,37 +198,187 @@ fn op_run(
     c.env(key, value);
   }
 
-  // TODO: make this work with other resources, eg. sockets
+  // Resources the child can't use directly are pumped through pipes once it
+  // has been spawned.
+  let mut handed_over = Vec::new();
+  let mut stdin_source = None;
+  let mut stdin_pump = None;
   let stdin_rid = run_args.stdin_rid;
-  if stdin_rid > 0 {
-    let file = clone_file(stdin_rid, &mut resource_table)?;
-    c.stdin(file);
+  if let Some(buf) = zero_copy {
+    let cursor = std::io::Cursor::new(buf.to_vec());
+    stdin_source = Some(Box::new(StreamResourceHolder::new(
+      StreamResource::BlobBody(cursor),
+    )));
+    c.stdin(std::process::Stdio::piped());
+  } else if stdin_rid > 0 {
+    let (stdio, pumped) =
+      resource_stdio(stdin_rid, &mut resource_table, &mut handed_over)?;
+    if pumped {
+      stdin_pump = Some(stdin_rid);
+    }
+    c.stdin(stdio);
   } else {
     c.stdin(subprocess_stdio_map(run_args.stdin.as_ref()));
   }
 
+  let mut stdout_pump = None;
   let stdout_rid = run_args.stdout_rid;
   if stdout_rid > 0 {
-    let file = clone_file(stdout_rid, &mut resource_table)?;
-    c.stdout(file);
+    let (stdio, pumped) =
+      resource_stdio(stdout_rid, &mut resource_table, &mut handed_over)?;
+    if pumped {
+      stdout_pump = Some(stdout_rid);
+    }
+    c.stdout(stdio);
   } else {
     c.stdout(subprocess_stdio_map(run_args.stdout.as_ref()));
   }
 
+  let mut stderr_pump = None;
   let stderr_rid = run_args.stderr_rid;
   if stderr_rid > 0 {
-    let file = clone_file(stderr_rid, &mut resource_table)?;
-    c.stderr(file);
+    let (stdio, pumped) =
+      resource_stdio(stderr_rid, &mut resource_table, &mut handed_over)?;
+    if pumped {
+      stderr_pump = Some(stderr_rid);
+    }
+    c.stderr(stdio);
   } else {
     c.stderr(subprocess_stdio_map(run_args.stderr.as_ref()));
   }
 
-  // We want to kill child when it's closed
-  c.kill_on_drop(true);
+  let unix_options = [
+    ("pty", run_args.pty.is_some()),
+    ("detached", run_args.detached),
+    ("uid", run_args.uid.is_some()),
+    ("gid", run_args.gid.is_some()),
+    ("umask", run_args.umask.is_some()),
+    ("rlimits", run_args.rlimits.is_some()),
+    ("timeout", run_args.timeout.is_some()),
+    ("killGracePeriod", run_args.kill_grace_period.is_some()),
+  ];
+  for (name, is_set) in unix_options.iter() {
+    if *is_set {
+      state.check_unstable(&format!("Deno.RunOptions.{}", name));
+      #[cfg(not(unix))]
+      return Err(OpError::other(format!(
+        "'{}' is only supported on Unix",
+        name
+      )));
+    }
+  }
+
+  // A pseudo-terminal makes the child a session leader, and so does
+  // `detached`; either way signals can be sent to its whole process group.
+  let group_leader = run_args.detached || run_args.pty.is_some();
+
+  #[cfg(unix)]
+  {
+    if run_args.detached && run_args.pty.is_none() {
+      unsafe {
+        c.pre_exec(|| {
+          if libc::setsid() == -1 {
+            return Err(std::io::Error::last_os_error());
+          }
+          Ok(())
+        });
+      }
+    }
+    if let Some(uid) = run_args.uid {
+      c.uid(uid);
+    }
+    if let Some(gid) = run_args.gid {
+      c.gid(gid);
+    }
+    let umask = run_args.umask;
+    let rlimits = run_args.rlimits;
+    if umask.is_some() || rlimits.is_some() {
+      unsafe {
+        c.pre_exec(move || {
+          if let Some(mask) = umask {
+            libc::umask(mask as libc::mode_t);
+          }
+          if let Some(rlimits) = &rlimits {
+            rlimits.apply()?;
+          }
+          Ok(())
+        });
+      }
+    }
+  }
+
+  // A pseudo-terminal replaces whatever stdio was configured above.
+  #[cfg(unix)]
+  let pty_master = match run_args.pty {
+    Some(size) => Some(attach_pty(&mut c, size)?),
+    None => None,
+  };
+
+  // We want to kill child when it's closed, unless it's meant to outlive us.
+  c.kill_on_drop(!run_args.detached);
+
+  // Resources handed over to the child only leave the resource table once it
+  // has been spawned, so they are still usable if that fails.
+  #[cfg(unix)]
+  for rid in &handed_over {
+    set_subprocess_stdio_nonblocking(&mut resource_table, *rid, false)?;
+  }
 
   // Spawn the command.
-  let mut child = c.spawn()?;
+  let spawned = c.spawn();
+  #[cfg(unix)]
+  {
+    if spawned.is_err() {
+      for rid in &handed_over {
+        set_subprocess_stdio_nonblocking(&mut resource_table, *rid, true)?;
+      }
+    }
+  }
+  let mut child = spawned?;
   let pid = child.id();
+  let mut handed_over: HashMap<u32, Box<StreamResourceHolder>> = handed_over
+    .into_iter()
+    .filter_map(|rid| {
+      let resource_holder = resource_table.remove::<StreamResourceHolder>(rid);
+      resource_holder.map(|resource_holder| (rid, resource_holder))
+    })
+    .collect();
+  // Drop the command so the parent's copies of the pty slave are closed,
+  // otherwise reads from the master never see the child hang up.
+  drop(c);
+
+  #[cfg(unix)]
+  let pty_rid = match pty_master {
+    Some(pty_master) => {
+      let rid = resource_table.add(
+        "pty",
+        Box::new(StreamResourceHolder::new(StreamResource::Pty(Box::new(
+          pty_master,
+        )))),
+      );
+      Some(rid)
+    }
+    None => None,
+  };
+  #[cfg(not(unix))]
+  let pty_rid: Option<u32> = None;
+
+  let stdin_source = stdin_source
+    .or_else(|| stdin_pump.and_then(|rid| handed_over.remove(&rid)));
+  if let Some(source) = stdin_source {
+    let child_stdin = child.stdin.take().unwrap();
+    tokio::spawn(log_pump_error(pump_to_writer(source, child_stdin)));
+  }
+  if let Some(sink) = stdout_pump.and_then(|rid| handed_over.remove(&rid)) {
+    let child_stdout = child.stdout.take().unwrap();
+    tokio::spawn(log_pump_error(pump_from_reader(child_stdout, sink)));
+  }
+  if let Some(sink) = stderr_pump.and_then(|rid| handed_over.remove(&rid)) {
+    let child_stderr = child.stderr.take().unwrap();
+    tokio::spawn(log_pump_error(pump_from_reader(child_stderr, sink)));
+  }
+  // The rest now belongs to the child, so the parent closes its copies.
+  drop(handed_over);
 
   let stdin_rid = match child.stdin.take() {
     Some(child_stdin) => {
@@ -155,7 +419,36 @@ fn op_run(
     None => None,
   };
 
-  let child_resource = ChildResource { child };
+  let exited = Arc::new(AtomicBool::new(false));
+  let closed = Arc::new(AtomicBool::new(false));
+  #[cfg(unix)]
+  {
+    if let Some(timeout) = run_args.timeout {
+      let grace_period = run_args
+        .kill_grace_period
+        .unwrap_or(DEFAULT_KILL_GRACE_PERIOD);
+      let target = if group_leader {
+        -(pid as i32)
+      } else {
+        pid as i32
+      };
+      tokio::spawn(enforce_timeout(
+        target,
+        Duration::from_millis(timeout),
+        Duration::from_millis(grace_period),
+        exited.clone(),
+        closed.clone(),
+      ));
+    }
+  }
+  #[cfg(not(unix))]
+  let _ = group_leader;
+
+  let child_resource = ChildResource {
+    child,
+    exited,
+    closed,
+  };
   let child_rid = resource_table.add("child", Box::new(child_resource));
 
   Ok(JsonOp::Sync(json!({
@@ -164,9 +457,219 @@ fn op_run(
     "stdinRid": stdin_rid,
     "stdoutRid": stdout_rid,
     "stderrRid": stderr_rid,
+    "ptyRid": pty_rid,
   })))
 }
 
+async fn log_pump_error(
+  pump: impl std::future::Future<Output = Result<(), OpError>>,
+) {
+  // The child closing its end of a pipe early is not an error of the parent.
+  if let Err(e) = pump.await {
+    debug!("subprocess stdio pump error: {}", e);
+  }
+}
+
+/// The master side of a pseudo-terminal allocated for a child process.
+#[cfg(unix)]
+pub struct PtyMaster {
+  io: PollEvented<PtyFd>,
+}
+
+#[cfg(unix)]
+impl PtyMaster {
+  fn new(fd: RawFd) -> Result<Self, OpError> {
+    // Take ownership first so the descriptor is closed on every error path.
+    let file = unsafe { std::fs::File::from_raw_fd(fd) };
+    set_cloexec(fd)?;
+    let flags = OFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFL)?);
+    fcntl(fd, FcntlArg::F_SETFL(flags | OFlag::O_NONBLOCK))?;
+    let io = PollEvented::new(PtyFd(file))?;
+    Ok(Self { io })
+  }
+
+  fn resize(&self, size: PtySize) -> Result<(), OpError> {
+    let winsize = window_size(size);
+    let fd = self.as_raw_fd();
+    // TIOCSWINSZ also delivers SIGWINCH to the foreground process group.
+    if unsafe { libc::ioctl(fd, libc::TIOCSWINSZ, &winsize) } == -1 {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+  }
+}
+
+#[cfg(unix)]
+impl AsRawFd for PtyMaster {
+  fn as_raw_fd(&self) -> RawFd {
+    self.io.get_ref().0.as_raw_fd()
+  }
+}
+
+#[cfg(unix)]
+impl AsyncRead for PtyMaster {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+    buf: &mut [u8],
+  ) -> Poll<std::io::Result<usize>> {
+    match ready!(Pin::new(&mut self.get_mut().io).poll_read(cx, buf)) {
+      // Linux reports EIO rather than end-of-file once every descriptor
+      // for the slave side has been closed.
+      Err(e) if e.raw_os_error() == Some(libc::EIO) => Poll::Ready(Ok(0)),
+      result => Poll::Ready(result),
+    }
+  }
+}
+
+#[cfg(unix)]
+impl AsyncWrite for PtyMaster {
+  fn poll_write(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+    buf: &[u8],
+  ) -> Poll<std::io::Result<usize>> {
+    Pin::new(&mut self.get_mut().io).poll_write(cx, buf)
+  }
+
+  fn poll_flush(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+  ) -> Poll<std::io::Result<()>> {
+    Pin::new(&mut self.get_mut().io).poll_flush(cx)
+  }
+
+  fn poll_shutdown(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+  ) -> Poll<std::io::Result<()>> {
+    Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+  }
+}
+
+/// Owned pty master descriptor that can be registered with the reactor.
+#[cfg(unix)]
+struct PtyFd(std::fs::File);
+
+#[cfg(unix)]
+impl mio::Evented for PtyFd {
+  fn register(
+    &self,
+    poll: &mio::Poll,
+    token: mio::Token,
+    interest: mio::Ready,
+    opts: mio::PollOpt,
+  ) -> std::io::Result<()> {
+    mio::unix::EventedFd(&self.0.as_raw_fd())
+      .register(poll, token, interest, opts)
+  }
+
+  fn reregister(
+    &self,
+    poll: &mio::Poll,
+    token: mio::Token,
+    interest: mio::Ready,
+    opts: mio::PollOpt,
+  ) -> std::io::Result<()> {
+    mio::unix::EventedFd(&self.0.as_raw_fd())
+      .reregister(poll, token, interest, opts)
+  }
+
+  fn deregister(&self, poll: &mio::Poll) -> std::io::Result<()> {
+    mio::unix::EventedFd(&self.0.as_raw_fd()).deregister(poll)
+  }
+}
+
+#[cfg(unix)]
+impl Read for PtyFd {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    (&self.0).read(buf)
+  }
+}
+
+#[cfg(unix)]
+impl Write for PtyFd {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    (&self.0).write(buf)
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    (&self.0).flush()
+  }
+}
+
+#[cfg(unix)]
+fn set_cloexec(fd: RawFd) -> Result<(), OpError> {
+  fcntl(fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+  Ok(())
+}
+
+#[cfg(unix)]
+fn window_size(size: PtySize) -> nix::pty::Winsize {
+  nix::pty::Winsize {
+    ws_row: size.rows,
+    ws_col: size.cols,
+    ws_xpixel: 0,
+    ws_ypixel: 0,
+  }
+}
+
+/// Allocates a pseudo-terminal and makes its slave side the controlling
+/// terminal and stdio of the command. Returns the master side.
+#[cfg(unix)]
+fn attach_pty(c: &mut Command, size: PtySize) -> Result<PtyMaster, OpError> {
+  let pty = nix::pty::openpty(&window_size(size), None)?;
+  let slave = unsafe { std::fs::File::from_raw_fd(pty.slave) };
+  let master = PtyMaster::new(pty.master)?;
+  set_cloexec(slave.as_raw_fd())?;
+
+  c.stdin(slave.try_clone()?);
+  c.stdout(slave.try_clone()?);
+  c.stderr(slave);
+
+  unsafe {
+    c.pre_exec(|| {
+      // Start a new session so the slave can become the controlling
+      // terminal of the child.
+      if libc::setsid() == -1 {
+        return Err(std::io::Error::last_os_error());
+      }
+      if libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+        return Err(std::io::Error::last_os_error());
+      }
+      Ok(())
+    });
+  }
+
+  Ok(master)
+}
+
+/// Sends SIGTERM once `timeout` has elapsed and SIGKILL if the process is
+/// still around after `grace_period`, unless the child is closed first. A
+/// negative `pid` addresses a process group, which is signalled even after
+/// its leader has exited, as long as any of its members remain.
+#[cfg(unix)]
+async fn enforce_timeout(
+  pid: i32,
+  timeout: Duration,
+  grace_period: Duration,
+  exited: Arc<AtomicBool>,
+  closed: Arc<AtomicBool>,
+) {
+  let stopped = || {
+    closed.load(Ordering::SeqCst) || (pid > 0 && exited.load(Ordering::SeqCst))
+  };
+  tokio::time::delay_for(timeout).await;
+  if stopped() || kill(pid, libc::SIGTERM).is_err() {
+    return;
+  }
+  tokio::time::delay_for(grace_period).await;
+  if stopped() {
+    return;
+  }
+  let _ = kill(pid, libc::SIGKILL);
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct RunStatusArgs {
@@ -192,7 +695,11 @@ fn op_run_status(
         .get_mut::<ChildResource>(rid)
         .ok_or_else(OpError::bad_resource_id)?;
       let child = &mut child_resource.child;
-      child.map_err(OpError::from).poll_unpin(cx)
+      let poll = child.map_err(OpError::from).poll_unpin(cx);
+      if poll.is_ready() {
+        child_resource.exited.store(true, Ordering::SeqCst);
+      }
+      poll
     })
     .await?;
 
@@ -218,6 +725,126 @@ fn op_run_status(
   Ok(JsonOp::Async(future.boxed_local()))
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunSyncArgs {
+  cmd: Vec<String>,
+  cwd: Option<String>,
+  env: Vec<(String, String)>,
+  stdin: String,
+  stdout: String,
+  stderr: String,
+  max_buffer: Option<usize>,
+}
+
+/// Reads `reader` to the end, failing once more than `max_buffer` bytes have
+/// been read.
+fn read_to_end_capped(
+  mut reader: impl Read,
+  name: &str,
+  max_buffer: Option<usize>,
+) -> Result<Vec<u8>, OpError> {
+  let mut buf = Vec::new();
+  match max_buffer {
+    Some(max_buffer) => {
+      reader.take(max_buffer as u64 + 1).read_to_end(&mut buf)?;
+      if buf.len() > max_buffer {
+        return Err(OpError::other(format!(
+          "{} exceeded maxBuffer of {} bytes",
+          name, max_buffer
+        )));
+      }
+    }
+    None => {
+      reader.read_to_end(&mut buf)?;
+    }
+  }
+  Ok(buf)
+}
+
+/// Spawns a process, waits for it to exit and returns its captured output.
+/// Output streams are read on separate threads so that a child blocked on a
+/// full pipe can't deadlock against the parent writing its stdin.
+fn op_run_sync(
+  state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.runSync");
+  let args: RunSyncArgs = serde_json::from_value(args)?;
+  state.check_run()?;
+
+  let mut c = std::process::Command::new(&args.cmd[0]);
+  c.args(&args.cmd[1..]);
+  if let Some(cwd) = &args.cwd {
+    c.current_dir(cwd);
+  }
+  for (key, value) in &args.env {
+    c.env(key, value);
+  }
+  if zero_copy.is_some() {
+    c.stdin(std::process::Stdio::piped());
+  } else {
+    c.stdin(subprocess_stdio_map(&args.stdin));
+  }
+  c.stdout(subprocess_stdio_map(&args.stdout));
+  c.stderr(subprocess_stdio_map(&args.stderr));
+
+  let mut child = c.spawn()?;
+
+  let stdin_writer = match (child.stdin.take(), zero_copy) {
+    (Some(mut child_stdin), Some(buf)) => {
+      let data = buf.to_vec();
+      Some(std::thread::spawn(move || {
+        // The child may exit without reading all of its input.
+        let _ = child_stdin.write_all(&data);
+      }))
+    }
+    _ => None,
+  };
+  let max_buffer = args.max_buffer;
+  let stdout_reader = child.stdout.take().map(|child_stdout| {
+    std::thread::spawn(move || {
+      read_to_end_capped(child_stdout, "stdout", max_buffer)
+    })
+  });
+  let stderr_reader = child.stderr.take().map(|child_stderr| {
+    std::thread::spawn(move || {
+      read_to_end_capped(child_stderr, "stderr", max_buffer)
+    })
+  });
+
+  let join = |reader: Option<std::thread::JoinHandle<_>>| match reader {
+    Some(reader) => reader.join().unwrap(),
+    None => Ok(Vec::new()),
+  };
+  let stdout = join(stdout_reader);
+  let stderr = join(stderr_reader);
+  if stdout.is_err() || stderr.is_err() {
+    // Don't leave behind a child that is still producing output.
+    let _ = child.kill();
+  }
+  let status = child.wait()?;
+  if let Some(stdin_writer) = stdin_writer {
+    stdin_writer.join().unwrap();
+  }
+  let stdout = stdout?;
+  let stderr = stderr?;
+
+  let code = status.code();
+  #[cfg(unix)]
+  let signal = status.signal();
+  #[cfg(not(unix))]
+  let signal: Option<i32> = None;
+
+  Ok(JsonOp::Sync(json!({
+    "code": code,
+    "signal": signal,
+    "stdout": base64::encode(&stdout),
+    "stderr": base64::encode(&stderr),
+  })))
+}
+
 #[derive(Deserialize)]
 struct KillArgs {
   pid: i32,
@@ -236,3 +863,44 @@ fn op_kill(
   kill(args.pid, args.signo)?;
   Ok(JsonOp::Sync(json!({})))
 }
+
+#[derive(Deserialize)]
+#[cfg_attr(not(unix), allow(dead_code))]
+struct PtyResizeArgs {
+  rid: i32,
+  cols: u16,
+  rows: u16,
+}
+
+fn op_pty_resize(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.Pty.resize");
+  let args: PtyResizeArgs = serde_json::from_value(args)?;
+
+  #[cfg(unix)]
+  {
+    let mut resource_table = isolate.resource_table.borrow_mut();
+    let resource_holder = resource_table
+      .get_mut::<StreamResourceHolder>(args.rid as u32)
+      .ok_or_else(OpError::bad_resource_id)?;
+    match &resource_holder.resource {
+      StreamResource::Pty(pty_master) => pty_master.resize(PtySize {
+        cols: args.cols,
+        rows: args.rows,
+      })?,
+      _ => return Err(OpError::bad_resource_id()),
+    }
+    Ok(JsonOp::Sync(json!({})))
+  }
+  #[cfg(not(unix))]
+  {
+    let _ = isolate;
+    Err(OpError::other(
+      "'pty' is only supported on Unix".to_string(),
+    ))
+  }
+}
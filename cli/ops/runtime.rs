@@ -1,17 +1,26 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-use super::dispatch_json::{JsonOp, Value};
+use super::dispatch_json::{Deserialize, JsonOp, Value};
 use crate::colors;
+use crate::heap_snapshot::HeapSnapshotRequest;
 use crate::op_error::OpError;
 use crate::state::State;
 use crate::version;
 use crate::DenoSubcommand;
 use deno_core::CoreIsolate;
+use deno_core::ErrBox;
 use deno_core::ZeroCopyBuf;
+use futures::channel::oneshot;
+use futures::future::FutureExt;
 use std::env;
+use std::path::Path;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_start", s.stateful_json_op(op_start));
   i.register_op("op_metrics", s.stateful_json_op(op_metrics));
+  i.register_op(
+    "op_write_heap_snapshot",
+    s.stateful_json_op(op_write_heap_snapshot),
+  );
 }
 
 fn op_start(
@@ -61,3 +70,34 @@ fn op_metrics(
     "bytesReceived": m.bytes_received
   })))
 }
+
+#[derive(Deserialize)]
+struct WriteHeapSnapshotArgs {
+  path: String,
+}
+
+fn op_write_heap_snapshot(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.writeHeapSnapshot");
+  let args: WriteHeapSnapshotArgs = serde_json::from_value(args)?;
+  let path = crate::fs::resolve_from_cwd(Path::new(&args.path))?;
+  state.check_write(&path)?;
+
+  // The snapshot can't be taken while this op is executing, so it is handed
+  // over to the worker which writes it once control returns from JavaScript.
+  let (sender, receiver) = oneshot::channel::<Result<(), ErrBox>>();
+  state
+    .borrow_mut()
+    .heap_snapshot_requests
+    .push(HeapSnapshotRequest { path, sender });
+
+  let fut = receiver.map(|result| match result {
+    Ok(Ok(())) => Ok(json!({})),
+    Ok(Err(err)) => Err(OpError::from(err)),
+    Err(_) => Err(OpError::other("Heap snapshot was cancelled".to_string())),
+  });
+  Ok(JsonOp::Async(fut.boxed_local()))
+}
@@ -1,10 +1,12 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-use super::dispatch_json::{JsonOp, Value};
+use super::dispatch_json::{Deserialize, JsonOp, Value};
 use crate::op_error::OpError;
 use crate::ops::json_op;
+use crate::ops::worker_host::detach_message_ports;
 use crate::state::State;
 use crate::web_worker::WebWorkerHandle;
 use crate::worker::WorkerEvent;
+use crate::worker::WorkerMessage;
 use deno_core::CoreIsolate;
 use deno_core::ZeroCopyBuf;
 use futures::channel::mpsc;
@@ -16,15 +18,18 @@ pub fn web_worker_op<D>(
 ) -> impl Fn(&mut CoreIsolate, Value, Option<ZeroCopyBuf>) -> Result<JsonOp, OpError>
 where
   D: Fn(
+    &mut CoreIsolate,
     &mpsc::Sender<WorkerEvent>,
     Value,
     Option<ZeroCopyBuf>,
   ) -> Result<JsonOp, OpError>,
 {
-  move |_isolate: &mut CoreIsolate,
+  move |isolate: &mut CoreIsolate,
         args: Value,
         zero_copy: Option<ZeroCopyBuf>|
-        -> Result<JsonOp, OpError> { dispatcher(&sender, args, zero_copy) }
+        -> Result<JsonOp, OpError> {
+    dispatcher(isolate, &sender, args, zero_copy)
+  }
 }
 
 pub fn web_worker_op2<D>(
@@ -71,16 +76,30 @@ pub fn init(
   );
 }
 
+#[derive(Deserialize)]
+struct PostMessageArgs {
+  transfer: Vec<u32>,
+}
+
 /// Post message to host as guest worker
 fn op_worker_post_message(
+  isolate: &mut CoreIsolate,
   sender: &mpsc::Sender<WorkerEvent>,
-  _args: Value,
+  args: Value,
   data: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  let d = Vec::from(data.unwrap().as_ref()).into_boxed_slice();
+  let args: PostMessageArgs = serde_json::from_value(args)?;
+  let ports = detach_message_ports(
+    &mut isolate.resource_table.borrow_mut(),
+    &args.transfer,
+  )?;
+  let msg = WorkerMessage {
+    data: Vec::from(data.unwrap().as_ref()).into_boxed_slice(),
+    ports,
+  };
   let mut sender = sender.clone();
   sender
-    .try_send(WorkerEvent::Message(d))
+    .try_send(WorkerEvent::Message(msg))
     .expect("Failed to post message to host");
   Ok(JsonOp::Sync(json!({})))
 }
@@ -0,0 +1,419 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use super::tls::{create_client_config, ClientTlsOptions};
+use crate::op_error::OpError;
+use crate::resolve_addr::resolve_addr;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use futures::future::FutureExt;
+use futures::lock::Mutex;
+use futures::stream::SplitSink;
+use futures::stream::SplitStream;
+use futures::SinkExt;
+use futures::StreamExt;
+use http::header::HeaderValue;
+use std::borrow::Cow;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Error as WsError;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use webpki::DNSNameRef;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_ws_check_permission",
+    s.stateful_json_op2(op_ws_check_permission),
+  );
+  i.register_op("op_ws_create", s.stateful_json_op2(op_ws_create));
+  i.register_op("op_ws_send", s.stateful_json_op2(op_ws_send));
+  i.register_op("op_ws_close", s.stateful_json_op2(op_ws_close));
+  i.register_op("op_ws_next_event", s.stateful_json_op2(op_ws_next_event));
+  i.register_op("op_ws_read_binary", s.stateful_json_op2(op_ws_read_binary));
+}
+
+/// The connection of a WebSocket, which is either plain TCP for `ws:` URLs
+/// or TLS for `wss:` URLs.
+enum WsConn {
+  Tcp(TcpStream),
+  Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for WsConn {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+    buf: &mut [u8],
+  ) -> Poll<Result<usize, io::Error>> {
+    match self.get_mut() {
+      WsConn::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+      WsConn::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+    }
+  }
+}
+
+impl AsyncWrite for WsConn {
+  fn poll_write(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+    buf: &[u8],
+  ) -> Poll<Result<usize, io::Error>> {
+    match self.get_mut() {
+      WsConn::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+      WsConn::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+    }
+  }
+
+  fn poll_flush(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+  ) -> Poll<Result<(), io::Error>> {
+    match self.get_mut() {
+      WsConn::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+      WsConn::Tls(stream) => Pin::new(stream).poll_flush(cx),
+    }
+  }
+
+  fn poll_shutdown(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+  ) -> Poll<Result<(), io::Error>> {
+    match self.get_mut() {
+      WsConn::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+      WsConn::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+    }
+  }
+}
+
+type WsSink = SplitSink<WebSocketStream<WsConn>, Message>;
+type WsSource = SplitStream<WebSocketStream<WsConn>>;
+
+/// An open WebSocket. Both halves are locked while in use, so messages can be
+/// sent while waiting for the next one to arrive.
+struct WsResource {
+  sink: Rc<Mutex<WsSink>>,
+  source: Rc<Mutex<WsSource>>,
+  /// The last binary message received, until `op_ws_read_binary` copies it
+  /// into a buffer provided by JS.
+  binary: Option<Vec<u8>>,
+}
+
+fn ws_error(e: WsError) -> OpError {
+  match e {
+    WsError::Io(e) => OpError::from(e),
+    e => OpError::other(e.to_string()),
+  }
+}
+
+#[derive(Deserialize)]
+struct CheckPermissionArgs {
+  url: String,
+}
+
+/// Checks the net permission for a WebSocket URL, so that the `WebSocket`
+/// constructor can throw synchronously.
+pub fn op_ws_check_permission(
+  _isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CheckPermissionArgs = serde_json::from_value(args)?;
+  let url = url::Url::parse(&args.url).map_err(|_| {
+    OpError::type_error(format!("Invalid WebSocket URL \"{}\"", args.url))
+  })?;
+  state.check_net_url(&url)?;
+  Ok(JsonOp::Sync(json!({})))
+}
+
+#[derive(Deserialize)]
+struct CreateArgs {
+  url: String,
+  protocols: Vec<String>,
+}
+
+/// Connects to a WebSocket server and performs the opening handshake.
+pub fn op_ws_create(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CreateArgs = serde_json::from_value(args)?;
+  let url = url::Url::parse(&args.url).map_err(|_| {
+    OpError::type_error(format!("Invalid WebSocket URL \"{}\"", args.url))
+  })?;
+  let tls = match url.scheme() {
+    "ws" => false,
+    "wss" => true,
+    scheme => {
+      return Err(OpError::type_error(format!(
+        "scheme '{}' not supported, expected 'ws' or 'wss'",
+        scheme
+      )))
+    }
+  };
+  state.check_net_url(&url)?;
+
+  let config = if tls {
+    let cert_file = state.borrow().global_state.flags.ca_file.clone();
+    Some(create_client_config(ClientTlsOptions {
+      cert_file,
+      ..Default::default()
+    })?)
+  } else {
+    None
+  };
+  let mut request = url.as_str().into_client_request().map_err(ws_error)?;
+  if !args.protocols.is_empty() {
+    let protocols = HeaderValue::from_str(&args.protocols.join(", "))
+      .map_err(|e| OpError::type_error(e.to_string()))?;
+    request
+      .headers_mut()
+      .insert("sec-websocket-protocol", protocols);
+  }
+
+  let resource_table = isolate.resource_table.clone();
+  let future = async move {
+    let hostname = url.host_str().unwrap_or("").to_string();
+    let port = url.port_or_known_default().unwrap();
+    let addr = resolve_addr(&hostname, port)?;
+    let tcp_stream = TcpStream::connect(&addr).await?;
+    let conn = if let Some(config) = config {
+      let tls_connector = TlsConnector::from(Arc::new(config));
+      let dnsname =
+        DNSNameRef::try_from_ascii_str(&hostname).map_err(|_| {
+          OpError::other(format!("Invalid DNS name {}", hostname))
+        })?;
+      let tls_stream = tls_connector.connect(dnsname, tcp_stream).await?;
+      WsConn::Tls(Box::new(tls_stream))
+    } else {
+      WsConn::Tcp(tcp_stream)
+    };
+
+    let (stream, response) = tokio_tungstenite::client_async(request, conn)
+      .await
+      .map_err(ws_error)?;
+    let protocol = response
+      .headers()
+      .get("sec-websocket-protocol")
+      .and_then(|v| v.to_str().ok())
+      .unwrap_or("")
+      .to_string();
+    let extensions = response
+      .headers()
+      .get_all("sec-websocket-extensions")
+      .iter()
+      .filter_map(|v| v.to_str().ok())
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    let (sink, source) = stream.split();
+    let rid = resource_table.borrow_mut().add(
+      "webSocketStream",
+      Box::new(WsResource {
+        sink: Rc::new(Mutex::new(sink)),
+        source: Rc::new(Mutex::new(source)),
+        binary: None,
+      }),
+    );
+    Ok(json!({
+      "rid": rid,
+      "protocol": protocol,
+      "extensions": extensions,
+    }))
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+fn get_sink(
+  isolate: &mut CoreIsolate,
+  rid: u32,
+) -> Result<Rc<Mutex<WsSink>>, OpError> {
+  let resource_table = isolate.resource_table.borrow();
+  let resource = resource_table
+    .get::<WsResource>(rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  Ok(resource.sink.clone())
+}
+
+#[derive(Deserialize)]
+struct SendArgs {
+  rid: u32,
+  text: Option<String>,
+}
+
+/// Sends a text message if `text` is given, and a binary message from `data`
+/// otherwise.
+pub fn op_ws_send(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: SendArgs = serde_json::from_value(args)?;
+  let message = match args.text {
+    Some(text) => Message::Text(text),
+    None => Message::Binary(data.map(|buf| buf.to_vec()).unwrap_or_default()),
+  };
+  let sink = get_sink(isolate, args.rid)?;
+
+  let future = async move {
+    sink.lock().await.send(message).await.map_err(ws_error)?;
+    Ok(json!({}))
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+#[derive(Deserialize)]
+struct CloseArgs {
+  rid: u32,
+  code: Option<u16>,
+  reason: Option<String>,
+}
+
+/// Starts the closing handshake. The connection is closed once the server
+/// has replied, which `op_ws_next_event` reports.
+pub fn op_ws_close(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let CloseArgs { rid, code, reason } = serde_json::from_value(args)?;
+  let frame = code.map(|code| CloseFrame {
+    code: CloseCode::from(code),
+    reason: Cow::Owned(reason.unwrap_or_default()),
+  });
+  let sink = get_sink(isolate, rid)?;
+
+  let future = async move {
+    sink
+      .lock()
+      .await
+      .send(Message::Close(frame))
+      .await
+      .map_err(ws_error)?;
+    Ok(json!({}))
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+#[derive(Deserialize)]
+struct NextEventArgs {
+  rid: u32,
+}
+
+/// Resolves with the next message of a WebSocket. Only the length of binary
+/// messages is reported, their data is read with `op_ws_read_binary`. Pings
+/// are answered automatically and not reported.
+pub fn op_ws_next_event(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: NextEventArgs = serde_json::from_value(args)?;
+  let rid = args.rid;
+  let resource_table = isolate.resource_table.clone();
+  let source = {
+    let resource_table = isolate.resource_table.borrow();
+    let resource = resource_table
+      .get::<WsResource>(args.rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    resource.source.clone()
+  };
+
+  let future = async move {
+    let mut source = source.lock().await;
+    let event = loop {
+      break match source.next().await {
+        Some(Ok(Message::Text(text))) => json!({
+          "kind": "string",
+          "data": text,
+        }),
+        Some(Ok(Message::Binary(data))) => {
+          let mut resource_table = resource_table.borrow_mut();
+          let resource = resource_table
+            .get_mut::<WsResource>(rid)
+            .ok_or_else(OpError::bad_resource_id)?;
+          let byte_length = data.len();
+          resource.binary = Some(data);
+          json!({
+            "kind": "binary",
+            "byteLength": byte_length,
+          })
+        }
+        Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => continue,
+        Some(Ok(Message::Close(Some(frame)))) => {
+          let code: u16 = frame.code.into();
+          json!({
+            "kind": "close",
+            "code": code,
+            "reason": frame.reason.to_string(),
+          })
+        }
+        Some(Ok(Message::Close(None))) => json!({
+          "kind": "close",
+          "code": 1005,
+          "reason": "",
+        }),
+        Some(Err(WsError::ConnectionClosed)) | None => json!({
+          "kind": "closed",
+        }),
+        Some(Err(e)) => json!({
+          "kind": "error",
+          "message": e.to_string(),
+        }),
+      };
+    };
+    Ok(event)
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+/// Copies the binary message reported by `op_ws_next_event` into `data`,
+/// which must have exactly its length.
+pub fn op_ws_read_binary(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: NextEventArgs = serde_json::from_value(args)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let resource = resource_table
+    .get_mut::<WsResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  let mut data = data.ok_or_else(|| {
+    OpError::type_error("A buffer for the message is required".to_string())
+  })?;
+  match resource.binary.take() {
+    Some(binary) if binary.len() == data.len() => {
+      data.copy_from_slice(&binary);
+      Ok(JsonOp::Sync(json!({})))
+    }
+    Some(binary) => {
+      let message = "Buffer length doesn't match the message length";
+      resource.binary = Some(binary);
+      Err(OpError::type_error(message.to_string()))
+    }
+    None => Err(OpError::other("No binary message to read".to_string())),
+  }
+}
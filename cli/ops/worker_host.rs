@@ -5,18 +5,30 @@ use crate::global_state::GlobalState;
 use crate::op_error::OpError;
 use crate::ops::io::get_stdio;
 use crate::permissions::Permissions;
+use crate::permissions::WorkerPermission;
+use crate::permissions::WorkerPermissions;
 use crate::startup_data;
 use crate::state::State;
 use crate::tokio_util::create_basic_runtime;
 use crate::web_worker::WebWorker;
 use crate::web_worker::WebWorkerHandle;
 use crate::worker::WorkerEvent;
+use crate::worker::WorkerMessage;
 use deno_core::CoreIsolate;
 use deno_core::ErrBox;
 use deno_core::ModuleSpecifier;
+use deno_core::ResourceTable;
 use deno_core::ZeroCopyBuf;
+use futures::future::poll_fn;
 use futures::future::FutureExt;
+use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::convert::From;
+use std::mem;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
 use std::thread::JoinHandle;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
@@ -27,11 +39,35 @@ pub fn init(i: &mut CoreIsolate, s: &State) {
   );
   i.register_op(
     "op_host_post_message",
-    s.stateful_json_op(op_host_post_message),
+    s.stateful_json_op2(op_host_post_message),
   );
   i.register_op(
     "op_host_get_message",
-    s.stateful_json_op(op_host_get_message),
+    s.stateful_json_op2(op_host_get_message),
+  );
+  i.register_op(
+    "op_create_message_channel",
+    s.stateful_json_op2(op_create_message_channel),
+  );
+  i.register_op(
+    "op_message_port_post_message",
+    s.stateful_json_op2(op_message_port_post_message),
+  );
+  i.register_op(
+    "op_message_port_recv_message",
+    s.stateful_json_op2(op_message_port_recv_message),
+  );
+  i.register_op(
+    "op_broadcast_subscribe",
+    s.stateful_json_op2(op_broadcast_subscribe),
+  );
+  i.register_op(
+    "op_broadcast_post_message",
+    s.stateful_json_op2(op_broadcast_post_message),
+  );
+  i.register_op(
+    "op_broadcast_recv_message",
+    s.stateful_json_op2(op_broadcast_recv_message),
   );
 }
 
@@ -158,6 +194,57 @@ fn run_worker_thread(
   Ok((join_handle, worker_handle))
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PermissionArg {
+  Bool(bool),
+  List(Vec<String>),
+  Inherit(String),
+}
+
+impl PermissionArg {
+  fn parse(arg: Option<Self>) -> Result<WorkerPermission, OpError> {
+    match arg {
+      None => Ok(WorkerPermission::Inherit),
+      Some(PermissionArg::Bool(true)) => Ok(WorkerPermission::Allow),
+      Some(PermissionArg::Bool(false)) => Ok(WorkerPermission::Deny),
+      Some(PermissionArg::List(list)) => Ok(WorkerPermission::Only(list)),
+      Some(PermissionArg::Inherit(s)) if s == "inherit" => {
+        Ok(WorkerPermission::Inherit)
+      }
+      Some(PermissionArg::Inherit(s)) => Err(OpError::type_error(format!(
+        "Invalid worker permission: {}",
+        s
+      ))),
+    }
+  }
+}
+
+#[derive(Deserialize)]
+struct WorkerPermissionsArgs {
+  read: Option<PermissionArg>,
+  write: Option<PermissionArg>,
+  net: Option<PermissionArg>,
+  env: Option<PermissionArg>,
+  run: Option<PermissionArg>,
+  plugin: Option<PermissionArg>,
+  hrtime: Option<PermissionArg>,
+}
+
+impl WorkerPermissionsArgs {
+  fn parse(self) -> Result<WorkerPermissions, OpError> {
+    Ok(WorkerPermissions {
+      read: PermissionArg::parse(self.read)?,
+      write: PermissionArg::parse(self.write)?,
+      net: PermissionArg::parse(self.net)?,
+      env: PermissionArg::parse(self.env)?,
+      run: PermissionArg::parse(self.run)?,
+      plugin: PermissionArg::parse(self.plugin)?,
+      hrtime: PermissionArg::parse(self.hrtime)?,
+    })
+  }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct CreateWorkerArgs {
@@ -166,6 +253,7 @@ struct CreateWorkerArgs {
   has_source_code: bool,
   source_code: String,
   use_deno_namespace: bool,
+  permissions: Option<WorkerPermissionsArgs>,
 }
 
 /// Create worker as the host
@@ -190,7 +278,11 @@ fn op_create_worker(
   let parent_state = state.clone();
   let mut state = state.borrow_mut();
   let global_state = state.global_state.clone();
-  let permissions = state.permissions.clone();
+  // Without explicit permissions the worker gets the same as its parent.
+  let permissions = match args.permissions {
+    Some(requested) => state.permissions.for_worker(&requested.parse()?)?,
+    None => state.permissions.clone(),
+  };
   let referrer = state.main_module.to_string();
   let worker_id = state.next_worker_id;
   state.next_worker_id += 1;
@@ -240,9 +332,15 @@ fn op_host_terminate_worker(
   Ok(JsonOp::Sync(json!({})))
 }
 
-fn serialize_worker_event(event: WorkerEvent) -> Value {
+fn serialize_worker_event(
+  event: WorkerEvent,
+  resource_table: &mut ResourceTable,
+) -> Value {
   match event {
-    WorkerEvent::Message(buf) => json!({ "type": "msg", "data": buf }),
+    WorkerEvent::Message(msg) => {
+      let ports = attach_message_ports(resource_table, msg.ports);
+      json!({ "type": "msg", "data": msg.data, "ports": ports })
+    }
     WorkerEvent::TerminalError(error) => {
       let mut serialized_error = json!({
         "type": "terminalError",
@@ -292,6 +390,7 @@ fn serialize_worker_event(event: WorkerEvent) -> Value {
 
 /// Get message from guest worker as host
 fn op_host_get_message(
+  isolate: &mut CoreIsolate,
   state: &State,
   args: Value,
   _data: Option<ZeroCopyBuf>,
@@ -305,6 +404,7 @@ fn op_host_get_message(
     worker_handle.clone()
   };
   let state_ = state.clone();
+  let resource_table = isolate.resource_table.clone();
   let op = async move {
     let response = match worker_handle.get_event().await {
       Some(event) => {
@@ -318,7 +418,7 @@ fn op_host_get_message(
             join_handle.join().expect("Worker thread panicked");
           }
         }
-        serialize_worker_event(event)
+        serialize_worker_event(event, &mut resource_table.borrow_mut())
       }
       None => {
         // Worker shuts down
@@ -339,15 +439,29 @@ fn op_host_get_message(
   Ok(JsonOp::Async(op.boxed_local()))
 }
 
+#[derive(Deserialize)]
+struct HostPostMessageArgs {
+  id: i32,
+  transfer: Vec<u32>,
+}
+
 /// Post message to guest worker as host
 fn op_host_post_message(
+  isolate: &mut CoreIsolate,
   state: &State,
   args: Value,
   data: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  let args: WorkerArgs = serde_json::from_value(args)?;
+  let args: HostPostMessageArgs = serde_json::from_value(args)?;
   let id = args.id as u32;
-  let msg = Vec::from(data.unwrap().as_ref()).into_boxed_slice();
+  let ports = detach_message_ports(
+    &mut isolate.resource_table.borrow_mut(),
+    &args.transfer,
+  )?;
+  let msg = WorkerMessage {
+    data: Vec::from(data.unwrap().as_ref()).into_boxed_slice(),
+    ports,
+  };
 
   debug!("post message to worker {}", id);
   let state = state.borrow();
@@ -358,3 +472,438 @@ fn op_host_post_message(
     .map_err(|e| OpError::other(e.to_string()))?;
   Ok(JsonOp::Sync(json!({})))
 }
+
+type Buf = Box<[u8]>;
+
+/// Messages waiting to be received by a message port or broadcast channel,
+/// together with the waker of the pending receive op.
+struct Mailbox<T> {
+  queue: VecDeque<T>,
+  waker: Option<Waker>,
+}
+
+impl<T> Default for Mailbox<T> {
+  fn default() -> Self {
+    Self {
+      queue: VecDeque::new(),
+      waker: None,
+    }
+  }
+}
+
+impl<T> Mailbox<T> {
+  fn push(&mut self, msg: T) {
+    self.queue.push_back(msg);
+    self.wake();
+  }
+
+  fn wake(&mut self) {
+    if let Some(waker) = self.waker.take() {
+      waker.wake();
+    }
+  }
+
+  fn poll_next(&mut self, cx: &mut Context) -> Poll<T> {
+    match self.queue.pop_front() {
+      Some(msg) => Poll::Ready(msg),
+      None => {
+        self.waker = Some(cx.waker().clone());
+        Poll::Pending
+      }
+    }
+  }
+}
+
+/// Message ports sent along with a message. Ports that are never received,
+/// because the message is dropped, are closed so their peers see the
+/// channel end.
+#[derive(Default)]
+pub struct TransferredPorts(Vec<u32>);
+
+impl TransferredPorts {
+  fn take(&mut self) -> Vec<u32> {
+    mem::take(&mut self.0)
+  }
+}
+
+impl Drop for TransferredPorts {
+  fn drop(&mut self) {
+    // Ports are taken out of messages before they are dropped while the
+    // table is locked, so this never locks it twice.
+    if !self.0.is_empty() {
+      let ids = self.take();
+      let mut table = MESSAGE_PORTS.lock().unwrap();
+      for id in ids {
+        table.close(id);
+      }
+    }
+  }
+}
+
+struct MessagePortEntry {
+  peer: Option<u32>,
+  mailbox: Mailbox<WorkerMessage>,
+}
+
+#[derive(Default)]
+struct MessagePortTable {
+  next_id: u32,
+  ports: HashMap<u32, MessagePortEntry>,
+}
+
+impl MessagePortTable {
+  fn insert(&mut self, entry: MessagePortEntry) -> u32 {
+    let id = self.next_id;
+    self.next_id += 1;
+    self.ports.insert(id, entry);
+    id
+  }
+
+  /// Moves the port to a new id, which ends any pending receive for the old
+  /// one.
+  fn detach(&mut self, id: u32) -> Option<u32> {
+    let mut port = self.ports.remove(&id)?;
+    port.mailbox.wake();
+    let peer = port.peer;
+    let new_id = self.insert(port);
+    if let Some(peer) = peer.and_then(|peer| self.ports.get_mut(&peer)) {
+      peer.peer = Some(new_id);
+    }
+    Some(new_id)
+  }
+
+  /// Closes the port along with the ports sent in messages it didn't
+  /// receive yet.
+  fn close(&mut self, id: u32) {
+    let mut ids = vec![id];
+    while let Some(id) = ids.pop() {
+      if let Some(mut port) = self.ports.remove(&id) {
+        port.mailbox.wake();
+        for msg in port.mailbox.queue.iter_mut() {
+          ids.extend(msg.ports.take());
+        }
+        if let Some(peer) = port.peer.and_then(|id| self.ports.get_mut(&id)) {
+          peer.peer = None;
+          peer.mailbox.wake();
+        }
+      }
+    }
+  }
+
+  /// Queues the message for the peer of the port. The message is dropped
+  /// and the ports sent with it are closed if there is no peer, or if the
+  /// peer itself is sent with the message.
+  fn post(&mut self, id: u32, mut msg: WorkerMessage) {
+    let peer = self.ports.get(&id).and_then(|port| port.peer);
+    if let Some(peer) = peer.filter(|peer| !msg.ports.0.contains(peer)) {
+      self.ports.get_mut(&peer).unwrap().mailbox.push(msg);
+      return;
+    }
+    for id in msg.ports.take() {
+      self.close(id);
+    }
+  }
+}
+
+struct BroadcastSubscriber {
+  name: String,
+  mailbox: Mailbox<Buf>,
+}
+
+#[derive(Default)]
+struct BroadcastChannelTable {
+  next_id: u32,
+  subscribers: HashMap<u32, BroadcastSubscriber>,
+}
+
+lazy_static! {
+  /// Message ports and broadcast channels are shared by all workers in the
+  /// process, so they are tracked here under process wide ids. Each isolate
+  /// refers to them through a resource that releases the entry on drop.
+  static ref MESSAGE_PORTS: Mutex<MessagePortTable> =
+    Mutex::new(MessagePortTable::default());
+  static ref BROADCAST_CHANNELS: Mutex<BroadcastChannelTable> =
+    Mutex::new(BroadcastChannelTable::default());
+}
+
+struct MessagePortResource {
+  /// `None` once the port has been sent to another worker.
+  id: Option<u32>,
+}
+
+impl Drop for MessagePortResource {
+  fn drop(&mut self) {
+    if let Some(id) = self.id {
+      MESSAGE_PORTS.lock().unwrap().close(id);
+    }
+  }
+}
+
+struct BroadcastChannelResource {
+  id: u32,
+}
+
+impl Drop for BroadcastChannelResource {
+  fn drop(&mut self) {
+    let mut table = BROADCAST_CHANNELS.lock().unwrap();
+    if let Some(mut subscriber) = table.subscribers.remove(&self.id) {
+      subscriber.mailbox.wake();
+    }
+  }
+}
+
+/// Removes the message ports with the given resource ids from the isolate, so
+/// they can be sent along with a message. Nothing is removed if any of the
+/// ids isn't an open message port.
+pub fn detach_message_ports(
+  resource_table: &mut ResourceTable,
+  rids: &[u32],
+) -> Result<TransferredPorts, OpError> {
+  for (i, rid) in rids.iter().enumerate() {
+    let is_port = resource_table
+      .get::<MessagePortResource>(*rid)
+      .map_or(false, |port| port.id.is_some());
+    if !is_port || rids[..i].contains(rid) {
+      return Err(OpError::bad_resource_id());
+    }
+  }
+
+  let mut ports = TransferredPorts::default();
+  for rid in rids {
+    let id = resource_table
+      .remove::<MessagePortResource>(*rid)
+      .unwrap()
+      .id
+      .take()
+      .unwrap();
+    if let Some(id) = MESSAGE_PORTS.lock().unwrap().detach(id) {
+      ports.0.push(id);
+    }
+  }
+  Ok(ports)
+}
+
+/// Adds the message ports received with a message to the isolate, returning
+/// their resource ids.
+pub fn attach_message_ports(
+  resource_table: &mut ResourceTable,
+  mut ports: TransferredPorts,
+) -> Vec<u32> {
+  ports
+    .take()
+    .into_iter()
+    .map(|id| {
+      resource_table.add(
+        "messagePort",
+        Box::new(MessagePortResource { id: Some(id) }),
+      )
+    })
+    .collect()
+}
+
+#[derive(Deserialize)]
+struct RidArgs {
+  rid: i32,
+}
+
+fn message_port_id(isolate: &CoreIsolate, rid: i32) -> Result<u32, OpError> {
+  let resource_table = isolate.resource_table.borrow();
+  resource_table
+    .get::<MessagePortResource>(rid as u32)
+    .and_then(|port| port.id)
+    .ok_or_else(OpError::bad_resource_id)
+}
+
+fn broadcast_channel_id(
+  isolate: &CoreIsolate,
+  rid: i32,
+) -> Result<u32, OpError> {
+  let resource_table = isolate.resource_table.borrow();
+  resource_table
+    .get::<BroadcastChannelResource>(rid as u32)
+    .map(|channel| channel.id)
+    .ok_or_else(OpError::bad_resource_id)
+}
+
+/// Create a pair of entangled message ports
+fn op_create_message_channel(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  _args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let (port1, port2) = {
+    let mut table = MESSAGE_PORTS.lock().unwrap();
+    let port1 = table.insert(MessagePortEntry {
+      peer: None,
+      mailbox: Mailbox::default(),
+    });
+    let port2 = table.insert(MessagePortEntry {
+      peer: Some(port1),
+      mailbox: Mailbox::default(),
+    });
+    table.ports.get_mut(&port1).unwrap().peer = Some(port2);
+    (port1, port2)
+  };
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid1 = resource_table.add(
+    "messagePort",
+    Box::new(MessagePortResource { id: Some(port1) }),
+  );
+  let rid2 = resource_table.add(
+    "messagePort",
+    Box::new(MessagePortResource { id: Some(port2) }),
+  );
+  Ok(JsonOp::Sync(json!({ "port1": rid1, "port2": rid2 })))
+}
+
+#[derive(Deserialize)]
+struct MessagePortPostMessageArgs {
+  rid: i32,
+  transfer: Vec<u32>,
+}
+
+/// Post message to the port entangled with the given one, moving the ports
+/// in `transfer` along with it. Messages sent after the other port was
+/// closed are silently dropped.
+fn op_message_port_post_message(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: MessagePortPostMessageArgs = serde_json::from_value(args)?;
+  let id = message_port_id(isolate, args.rid)?;
+  if args.transfer.contains(&(args.rid as u32)) {
+    return Err(OpError::type_error(
+      "Port can't be transferred through itself".to_string(),
+    ));
+  }
+  let ports = detach_message_ports(
+    &mut isolate.resource_table.borrow_mut(),
+    &args.transfer,
+  )?;
+  let msg = WorkerMessage {
+    data: Vec::from(data.unwrap().as_ref()).into_boxed_slice(),
+    ports,
+  };
+  MESSAGE_PORTS.lock().unwrap().post(id, msg);
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// Get next message sent to the port, resolves to `null` once the port or
+/// its peer is closed or the port is transferred.
+fn op_message_port_recv_message(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RidArgs = serde_json::from_value(args)?;
+  let id = message_port_id(isolate, args.rid)?;
+  let resource_table = isolate.resource_table.clone();
+
+  let op = poll_fn(move |cx| -> Poll<Option<WorkerMessage>> {
+    let mut table = MESSAGE_PORTS.lock().unwrap();
+    let port = match table.ports.get_mut(&id) {
+      Some(port) => port,
+      None => return Poll::Ready(None),
+    };
+    match port.mailbox.poll_next(cx) {
+      Poll::Ready(msg) => Poll::Ready(Some(msg)),
+      Poll::Pending if port.peer.is_none() => Poll::Ready(None),
+      Poll::Pending => Poll::Pending,
+    }
+  })
+  .map(move |maybe_msg| match maybe_msg {
+    Some(msg) => {
+      let mut resource_table = resource_table.borrow_mut();
+      let ports = attach_message_ports(&mut resource_table, msg.ports);
+      Ok(json!({ "data": msg.data, "ports": ports }))
+    }
+    None => Ok(json!(null)),
+  });
+  Ok(JsonOp::Async(op.boxed_local()))
+}
+
+#[derive(Deserialize)]
+struct BroadcastSubscribeArgs {
+  name: String,
+}
+
+/// Subscribe to broadcast channel with given name
+fn op_broadcast_subscribe(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: BroadcastSubscribeArgs = serde_json::from_value(args)?;
+  let id = {
+    let mut table = BROADCAST_CHANNELS.lock().unwrap();
+    let id = table.next_id;
+    table.next_id += 1;
+    table.subscribers.insert(
+      id,
+      BroadcastSubscriber {
+        name: args.name,
+        mailbox: Mailbox::default(),
+      },
+    );
+    id
+  };
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table.add(
+    "broadcastChannel",
+    Box::new(BroadcastChannelResource { id }),
+  );
+  Ok(JsonOp::Sync(json!({ "rid": rid })))
+}
+
+/// Post message to every other subscriber of the channel, in any worker
+fn op_broadcast_post_message(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RidArgs = serde_json::from_value(args)?;
+  let id = broadcast_channel_id(isolate, args.rid)?;
+  let msg = Vec::from(data.unwrap().as_ref()).into_boxed_slice();
+
+  let mut table = BROADCAST_CHANNELS.lock().unwrap();
+  let name = match table.subscribers.get(&id) {
+    Some(subscriber) => subscriber.name.clone(),
+    None => return Err(OpError::bad_resource_id()),
+  };
+  for (subscriber_id, subscriber) in table.subscribers.iter_mut() {
+    if *subscriber_id != id && subscriber.name == name {
+      subscriber.mailbox.push(msg.clone());
+    }
+  }
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// Get next message broadcast on the channel, resolves to `null` once the
+/// channel is closed.
+fn op_broadcast_recv_message(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RidArgs = serde_json::from_value(args)?;
+  let id = broadcast_channel_id(isolate, args.rid)?;
+
+  let op = poll_fn(move |cx| -> Poll<Result<Value, OpError>> {
+    let mut table = BROADCAST_CHANNELS.lock().unwrap();
+    match table.subscribers.get_mut(&id) {
+      Some(subscriber) => subscriber
+        .mailbox
+        .poll_next(cx)
+        .map(|msg| Ok(json!({ "data": msg }))),
+      None => Poll::Ready(Ok(json!(null))),
+    }
+  });
+  Ok(JsonOp::Async(op.boxed_local()))
+}
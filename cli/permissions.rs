@@ -1,6 +1,7 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use crate::colors;
 use crate::flags::Flags;
+use crate::fs::resolve_from_cwd;
 use crate::op_error::OpError;
 use std::collections::HashSet;
 use std::fmt;
@@ -295,6 +296,177 @@ impl Permissions {
       n => Err(OpError::other(format!("No such permission name: {}", n))),
     }
   }
+
+  /// Derives the permissions of a worker from these permissions. Fails if
+  /// the worker would be granted anything that isn't granted here.
+  pub fn for_worker(
+    &self,
+    requested: &WorkerPermissions,
+  ) -> Result<Self, OpError> {
+    let (allow_read, read_whitelist) = scope_paths(
+      "read",
+      self.allow_read,
+      &self.read_whitelist,
+      &requested.read,
+      |path| self.get_state_read(&Some(path)),
+    )?;
+    let (allow_write, write_whitelist) = scope_paths(
+      "write",
+      self.allow_write,
+      &self.write_whitelist,
+      &requested.write,
+      |path| self.get_state_write(&Some(path)),
+    )?;
+    let (allow_net, net_whitelist) = scope_hosts(
+      self.allow_net,
+      &self.net_whitelist,
+      &requested.net,
+      |host, port| self.get_state_net(host, port),
+    )?;
+    Ok(Self {
+      allow_read,
+      read_whitelist,
+      allow_write,
+      write_whitelist,
+      allow_net,
+      net_whitelist,
+      allow_env: scope_state("env", self.allow_env, &requested.env)?,
+      allow_run: scope_state("run", self.allow_run, &requested.run)?,
+      allow_plugin: scope_state(
+        "plugin",
+        self.allow_plugin,
+        &requested.plugin,
+      )?,
+      allow_hrtime: scope_state(
+        "hrtime",
+        self.allow_hrtime,
+        &requested.hrtime,
+      )?,
+    })
+  }
+}
+
+/// A permission requested for a worker, relative to the same permission of
+/// the thread creating it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkerPermission {
+  Inherit,
+  Allow,
+  Deny,
+  /// Allow access to these paths or hosts only.
+  Only(Vec<String>),
+}
+
+impl Default for WorkerPermission {
+  fn default() -> Self {
+    WorkerPermission::Inherit
+  }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WorkerPermissions {
+  pub read: WorkerPermission,
+  pub write: WorkerPermission,
+  pub net: WorkerPermission,
+  pub env: WorkerPermission,
+  pub run: WorkerPermission,
+  pub plugin: WorkerPermission,
+  pub hrtime: WorkerPermission,
+}
+
+fn escalation_error(name: &str) -> OpError {
+  OpError::permission_denied(format!(
+    "Can't escalate parent thread permissions for \"{}\"",
+    name
+  ))
+}
+
+// A worker must not be able to prompt for access its parent doesn't have, so
+// whatever isn't granted is denied.
+fn scope_state(
+  name: &str,
+  parent: PermissionState,
+  requested: &WorkerPermission,
+) -> Result<PermissionState, OpError> {
+  match requested {
+    WorkerPermission::Inherit if parent == PermissionState::Ask => {
+      Ok(PermissionState::Deny)
+    }
+    WorkerPermission::Inherit => Ok(parent),
+    WorkerPermission::Allow if parent.is_allow() => Ok(PermissionState::Allow),
+    WorkerPermission::Allow => Err(escalation_error(name)),
+    WorkerPermission::Deny => Ok(PermissionState::Deny),
+    WorkerPermission::Only(_) => Err(OpError::type_error(format!(
+      "\"{}\" permission can't be limited to a list",
+      name
+    ))),
+  }
+}
+
+fn scope_paths(
+  name: &str,
+  parent: PermissionState,
+  parent_whitelist: &HashSet<PathBuf>,
+  requested: &WorkerPermission,
+  get_state: impl Fn(&Path) -> PermissionState,
+) -> Result<(PermissionState, HashSet<PathBuf>), OpError> {
+  match requested {
+    WorkerPermission::Inherit => Ok((
+      scope_state(name, parent, requested)?,
+      parent_whitelist.clone(),
+    )),
+    WorkerPermission::Only(paths) => {
+      let mut whitelist = HashSet::new();
+      for path in paths {
+        let path = resolve_from_cwd(Path::new(path))?;
+        if !get_state(&path).is_allow() {
+          return Err(escalation_error(name));
+        }
+        whitelist.insert(path);
+      }
+      Ok((PermissionState::Deny, whitelist))
+    }
+    _ => Ok((scope_state(name, parent, requested)?, HashSet::new())),
+  }
+}
+
+fn scope_hosts(
+  parent: PermissionState,
+  parent_whitelist: &HashSet<String>,
+  requested: &WorkerPermission,
+  get_state: impl Fn(&str, Option<u16>) -> PermissionState,
+) -> Result<(PermissionState, HashSet<String>), OpError> {
+  match requested {
+    WorkerPermission::Inherit => Ok((
+      scope_state("net", parent, requested)?,
+      parent_whitelist.clone(),
+    )),
+    WorkerPermission::Only(hosts) => {
+      let mut whitelist = HashSet::new();
+      for host in hosts {
+        let (hostname, port) = split_host_port(host)?;
+        if !get_state(hostname, port).is_allow() {
+          return Err(escalation_error("net"));
+        }
+        whitelist.insert(host.clone());
+      }
+      Ok((PermissionState::Deny, whitelist))
+    }
+    _ => Ok((scope_state("net", parent, requested)?, HashSet::new())),
+  }
+}
+
+/// Splits a net whitelist entry like "deno.land" or "127.0.0.1:4545".
+fn split_host_port(host: &str) -> Result<(&str, Option<u16>), OpError> {
+  match host.rfind(':') {
+    Some(i) => {
+      let port = host[i + 1..].parse::<u16>().map_err(|_| {
+        OpError::type_error(format!("Invalid net permission: {}", host))
+      })?;
+      Ok((&host[..i], Some(port)))
+    }
+    None => Ok((host, None)),
+  }
 }
 
 /// Shows the permission prompt and returns the answer according to the user input.
@@ -722,4 +894,70 @@ mod tests {
     assert_eq!(perms1.request_hrtime(), PermissionState::Deny);
     drop(guard);
   }
+
+  #[test]
+  // Rooted paths without a drive letter aren't absolute on Windows.
+  #[cfg(not(windows))]
+  fn test_permissions_for_worker() {
+    let perms = Permissions::from_flags(&Flags {
+      read_whitelist: vec![PathBuf::from("/a/specific")],
+      net_whitelist: svec!["localhost", "127.0.0.1:4545"],
+      allow_env: true,
+      ..Default::default()
+    });
+
+    // Everything is inherited by default.
+    let worker_perms = perms.for_worker(&WorkerPermissions::default()).unwrap();
+    assert!(worker_perms
+      .check_read(Path::new("/a/specific/dir"))
+      .is_ok());
+    assert!(worker_perms.check_env().is_ok());
+
+    let worker_perms = perms
+      .for_worker(&WorkerPermissions {
+        read: WorkerPermission::Only(svec!["/a/specific/dir"]),
+        net: WorkerPermission::Only(svec!["localhost:8080"]),
+        env: WorkerPermission::Deny,
+        ..Default::default()
+      })
+      .unwrap();
+    assert!(worker_perms
+      .check_read(Path::new("/a/specific/dir/x"))
+      .is_ok());
+    assert!(worker_perms.check_read(Path::new("/a/specific")).is_err());
+    assert!(worker_perms.check_net("localhost", 8080).is_ok());
+    assert!(worker_perms.check_net("localhost", 8081).is_err());
+    assert!(worker_perms.check_env().is_err());
+    assert_eq!(worker_perms.allow_read, PermissionState::Deny);
+    assert_eq!(worker_perms.allow_env, PermissionState::Deny);
+
+    // Nothing can be granted that the parent doesn't have.
+    for requested in vec![
+      WorkerPermissions {
+        read: WorkerPermission::Allow,
+        ..Default::default()
+      },
+      WorkerPermissions {
+        read: WorkerPermission::Only(svec!["/a"]),
+        ..Default::default()
+      },
+      WorkerPermissions {
+        net: WorkerPermission::Only(svec!["127.0.0.1"]),
+        ..Default::default()
+      },
+      WorkerPermissions {
+        run: WorkerPermission::Allow,
+        ..Default::default()
+      },
+    ] {
+      assert!(perms.for_worker(&requested).is_err());
+    }
+
+    assert!(perms
+      .for_worker(&WorkerPermissions {
+        env: WorkerPermission::Only(svec!["FOO"]),
+        ..Default::default()
+      })
+      .is_err());
+  }
 }
@@ -0,0 +1,206 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Rewrite table used by `SourceFileFetcher` to download remote modules from
+//! a mirror, e.g. `https://deno.land/std@0.50.0/` from an internal host.
+//!
+//! Only the network request is redirected. Modules keep their original URL,
+//! so cache entries, lockfile entries and `import.meta.url` are the same
+//! whether or not a mirror is used.
+
+use crate::op_error::OpError;
+use deno_core::ErrBox;
+use serde_json::Value;
+use std::fs;
+use url::Url;
+
+#[derive(Clone, Debug, Default)]
+pub struct RegistryMirrors(Vec<(String, String)>);
+
+impl RegistryMirrors {
+  /// Builds the table from `--registry-mirror` flags and an optional JSON
+  /// config file. Flags take precedence over entries of the config file.
+  pub fn new(
+    mirrors: &[(String, String)],
+    maybe_config_path: Option<&str>,
+  ) -> Result<Self, ErrBox> {
+    let mut entries = Vec::new();
+    if let Some(config_path) = maybe_config_path {
+      let json = fs::read_to_string(config_path).map_err(|err| {
+        OpError::other(format!(
+          "Error retrieving registry mirror config at \"{}\": {}",
+          config_path, err
+        ))
+      })?;
+      entries.extend(Self::parse_config(&json)?);
+    }
+    // On equal prefixes the last entry wins, see `replace_prefix`.
+    entries.extend_from_slice(mirrors);
+
+    for (from, to) in &entries {
+      validate_prefix(from)?;
+      validate_prefix(to)?;
+    }
+
+    Ok(Self(entries))
+  }
+
+  /// Parses a config file of the form
+  /// `{ "https://deno.land/std@0.50.0/": "https://mirror.internal/std/" }`.
+  pub fn parse_config(json: &str) -> Result<Vec<(String, String)>, ErrBox> {
+    let value: Value = serde_json::from_str(json)?;
+    let map = match value.as_object() {
+      Some(map) => map,
+      None => {
+        return Err(
+          OpError::other(
+            "Registry mirror config must be a JSON object".to_string(),
+          )
+          .into(),
+        )
+      }
+    };
+
+    let mut entries = Vec::new();
+    for (from, to) in map {
+      match to.as_str() {
+        Some(to) => entries.push((from.to_string(), to.to_string())),
+        None => {
+          return Err(
+            OpError::other(format!(
+              "Registry mirror for \"{}\" must be a string",
+              from
+            ))
+            .into(),
+          )
+        }
+      }
+    }
+    Ok(entries)
+  }
+
+  /// Returns the URL that should be requested instead of `url`.
+  pub fn rewrite(&self, url: &Url) -> Url {
+    self.replace_prefix(url, |(from, to)| (from, to))
+  }
+
+  /// Maps a URL on a mirror back to its original location. Used for
+  /// redirects returned by a mirror.
+  pub fn restore(&self, url: &Url) -> Url {
+    self.replace_prefix(url, |(from, to)| (to, from))
+  }
+
+  /// Replaces the longest matching prefix, as selected by `select` which
+  /// returns a `(prefix, replacement)` pair for each entry. If several
+  /// entries have the same prefix, the last one is used.
+  fn replace_prefix<'a, F>(&'a self, url: &Url, select: F) -> Url
+  where
+    F: Fn((&'a String, &'a String)) -> (&'a String, &'a String),
+  {
+    let url_str = url.as_str();
+    let maybe_entry = self
+      .0
+      .iter()
+      .map(|(from, to)| select((from, to)))
+      .filter(|(prefix, _)| url_str.starts_with(prefix.as_str()))
+      .max_by_key(|(prefix, _)| prefix.len());
+
+    match maybe_entry {
+      Some((prefix, replacement)) => {
+        let rewritten = format!("{}{}", replacement, &url_str[prefix.len()..]);
+        Url::parse(&rewritten).unwrap_or_else(|_| url.clone())
+      }
+      None => url.clone(),
+    }
+  }
+}
+
+fn validate_prefix(prefix: &str) -> Result<(), ErrBox> {
+  match Url::parse(prefix) {
+    Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(()),
+    _ => Err(
+      OpError::other(format!(
+        "Invalid registry mirror URL \"{}\", expected an http or https URL",
+        prefix
+      ))
+      .into(),
+    ),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn mirrors() -> RegistryMirrors {
+    RegistryMirrors::new(
+      &[
+        (
+          "https://deno.land/".to_string(),
+          "https://mirror.internal/deno/".to_string(),
+        ),
+        (
+          "https://deno.land/std@0.50.0/".to_string(),
+          "https://std.internal/0.50.0/".to_string(),
+        ),
+      ],
+      None,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_rewrite() {
+    let mirrors = mirrors();
+    let url = Url::parse("https://deno.land/x/oak/mod.ts").unwrap();
+    assert_eq!(
+      mirrors.rewrite(&url).as_str(),
+      "https://mirror.internal/deno/x/oak/mod.ts"
+    );
+    // The longest prefix wins.
+    let url = Url::parse("https://deno.land/std@0.50.0/fs/mod.ts").unwrap();
+    assert_eq!(
+      mirrors.rewrite(&url).as_str(),
+      "https://std.internal/0.50.0/fs/mod.ts"
+    );
+    let url = Url::parse("https://example.com/mod.ts").unwrap();
+    assert_eq!(mirrors.rewrite(&url), url);
+  }
+
+  #[test]
+  fn test_restore() {
+    let mirrors = mirrors();
+    let url = Url::parse("https://std.internal/0.50.0/fs/mod.ts").unwrap();
+    assert_eq!(
+      mirrors.restore(&url).as_str(),
+      "https://deno.land/std@0.50.0/fs/mod.ts"
+    );
+    let url = Url::parse("https://example.com/mod.ts").unwrap();
+    assert_eq!(mirrors.restore(&url), url);
+  }
+
+  #[test]
+  fn test_parse_config() {
+    let entries = RegistryMirrors::parse_config(
+      r#"{ "https://deno.land/": "https://mirror.internal/" }"#,
+    )
+    .unwrap();
+    assert_eq!(
+      entries,
+      vec![(
+        "https://deno.land/".to_string(),
+        "https://mirror.internal/".to_string()
+      )]
+    );
+    assert!(RegistryMirrors::parse_config("[]").is_err());
+    assert!(RegistryMirrors::parse_config(r#"{ "a": 1 }"#).is_err());
+  }
+
+  #[test]
+  fn test_invalid_prefix() {
+    let result = RegistryMirrors::new(
+      &[("https://deno.land/".to_string(), "mirror".to_string())],
+      None,
+    );
+    assert!(result.is_err());
+  }
+}
@@ -1,9 +1,12 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::cpu_profiler::CpuProfiler;
 use crate::file_fetcher::SourceFileFetcher;
 use crate::global_state::GlobalState;
 use crate::global_timer::GlobalTimer;
+use crate::heap_snapshot::HeapSnapshotRequest;
 use crate::import_map::ImportMap;
 use crate::metrics::Metrics;
+use crate::module_graph::ModuleGraphLoader;
 use crate::op_error::OpError;
 use crate::ops::JsonOp;
 use crate::ops::MinimalOp;
@@ -67,6 +70,10 @@ pub struct StateInner {
   pub seeded_rng: Option<StdRng>,
   pub target_lib: TargetLib,
   pub debug_type: DebugType,
+  pub heap_snapshot_requests: Vec<HeapSnapshotRequest>,
+  /// Set by the worker when `--cpu-prof` is given. It lives here so the
+  /// profile can also be written when the process exits from an op.
+  pub cpu_profiler: Option<CpuProfiler>,
 }
 
 impl State {
@@ -245,6 +252,18 @@ impl State {
       exit_unstable(api_name);
     }
   }
+
+  /// Stops the profiler started by `--cpu-prof`, if any, and writes the
+  /// profile. Has to be called while the worker's inspector is still alive.
+  pub fn stop_cpu_profiler(&self) {
+    let maybe_cpu_profiler = self.0.borrow_mut().cpu_profiler.take();
+    if let Some(cpu_profiler) = maybe_cpu_profiler {
+      let global_state = self.0.borrow().global_state.clone();
+      if let Err(err) = cpu_profiler.stop(&global_state.ts_compiler) {
+        eprintln!("Failed to write CPU profile: {}", err);
+      }
+    }
+  }
 }
 
 fn exit_unstable(api_name: &str) {
@@ -296,7 +315,7 @@ impl ModuleLoader for State {
           "http" | "https" => {
             let specifier_url = module_specifier.as_url();
             match specifier_url.scheme() {
-              "http" | "https" => {}
+              "http" | "https" | "data" => {}
               _ => {
                 let e = OpError::permission_denied("Remote module are not allowed to statically import local modules. Use dynamic import instead.".to_string());
                 return async move { Err(e.into()) }.boxed_local();
@@ -322,6 +341,7 @@ impl ModuleLoader for State {
         // Real module name, might be different from initial specifier
         // due to redirections.
         code: compiled_module.code,
+        binary: compiled_module.binary,
         module_url_specified,
         module_url_found: compiled_module.name,
       })
@@ -330,25 +350,36 @@ impl ModuleLoader for State {
     fut.boxed_local()
   }
 
+  /// Downloads the whole static module graph in parallel, so that `load`
+  /// finds every module in the in-process cache.
   fn prepare_load(
     &self,
     _load_id: ModuleLoadId,
-    _module_specifier: &ModuleSpecifier,
-    _maybe_referrer: Option<String>,
-    _is_dyn_import: bool,
+    module_specifier: &ModuleSpecifier,
+    maybe_referrer: Option<String>,
+    is_dyn_import: bool,
   ) -> Pin<Box<dyn Future<Output = Result<(), ErrBox>>>> {
-    // TODO(bartlomieju):
-    // 1. recursively:
-    //    a) resolve specifier
-    //    b) check permission if dynamic import
-    //    c) fetch/download source code
-    //    d) parse the source code and extract all import/exports (dependencies)
-    //    e) add discovered deps and loop algorithm until no new dependencies
-    //        are discovered
-    // 2. run through appropriate compiler giving it access only to
-    //     discovered files
-
-    async { Ok(()) }.boxed_local()
+    // Denied dynamic imports are reported by `load`.
+    if is_dyn_import && self.check_dyn_import(module_specifier).is_err() {
+      return async { Ok(()) }.boxed_local();
+    }
+
+    let state = self.borrow();
+    let module_graph_loader = ModuleGraphLoader::new(
+      state.global_state.file_fetcher.clone(),
+      state.import_map.clone(),
+    );
+    let module_specifier = module_specifier.clone();
+    let maybe_referrer = maybe_referrer
+      .and_then(|referrer| ModuleSpecifier::resolve_url(&referrer).ok());
+
+    async move {
+      module_graph_loader
+        .prefetch(module_specifier, maybe_referrer)
+        .await;
+      Ok(())
+    }
+    .boxed_local()
   }
 }
 
@@ -395,6 +426,8 @@ impl State {
       seeded_rng,
       target_lib: TargetLib::Main,
       debug_type,
+      heap_snapshot_requests: vec![],
+      cpu_profiler: None,
     }));
 
     Ok(Self(state))
@@ -430,6 +463,8 @@ impl State {
       seeded_rng,
       target_lib: TargetLib::Worker,
       debug_type: DebugType::Dependent,
+      heap_snapshot_requests: vec![],
+      cpu_profiler: None,
     }));
 
     Ok(Self(state))
@@ -494,6 +529,8 @@ impl State {
         self.check_read(Path::new(&path))?;
         Ok(())
       }
+      // The contents are provided by the program itself.
+      "data" | "blob" => Ok(()),
       _ => unreachable!(),
     }
   }
@@ -6,12 +6,17 @@ use crate::swc_common::errors::DiagnosticBuilder;
 use crate::swc_common::errors::Emitter;
 use crate::swc_common::errors::Handler;
 use crate::swc_common::errors::HandlerFlags;
+use crate::swc_common::BytePos;
 use crate::swc_common::FileName;
 use crate::swc_common::Globals;
 use crate::swc_common::SourceMap;
 use crate::swc_common::Span;
 use crate::swc_ecma_ast;
 use crate::swc_ecma_parser::lexer::Lexer;
+use crate::swc_ecma_parser::token::Keyword;
+use crate::swc_ecma_parser::token::Token;
+use crate::swc_ecma_parser::token::TokenAndSpan;
+use crate::swc_ecma_parser::token::Word;
 use crate::swc_ecma_parser::JscTarget;
 use crate::swc_ecma_parser::Parser;
 use crate::swc_ecma_parser::Session;
@@ -113,11 +118,11 @@ impl AstParser {
   where
     F: FnOnce(Result<swc_ecma_ast::Module, SwcDiagnosticBuffer>) -> R,
   {
+    let source_code = strip_json_import_assertions(source_code);
     swc_common::GLOBALS.set(&self.globals, || {
-      let swc_source_file = self.source_map.new_source_file(
-        FileName::Custom(file_name.to_string()),
-        source_code.to_string(),
-      );
+      let swc_source_file = self
+        .source_map
+        .new_source_file(FileName::Custom(file_name.to_string()), source_code);
 
       let buffered_err = self.buffered_error.clone();
       let session = Session {
@@ -165,6 +170,110 @@ impl AstParser {
   }
 }
 
+fn is_ident(token: &TokenAndSpan, name: &str) -> bool {
+  match &token.token {
+    Token::Word(Word::Ident(ident)) => &**ident == name,
+    _ => false,
+  }
+}
+
+fn is_str(token: &TokenAndSpan, value: Option<&str>) -> bool {
+  match &token.token {
+    Token::Str { value: v, .. } => {
+      value.map(|value| &**v == value).unwrap_or(true)
+    }
+    _ => false,
+  }
+}
+
+/// Returns the byte range of the `assert { type: "json" }` clause following
+/// the module specifier at `tokens[0]`, if any.
+fn json_import_assertion_range(
+  tokens: &[TokenAndSpan],
+) -> Option<(BytePos, BytePos)> {
+  let mut iter = tokens.iter();
+  let assert = iter.nth(1)?;
+  if !is_ident(assert, "assert") || assert.had_line_break {
+    return None;
+  }
+  let mut expected_end = false;
+  for (i, token) in iter.enumerate() {
+    match i {
+      0 if token.token == Token::LBrace => {}
+      1 if is_ident(token, "type") || is_str(token, Some("type")) => {}
+      2 if token.token == Token::Colon => {}
+      3 if is_str(token, Some("json")) => expected_end = true,
+      4 if token.token == Token::Comma => {}
+      4 | 5 if expected_end && token.token == Token::RBrace => {
+        return Some((assert.span.lo, token.span.hi));
+      }
+      _ => return None,
+    }
+  }
+  None
+}
+
+/// Blanks out `assert { type: "json" }` clauses of static imports and
+/// re-exports, which neither V8 nor the TypeScript compiler can parse yet.
+///
+/// The source is tokenized, so the same text inside string literals,
+/// template literals and comments is left untouched. Clauses are replaced
+/// with spaces, so positions reported in stack traces and diagnostics stay
+/// the same.
+pub fn strip_json_import_assertions(source_code: &str) -> String {
+  if !source_code.contains("assert") {
+    return source_code.to_string();
+  }
+
+  let source_map = SourceMap::default();
+  let handler = Handler::with_emitter_and_flags(
+    Box::new(SwcErrorBuffer::default()),
+    HandlerFlags::default(),
+  );
+  let session = Session { handler: &handler };
+  let source_file =
+    source_map.new_source_file(FileName::Anon, source_code.to_string());
+
+  let mut ts_config = TsConfig::default();
+  ts_config.dynamic_import = true;
+  let tokens: Vec<TokenAndSpan> =
+    swc_common::GLOBALS.set(&Globals::new(), || {
+      Lexer::new(
+        session,
+        Syntax::Typescript(ts_config),
+        JscTarget::Es2019,
+        SourceFileInput::from(&*source_file),
+        None,
+      )
+      .collect()
+    });
+
+  let mut bytes = source_code.as_bytes().to_vec();
+  for (i, token) in tokens.iter().enumerate() {
+    // Only string literals that are the module specifier of an import or
+    // export declaration, ie. `from "..."` or `import "..."`.
+    let is_specifier = is_str(token, None)
+      && i > 0
+      && (is_ident(&tokens[i - 1], "from")
+        || tokens[i - 1].token == Token::Word(Word::Keyword(Keyword::Import)));
+    if !is_specifier {
+      continue;
+    }
+    if let Some((lo, hi)) = json_import_assertion_range(&tokens[i..]) {
+      let start = (lo - source_file.start_pos).0 as usize;
+      let end = (hi - source_file.start_pos).0 as usize;
+      for byte in &mut bytes[start..end] {
+        if *byte != b'\n' {
+          *byte = b' ';
+        }
+      }
+    }
+  }
+  // Only whole tokens and the whitespace between them are replaced, so the
+  // result is still valid UTF-8.
+  String::from_utf8(bytes).unwrap()
+}
+
 struct DependencyVisitor {
   dependencies: Vec<String>,
   analyze_dynamic_imports: bool,
@@ -257,7 +366,6 @@ impl Visit for DependencyVisitor {
 ///
 ///    await import(`./${dir}/fizz.ts`)
 ///    await import("./" + "fizz.ts")
-#[allow(unused)]
 pub fn analyze_dependencies(
   source_code: &str,
   analyze_dynamic_imports: bool,
@@ -317,3 +425,32 @@ const a = await import("./" + "buzz.ts");
     ]
   );
 }
+
+#[test]
+fn test_strip_json_import_assertions() {
+  let code = "import data from './data.json' assert { type: 'json' };";
+  assert_eq!(
+    strip_json_import_assertions(code),
+    "import data from './data.json'                        ;"
+  );
+  // Newlines are kept.
+  let code =
+    "export { default } from \"./a.json\" assert {\n  type: \"json\"\n};";
+  assert_eq!(
+    strip_json_import_assertions(code),
+    "export { default } from \"./a.json\"         \n              \n ;"
+  );
+  let code = "import './mod.ts';\nconst assert = { type: 'json' };";
+  assert_eq!(strip_json_import_assertions(code), code);
+}
+
+#[test]
+fn test_strip_json_import_assertions_ignores_literals() {
+  let code = r#"
+const a = "import x from './a.json' assert { type: 'json' }";
+const b = `import x from './b.json' assert { type: 'json' }`;
+// import x from './c.json' assert { type: 'json' }
+/* import x from './d.json' assert { type: 'json' } */
+"#;
+  assert_eq!(strip_json_import_assertions(code), code);
+}
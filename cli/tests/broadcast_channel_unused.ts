@@ -0,0 +1,4 @@
+// A channel nobody listens on doesn't keep the program alive.
+const channel = new BroadcastChannel("unused");
+channel.postMessage("hello");
+console.log("done");
@@ -222,6 +222,41 @@ fn fmt_stdin_error() {
   assert!(!output.status.success());
 }
 
+fn run_with_cpu_prof(script: &str) -> (std::process::ExitStatus, usize) {
+  let t = TempDir::new().expect("tempdir fail");
+  let status = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("run")
+    .arg(format!("--cpu-prof={}", t.path().display()))
+    .arg(script)
+    .stdout(std::process::Stdio::null())
+    .stderr(std::process::Stdio::null())
+    .spawn()
+    .expect("Failed to spawn script")
+    .wait()
+    .expect("Failed to wait for child process");
+  let profiles = std::fs::read_dir(t.path())
+    .unwrap()
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.extension().unwrap() == "cpuprofile")
+    .count();
+  (status, profiles)
+}
+
+#[test]
+fn cpu_prof_written_on_exit() {
+  let (status, profiles) = run_with_cpu_prof("cli/tests/exit_error42.ts");
+  assert_eq!(status.code(), Some(42));
+  assert_eq!(profiles, 1);
+}
+
+#[test]
+fn cpu_prof_written_on_uncaught_error() {
+  let (status, profiles) = run_with_cpu_prof("cli/tests/error_001.ts");
+  assert_eq!(status.code(), Some(1));
+  assert_eq!(profiles, 1);
+}
+
 // Warning: this test requires internet access.
 #[test]
 fn upgrade_in_tmpdir() {
@@ -954,9 +989,25 @@ itest_ignore!(_019_media_types {
 
 itest!(_020_json_modules {
   args: "run --reload 020_json_modules.ts",
-  check_stderr: true,
   output: "020_json_modules.ts.out",
+});
+
+itest!(json_module_assert {
+  args: "run --reload json_module_assert.ts",
+  output: "json_module_assert.ts.out",
+});
+
+itest!(json_module_type_error {
+  args: "run --reload json_module_type_error.ts",
+  check_stderr: true,
   exit_code: 1,
+  output: "json_module_type_error.ts.out",
+});
+
+itest!(json_module_remote {
+  args: "run --reload json_module_remote.js",
+  output: "json_module_remote.js.out",
+  http_server: true,
 });
 
 itest!(_021_mjs_modules {
@@ -1604,6 +1655,18 @@ itest!(wasm_async {
   output: "wasm_async.out",
 });
 
+itest!(wasm_module {
+  args: "run --reload wasm_module.ts",
+  output: "wasm_module.ts.out",
+});
+
+itest!(wasm_module_type_error {
+  args: "run --reload wasm_module_type_error.ts",
+  check_stderr: true,
+  exit_code: 1,
+  output: "wasm_module_type_error.ts.out",
+});
+
 itest!(top_level_await {
   args: "run --allow-read top_level_await.js",
   output: "top_level_await.out",
@@ -1646,6 +1709,13 @@ itest!(unstable_enabled_js {
   output: "unstable_enabled_js.out",
 });
 
+itest!(tls_unstable_options {
+  args: "run --reload tls_unstable_options.js",
+  check_stderr: true,
+  exit_code: 70,
+  output: "tls_unstable_options.out",
+});
+
 itest!(_053_import_compression {
   args: "run --reload --allow-net 053_import_compression/main.ts",
   output: "053_import_compression.out",
@@ -1692,6 +1762,11 @@ itest!(proto_exploit {
   output: "proto_exploit.js.out",
 });
 
+itest!(broadcast_channel_unused {
+  args: "run --reload broadcast_channel_unused.ts",
+  output: "broadcast_channel_unused.ts.out",
+});
+
 #[test]
 fn cafile_fetch() {
   use deno::http_cache::url_to_filename;
@@ -0,0 +1,3 @@
+import config from "./subdir/config.json" assert { type: "json" };
+const bar: boolean = config.foo.bar;
+console.log(bar, config.foo.baz[0]);
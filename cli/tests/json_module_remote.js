@@ -0,0 +1,2 @@
+import config from "http://localhost:4545/cli/tests/subdir/config.json";
+console.log(JSON.stringify(config));
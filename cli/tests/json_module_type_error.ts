@@ -0,0 +1,3 @@
+import config from "./subdir/config.json";
+const bar: number = config.foo.bar;
+console.log(bar);
@@ -0,0 +1,6 @@
+const channel = new BroadcastChannel("test");
+channel.onmessage = (e) => {
+  channel.postMessage(`${e.data} from worker`);
+  channel.close();
+};
+postMessage("ready");
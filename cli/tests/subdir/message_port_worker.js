@@ -0,0 +1,15 @@
+onmessage = function (e) {
+  const { start } = e.data;
+  const [port] = e.ports;
+  port.onmessage = (e) => {
+    if (start) {
+      postMessage(e.data);
+    } else {
+      port.postMessage(`${e.data} world`);
+    }
+    port.close();
+  };
+  if (start) {
+    port.postMessage("hello");
+  }
+};
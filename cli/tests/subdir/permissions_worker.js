@@ -0,0 +1,8 @@
+onmessage = async function () {
+  const net = await Deno.permissions.query({ name: "net" });
+  const localhost = await Deno.permissions.query({
+    name: "net",
+    url: "http://localhost:4545",
+  });
+  postMessage({ net: net.state, localhost: localhost.state });
+};
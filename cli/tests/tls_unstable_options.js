@@ -0,0 +1,2 @@
+// PEM string certificates are only available with --unstable.
+Deno.listenTls({ port: 4557, cert: "", key: "" });
@@ -0,0 +1,4 @@
+import { add, quadruple, memory } from "./wasm_module.wasm";
+
+const sum: number = add(1, 2);
+console.log(sum, quadruple(3), memory instanceof WebAssembly.Memory);
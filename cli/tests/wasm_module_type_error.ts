@@ -0,0 +1,4 @@
+import { add } from "./wasm_module.wasm";
+
+const sum: string = add(1, 2);
+console.log(sum);
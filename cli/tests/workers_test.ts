@@ -307,3 +307,166 @@ Deno.test({
     w.terminate();
   },
 });
+
+Deno.test({
+  name: "worker with scoped permissions",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+    const w = new Worker("../tests/subdir/permissions_worker.js", {
+      type: "module",
+      deno: { permissions: { net: ["localhost:4545"] } },
+    });
+    w.onmessage = (e): void => {
+      assertEquals(e.data, { net: "denied", localhost: "granted" });
+      promise.resolve();
+    };
+    w.postMessage(null);
+    await promise;
+    w.terminate();
+  },
+});
+
+Deno.test({
+  name: "worker can't escalate permissions",
+  fn: function (): void {
+    let err;
+    try {
+      new Worker("../tests/subdir/permissions_worker.js", {
+        type: "module",
+        deno: { permissions: { read: true } },
+      });
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof Deno.errors.PermissionDenied);
+  },
+});
+
+Deno.test({
+  name: "worker MessageChannel between siblings",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+    const w1 = new Worker("../tests/subdir/message_port_worker.js", {
+      type: "module",
+    });
+    const w2 = new Worker("../tests/subdir/message_port_worker.js", {
+      type: "module",
+    });
+    const { port1, port2 } = new MessageChannel();
+    w1.onmessage = (e): void => {
+      assertEquals(e.data, "hello world");
+      promise.resolve();
+    };
+    w2.postMessage({ start: false }, [port2]);
+    w1.postMessage({ start: true }, [port1]);
+    await promise;
+    w1.terminate();
+    w2.terminate();
+  },
+});
+
+Deno.test({
+  name: "MessagePort transfer errors",
+  fn: function (): void {
+    const { port1, port2 } = new MessageChannel();
+    const closed = new MessageChannel();
+    closed.port1.close();
+    const cases: Array<[unknown, unknown[]]> = [
+      [null, [port1]],
+      [null, [port2, port2]],
+      [null, [closed.port1]],
+      [null, [new ArrayBuffer(1)]],
+    ];
+    for (const [message, transfer] of cases) {
+      let err;
+      try {
+        port1.postMessage(message, transfer);
+      } catch (e) {
+        err = e;
+      }
+      assert(err instanceof DOMException);
+      assertEquals(err.name, "DataCloneError");
+    }
+    port1.close();
+    port2.close();
+    closed.port2.close();
+  },
+});
+
+Deno.test({
+  name: "MessagePort stays usable when sending fails",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+    const { port1, port2 } = new MessageChannel();
+    const other = new MessageChannel();
+    let err;
+    try {
+      port1.postMessage(null, [other.port1, {}]);
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof DOMException);
+    assertEquals(err.name, "DataCloneError");
+    other.port2.onmessage = (e): void => {
+      assertEquals(e.data, "still entangled");
+      promise.resolve();
+    };
+    other.port1.postMessage("still entangled");
+    await promise;
+    for (const port of [port1, port2, other.port1, other.port2]) {
+      port.close();
+    }
+  },
+});
+
+Deno.test({
+  name: "MessagePort posted to a closed channel is closed",
+  fn: async function (): Promise<void> {
+    const { port1, port2 } = new MessageChannel();
+    const other = new MessageChannel();
+    port2.close();
+    // Receiving on the peer of the dropped port only ends once that port is
+    // closed, otherwise the op sanitizer reports the receive as leaking.
+    other.port2.start();
+    port1.postMessage(null, [other.port1]);
+    await new Promise((resolve): number => setTimeout(resolve, 0));
+    port1.close();
+    other.port2.close();
+  },
+});
+
+Deno.test({
+  name: "worker BroadcastChannel",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+    const w = new Worker("../tests/subdir/broadcast_channel_worker.js", {
+      type: "module",
+    });
+    const received: unknown[] = [];
+    const a = new BroadcastChannel("test");
+    const b = new BroadcastChannel("test");
+    const other = new BroadcastChannel("other");
+    other.onmessage = (): void => {
+      throw new Error("unexpected message");
+    };
+    b.addEventListener("message", (e): void => {
+      received.push((e as MessageEvent).data);
+      if (received.length === 2) {
+        promise.resolve();
+      }
+    });
+    a.onmessage = (e): void => {
+      assertEquals(e.data, "hi from worker");
+    };
+    w.onmessage = (e): void => {
+      assertEquals(e.data, "ready");
+      a.postMessage("hi");
+    };
+    await promise;
+    assertEquals(received, ["hi", "hi from worker"]);
+    a.close();
+    b.close();
+    other.close();
+    w.terminate();
+  },
+});
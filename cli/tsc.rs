@@ -52,6 +52,8 @@ use url::Url;
 pub struct CompiledModule {
   pub code: String,
   pub name: String,
+  /// Passed to `code` as `import.meta.binary`.
+  pub binary: Option<Buf>,
 }
 
 pub struct CompilerWorker(WebWorker);
@@ -537,6 +539,10 @@ impl TsCompiler {
         self.cache_source_map(&specifier, &source.contents)?;
       } else if emitted_name.ends_with(".js") {
         self.cache_compiled_file(&specifier, &source.contents)?;
+      } else if emitted_name.ends_with(".json") {
+        // JSON modules are wrapped when they are loaded, see
+        // `GlobalState::fetch_compiled_module`.
+        continue;
       } else {
         panic!("Trying to cache unknown file type {}", emitted_name);
       }
@@ -556,6 +562,7 @@ impl TsCompiler {
         .unwrap()
         .to_string(),
       name: module_url.to_string(),
+      binary: None,
     };
 
     Ok(compiled_module)
@@ -756,10 +763,10 @@ async fn execute_in_thread(
     tokio_util::run_basic(worker).expect("Panic in event loop");
   })?;
   let handle = handle_receiver.recv().unwrap()?;
-  handle.post_message(req)?;
+  handle.post_message(req.into())?;
   let event = handle.get_event().await.expect("Compiler didn't respond");
   let buf = match event {
-    WorkerEvent::Message(buf) => Ok(buf),
+    WorkerEvent::Message(msg) => Ok(msg.data),
     WorkerEvent::Error(error) => Err(error),
     WorkerEvent::TerminalError(error) => Err(error),
   }?;
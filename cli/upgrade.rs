@@ -7,12 +7,14 @@
 //! the same functions as ops available in JS runtime.
 
 extern crate semver_parser;
+use crate::auth_tokens::AuthTokens;
 use crate::futures::FutureExt;
 use crate::http_util::fetch_once;
 use crate::http_util::FetchOnceResult;
 use crate::op_error::OpError;
 use crate::ErrBox;
 use regex::Regex;
+use reqwest::header::AUTHORIZATION;
 use reqwest::{redirect::Policy, Client};
 use semver_parser::version::parse as semver_parse;
 use semver_parser::version::Version;
@@ -36,16 +38,17 @@ const ARCHIVE_NAME: &str = "deno-x86_64-apple-darwin.zip";
 #[cfg(target_os = "linux")]
 const ARCHIVE_NAME: &str = "deno-x86_64-unknown-linux-gnu.zip";
 
-async fn get_latest_version(client: &Client) -> Result<Version, ErrBox> {
+async fn get_latest_version(
+  client: &Client,
+  auth_tokens: &AuthTokens,
+) -> Result<Version, ErrBox> {
   println!("Checking for latest version");
-  let body = client
-    .get(Url::parse(
-      "https://github.com/denoland/deno/releases/latest",
-    )?)
-    .send()
-    .await?
-    .text()
-    .await?;
+  let url = Url::parse("https://github.com/denoland/deno/releases/latest")?;
+  let mut request = client.get(url.clone());
+  if let Some(auth_token) = auth_tokens.get(&url) {
+    request = request.header(AUTHORIZATION, auth_token.to_string());
+  }
+  let body = request.send().await?.text().await?;
   let v = find_version(&body)?;
   Ok(semver_parse(&v).unwrap())
 }
@@ -58,6 +61,7 @@ pub async fn upgrade_command(
   version: Option<String>,
 ) -> Result<(), ErrBox> {
   let client = Client::builder().redirect(Policy::none()).build()?;
+  let auth_tokens = AuthTokens::from_env();
   let current_version = semver_parse(crate::version::DENO).unwrap();
 
   let install_version = match version {
@@ -76,7 +80,7 @@ pub async fn upgrade_command(
       }
     },
     None => {
-      let latest_version = get_latest_version(&client).await?;
+      let latest_version = get_latest_version(&client, &auth_tokens).await?;
 
       if !force && current_version >= latest_version {
         println!(
@@ -95,8 +99,12 @@ pub async fn upgrade_command(
     &install_version
   );
 
-  let archive_data =
-    download_package(&compose_url_to_exec(&install_version)?, client).await?;
+  let archive_data = download_package(
+    &compose_url_to_exec(&install_version)?,
+    client,
+    auth_tokens,
+  )
+  .await?;
 
   let old_exe_path = std::env::current_exe()?;
   let new_exe_path = unpack(archive_data)?;
@@ -116,15 +124,18 @@ pub async fn upgrade_command(
 fn download_package(
   url: &Url,
   client: Client,
+  auth_tokens: AuthTokens,
 ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, ErrBox>>>> {
   println!("downloading {}", url);
   let url = url.clone();
+  let maybe_auth_token = auth_tokens.get(&url);
   let fut = async move {
-    match fetch_once(client.clone(), &url, None).await? {
+    match fetch_once(client.clone(), &url, None, None, maybe_auth_token).await?
+    {
       FetchOnceResult::Code(source, _) => Ok(source),
       FetchOnceResult::NotModified => unreachable!(),
       FetchOnceResult::Redirect(_url, _) => {
-        download_package(&_url, client).await
+        download_package(&_url, client, auth_tokens).await
       }
     }
   };
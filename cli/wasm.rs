@@ -0,0 +1,503 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Loads WebAssembly binaries as ES modules.
+//!
+//! A `.wasm` file is turned into a synthetic JavaScript module which imports
+//! every module named in the binary's import section, instantiates the
+//! binary with those namespaces as its import object and re-exports the
+//! instance's exports. For type checking, a declaration file is generated
+//! from the signatures found in the binary.
+
+use crate::file_fetcher::SourceFile;
+use crate::op_error::OpError;
+use crate::tsc::CompiledModule;
+use deno_core::ErrBox;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+const WASM_VERSION: &[u8] = &[1, 0, 0, 0];
+
+const SECTION_TYPE: u8 = 1;
+const SECTION_IMPORT: u8 = 2;
+const SECTION_FUNCTION: u8 = 3;
+const SECTION_EXPORT: u8 = 7;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueType {
+  I32,
+  I64,
+  F32,
+  F64,
+  Other(u8),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionType {
+  pub params: Vec<ValueType>,
+  pub results: Vec<ValueType>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExternalKind {
+  Function(FunctionType),
+  Table,
+  Memory,
+  Global,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WasmImport {
+  pub module: String,
+  pub name: String,
+  pub kind: ExternalKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WasmExport {
+  pub name: String,
+  pub kind: ExternalKind,
+}
+
+/// The imports and exports of a WebAssembly binary.
+#[derive(Debug, Default, PartialEq)]
+pub struct WasmModuleInfo {
+  pub imports: Vec<WasmImport>,
+  pub exports: Vec<WasmExport>,
+}
+
+impl WasmModuleInfo {
+  /// Reads the type, import, function and export sections of `bytes`. All
+  /// other sections, including the code, are skipped; V8 validates them
+  /// when the module is compiled.
+  pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(4)? != WASM_MAGIC {
+      return Err("missing magic number".to_string());
+    }
+    if reader.take(4)? != WASM_VERSION {
+      return Err("unsupported version".to_string());
+    }
+
+    let mut types = Vec::new();
+    // Type indices of all functions, imported functions come first.
+    let mut functions = Vec::new();
+    let mut info = WasmModuleInfo::default();
+    // Exports are resolved once all functions are known.
+    let mut exports = Vec::new();
+
+    while !reader.is_empty() {
+      let id = reader.u8()?;
+      let size = reader.u32()? as usize;
+      let mut section = Reader {
+        bytes: reader.take(size)?,
+        pos: 0,
+      };
+      match id {
+        SECTION_TYPE => {
+          for _ in 0..section.u32()? {
+            if section.u8()? != 0x60 {
+              return Err("invalid function type".to_string());
+            }
+            let params = section.value_types()?;
+            let results = section.value_types()?;
+            types.push(FunctionType { params, results });
+          }
+        }
+        SECTION_IMPORT => {
+          for _ in 0..section.u32()? {
+            let module = section.name()?;
+            let name = section.name()?;
+            let kind = match section.u8()? {
+              0x00 => {
+                let index = section.u32()?;
+                functions.push(index);
+                ExternalKind::Function(get_type(&types, index)?)
+              }
+              0x01 => {
+                section.u8()?;
+                section.limits()?;
+                ExternalKind::Table
+              }
+              0x02 => {
+                section.limits()?;
+                ExternalKind::Memory
+              }
+              0x03 => {
+                section.value_type()?;
+                section.u8()?;
+                ExternalKind::Global
+              }
+              kind => return Err(format!("invalid import kind {}", kind)),
+            };
+            info.imports.push(WasmImport { module, name, kind });
+          }
+        }
+        SECTION_FUNCTION => {
+          for _ in 0..section.u32()? {
+            functions.push(section.u32()?);
+          }
+        }
+        SECTION_EXPORT => {
+          for _ in 0..section.u32()? {
+            let name = section.name()?;
+            let kind = section.u8()?;
+            let index = section.u32()?;
+            exports.push((name, kind, index));
+          }
+        }
+        _ => {}
+      }
+    }
+
+    for (name, kind, index) in exports {
+      let kind = match kind {
+        0x00 => {
+          let type_index = functions
+            .get(index as usize)
+            .ok_or_else(|| format!("invalid function index {}", index))?;
+          ExternalKind::Function(get_type(&types, *type_index)?)
+        }
+        0x01 => ExternalKind::Table,
+        0x02 => ExternalKind::Memory,
+        0x03 => ExternalKind::Global,
+        kind => return Err(format!("invalid export kind {}", kind)),
+      };
+      info.exports.push(WasmExport { name, kind });
+    }
+
+    Ok(info)
+  }
+}
+
+fn get_type(
+  types: &[FunctionType],
+  index: u32,
+) -> Result<FunctionType, String> {
+  types
+    .get(index as usize)
+    .cloned()
+    .ok_or_else(|| format!("invalid type index {}", index))
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Reader<'a> {
+  fn is_empty(&self) -> bool {
+    self.pos >= self.bytes.len()
+  }
+
+  fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+    if self.bytes.len() - self.pos < len {
+      return Err("unexpected end of file".to_string());
+    }
+    let bytes = &self.bytes[self.pos..self.pos + len];
+    self.pos += len;
+    Ok(bytes)
+  }
+
+  fn u8(&mut self) -> Result<u8, String> {
+    Ok(self.take(1)?[0])
+  }
+
+  /// Reads an unsigned LEB128 integer.
+  fn u32(&mut self) -> Result<u32, String> {
+    let mut result = 0u32;
+    for shift in (0..35).step_by(7) {
+      let byte = self.u8()?;
+      result |= u32::from(byte & 0x7f) << shift;
+      if byte & 0x80 == 0 {
+        return Ok(result);
+      }
+    }
+    Err("invalid integer".to_string())
+  }
+
+  fn name(&mut self) -> Result<String, String> {
+    let len = self.u32()? as usize;
+    let bytes = self.take(len)?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| "invalid name".to_string())
+  }
+
+  fn value_type(&mut self) -> Result<ValueType, String> {
+    Ok(match self.u8()? {
+      0x7f => ValueType::I32,
+      0x7e => ValueType::I64,
+      0x7d => ValueType::F32,
+      0x7c => ValueType::F64,
+      other => ValueType::Other(other),
+    })
+  }
+
+  fn value_types(&mut self) -> Result<Vec<ValueType>, String> {
+    (0..self.u32()?).map(|_| self.value_type()).collect()
+  }
+
+  fn limits(&mut self) -> Result<(), String> {
+    let flags = self.u8()?;
+    self.u32()?;
+    if flags & 1 != 0 {
+      self.u32()?;
+    }
+    Ok(())
+  }
+}
+
+fn parse_source_file(
+  source_file: &SourceFile,
+) -> Result<WasmModuleInfo, ErrBox> {
+  WasmModuleInfo::parse(&source_file.source_code).map_err(|err| {
+    OpError::other(format!(
+      "Invalid WebAssembly module \"{}\": {}",
+      source_file.url, err
+    ))
+    .into()
+  })
+}
+
+fn js_string(s: &str) -> String {
+  serde_json::Value::from(s).to_string()
+}
+
+/// Returns true if `name` can be used in an `export { local as name }`
+/// clause, which accepts reserved words as well.
+fn is_valid_export_name(name: &str) -> bool {
+  let mut chars = name.chars();
+  match chars.next() {
+    Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {}
+    _ => return false,
+  }
+  chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Returns the `export { ... }` clause that exports the locals `e0`, `e1`,
+/// ... under the names of the binary's exports.
+fn export_clause(exports: &[WasmExport]) -> String {
+  let specifiers = exports
+    .iter()
+    .enumerate()
+    .filter(|(_, export)| is_valid_export_name(&export.name))
+    .map(|(i, export)| format!("e{} as {}", i, export.name))
+    .collect::<Vec<_>>();
+  if specifiers.is_empty() {
+    // Still makes the file a module.
+    return "export {};\n".to_string();
+  }
+  format!("export {{ {} }};\n", specifiers.join(", "))
+}
+
+/// Generates the JavaScript module that instantiates a WebAssembly binary.
+/// The binary itself is passed to the module as `import.meta.binary`.
+///
+/// Exports are bound to generated locals and exported under their own names,
+/// so they can't clash with the other locals of the module.
+pub fn compile_wasm_module(
+  source_file: &SourceFile,
+) -> Result<CompiledModule, ErrBox> {
+  let info = parse_source_file(source_file)?;
+  let mut code = String::new();
+
+  let mut modules = Vec::new();
+  let mut seen = HashSet::new();
+  for import in &info.imports {
+    if seen.insert(import.module.as_str()) {
+      modules.push(import.module.as_str());
+    }
+  }
+  for (i, module) in modules.iter().enumerate() {
+    writeln!(code, "import * as i{} from {};", i, js_string(module)).unwrap();
+  }
+  code.push_str("const imports = Object.create(null);\n");
+  for (i, module) in modules.iter().enumerate() {
+    writeln!(code, "imports[{}] = i{};", js_string(module), i).unwrap();
+  }
+
+  code.push_str("const module = new WebAssembly.Module(import.meta.binary);\n");
+  code
+    .push_str("const instance = new WebAssembly.Instance(module, imports);\n");
+  for (i, export) in info.exports.iter().enumerate() {
+    if is_valid_export_name(&export.name) {
+      writeln!(
+        code,
+        "const e{} = instance.exports[{}];",
+        i,
+        js_string(&export.name)
+      )
+      .unwrap();
+    }
+  }
+  code.push_str(&export_clause(&info.exports));
+
+  Ok(CompiledModule {
+    code,
+    name: source_file.url.to_string(),
+    binary: Some(source_file.source_code.clone().into_boxed_slice()),
+  })
+}
+
+fn ts_type(value_type: &ValueType) -> &'static str {
+  match value_type {
+    ValueType::I32 | ValueType::F32 | ValueType::F64 => "number",
+    ValueType::I64 => "bigint",
+    ValueType::Other(_) => "unknown",
+  }
+}
+
+/// Generates a TypeScript declaration file describing the exports of a
+/// WebAssembly binary, used by the compiler in place of the binary.
+pub fn wasm_declaration(source_file: &SourceFile) -> Result<String, ErrBox> {
+  let info = parse_source_file(source_file)?;
+  let mut code = String::new();
+
+  for (i, export) in info.exports.iter().enumerate() {
+    if !is_valid_export_name(&export.name) {
+      continue;
+    }
+    let ts_export_type = match &export.kind {
+      ExternalKind::Function(function_type) => {
+        let params = function_type
+          .params
+          .iter()
+          .enumerate()
+          .map(|(i, param)| format!("arg{}: {}", i, ts_type(param)))
+          .collect::<Vec<_>>()
+          .join(", ");
+        let result = match function_type.results.as_slice() {
+          [] => "void".to_string(),
+          [result] => ts_type(result).to_string(),
+          _ => "unknown[]".to_string(),
+        };
+        format!("({}) => {}", params, result)
+      }
+      ExternalKind::Table => "WebAssembly.Table".to_string(),
+      ExternalKind::Memory => "WebAssembly.Memory".to_string(),
+      ExternalKind::Global => "WebAssembly.Global".to_string(),
+    };
+    writeln!(code, "declare const e{}: {};", i, ts_export_type).unwrap();
+  }
+  code.push_str(&export_clause(&info.exports));
+
+  Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::msg::MediaType;
+  use url::Url;
+
+  fn wasm_source_file() -> SourceFile {
+    let path = crate::test_util::tests_path().join("wasm_module.wasm");
+    SourceFile {
+      url: Url::from_file_path(&path).unwrap(),
+      filename: path.clone(),
+      media_type: MediaType::Wasm,
+      source_code: std::fs::read(&path).unwrap(),
+      types_url: None,
+    }
+  }
+
+  #[test]
+  fn test_parse() {
+    let info = WasmModuleInfo::parse(&wasm_source_file().source_code).unwrap();
+    let unary = FunctionType {
+      params: vec![ValueType::I32],
+      results: vec![ValueType::I32],
+    };
+    assert_eq!(
+      info.imports,
+      vec![WasmImport {
+        module: "./wasm_module_env.js".to_string(),
+        name: "double".to_string(),
+        kind: ExternalKind::Function(unary.clone()),
+      }]
+    );
+    assert_eq!(
+      info.exports,
+      vec![
+        WasmExport {
+          name: "add".to_string(),
+          kind: ExternalKind::Function(FunctionType {
+            params: vec![ValueType::I32, ValueType::I32],
+            results: vec![ValueType::I32],
+          }),
+        },
+        WasmExport {
+          name: "quadruple".to_string(),
+          kind: ExternalKind::Function(unary),
+        },
+        WasmExport {
+          name: "memory".to_string(),
+          kind: ExternalKind::Memory,
+        },
+      ]
+    );
+
+    assert!(WasmModuleInfo::parse(b"\0asm").is_err());
+    assert!(WasmModuleInfo::parse(b"not wasm").is_err());
+  }
+
+  #[test]
+  fn test_compile_wasm_module() {
+    let source_file = wasm_source_file();
+    let compiled_module = compile_wasm_module(&source_file).unwrap();
+    assert_eq!(
+      compiled_module.code,
+      "import * as i0 from \"./wasm_module_env.js\";\n\
+       const imports = Object.create(null);\n\
+       imports[\"./wasm_module_env.js\"] = i0;\n\
+       const module = new WebAssembly.Module(import.meta.binary);\n\
+       const instance = new WebAssembly.Instance(module, imports);\n\
+       const e0 = instance.exports[\"add\"];\n\
+       const e1 = instance.exports[\"quadruple\"];\n\
+       const e2 = instance.exports[\"memory\"];\n\
+       export { e0 as add, e1 as quadruple, e2 as memory };\n"
+    );
+    assert_eq!(
+      compiled_module.binary.as_deref(),
+      Some(source_file.source_code.as_slice())
+    );
+  }
+
+  #[test]
+  fn test_export_clause() {
+    let export = |name: &str| WasmExport {
+      name: name.to_string(),
+      kind: ExternalKind::Memory,
+    };
+    assert_eq!(
+      export_clause(&[
+        export("instance"),
+        export("foo-bar"),
+        export("default")
+      ]),
+      "export { e0 as instance, e2 as default };\n"
+    );
+    assert_eq!(export_clause(&[]), "export {};\n");
+  }
+
+  #[test]
+  fn test_wasm_declaration() {
+    assert_eq!(
+      wasm_declaration(&wasm_source_file()).unwrap(),
+      "declare const e0: (arg0: number, arg1: number) => number;\n\
+       declare const e1: (arg0: number) => number;\n\
+       declare const e2: WebAssembly.Memory;\n\
+       export { e0 as add, e1 as quadruple, e2 as memory };\n"
+    );
+  }
+
+  #[test]
+  fn test_is_valid_export_name() {
+    assert!(is_valid_export_name("add"));
+    assert!(is_valid_export_name("_start"));
+    assert!(is_valid_export_name("$x1"));
+    assert!(!is_valid_export_name("1x"));
+    assert!(!is_valid_export_name("foo-bar"));
+    assert!(is_valid_export_name("delete"));
+    assert!(!is_valid_export_name(""));
+  }
+}
@@ -1,5 +1,6 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use crate::ops;
+use crate::ops::worker_host::attach_message_ports;
 use crate::state::State;
 use crate::worker::Worker;
 use crate::worker::WorkerEvent;
@@ -131,11 +132,14 @@ impl WebWorker {
       ops::errors::init(isolate, &state);
       ops::timers::init(isolate, &state);
       ops::fetch::init(isolate, &state);
+      ops::blob::init(isolate, &state);
+      ops::websocket::init(isolate, &state);
 
       if has_deno_namespace {
         ops::runtime_compiler::init(isolate, &state);
         ops::fs::init(isolate, &state);
         ops::fs_events::init(isolate, &state);
+        ops::http::init(isolate, &state);
         ops::plugins::init(isolate, &state);
         ops::net::init(isolate, &state);
         ops::tls::init(isolate, &state);
@@ -216,9 +220,14 @@ impl Future for WebWorker {
     {
       match r {
         Some(msg) => {
-          let msg = String::from_utf8(msg.to_vec()).unwrap();
+          let ports = attach_message_ports(
+            &mut worker.isolate.resource_table.borrow_mut(),
+            msg.ports,
+          );
+          let msg = String::from_utf8(msg.data.to_vec()).unwrap();
           debug!("received message from host: {}", msg);
-          let script = format!("workerMessageRecvCallback({})", msg);
+          let script =
+            format!("workerMessageRecvCallback({}, {})", msg, json!(ports));
 
           if let Err(e) = worker.execute(&script) {
             // If execution was terminated during message callback then
@@ -297,20 +306,20 @@ mod tests {
 
     tokio_util::run_basic(async move {
       let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
-      let r = handle.post_message(msg.clone());
+      let r = handle.post_message(msg.clone().into());
       assert!(r.is_ok());
 
       let maybe_msg = handle.get_event().await;
       assert!(maybe_msg.is_some());
 
-      let r = handle.post_message(msg.clone());
+      let r = handle.post_message(msg.clone().into());
       assert!(r.is_ok());
 
       let maybe_msg = handle.get_event().await;
       assert!(maybe_msg.is_some());
       match maybe_msg {
-        Some(WorkerEvent::Message(buf)) => {
-          assert_eq!(*buf, *b"[1,2,3]");
+        Some(WorkerEvent::Message(msg)) => {
+          assert_eq!(*msg.data, *b"[1,2,3]");
         }
         _ => unreachable!(),
       }
@@ -319,7 +328,7 @@ mod tests {
         .to_string()
         .into_boxed_str()
         .into_boxed_bytes();
-      let r = handle.post_message(msg);
+      let r = handle.post_message(msg.into());
       assert!(r.is_ok());
       let event = handle.get_event().await;
       assert!(event.is_none());
@@ -346,7 +355,7 @@ mod tests {
 
     tokio_util::run_basic(async move {
       let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
-      let r = handle.post_message(msg.clone());
+      let r = handle.post_message(msg.clone().into());
       assert!(r.is_ok());
       let event = handle.get_event().await;
       assert!(event.is_none());
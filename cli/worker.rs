@@ -1,7 +1,10 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::cpu_profiler::CpuProfiler;
 use crate::fmt_errors::JSError;
+use crate::heap_snapshot;
 use crate::inspector::DenoInspector;
 use crate::ops;
+use crate::ops::worker_host::TransferredPorts;
 use crate::state::DebugType;
 use crate::state::State;
 use deno_core::Buf;
@@ -15,40 +18,60 @@ use futures::stream::StreamExt;
 use futures::task::AtomicWaker;
 use std::env;
 use std::future::Future;
+use std::mem;
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::path::Path;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
 use tokio::sync::Mutex as AsyncMutex;
 use url::Url;
 
+/// A message posted to or from a worker, together with the message ports
+/// transferred with it.
+pub struct WorkerMessage {
+  pub data: Buf,
+  pub ports: TransferredPorts,
+}
+
+impl From<Buf> for WorkerMessage {
+  fn from(data: Buf) -> Self {
+    Self {
+      data,
+      ports: TransferredPorts::default(),
+    }
+  }
+}
+
 /// Events that are sent to host from child
 /// worker.
 pub enum WorkerEvent {
-  Message(Buf),
+  Message(WorkerMessage),
   Error(ErrBox),
   TerminalError(ErrBox),
 }
 
 pub struct WorkerChannelsInternal {
   pub sender: mpsc::Sender<WorkerEvent>,
-  pub receiver: mpsc::Receiver<Buf>,
+  pub receiver: mpsc::Receiver<WorkerMessage>,
 }
 
 #[derive(Clone)]
 pub struct WorkerHandle {
-  pub sender: mpsc::Sender<Buf>,
+  pub sender: mpsc::Sender<WorkerMessage>,
   pub receiver: Arc<AsyncMutex<mpsc::Receiver<WorkerEvent>>>,
 }
 
 impl WorkerHandle {
   /// Post message to worker as a host.
-  pub fn post_message(&self, buf: Buf) -> Result<(), ErrBox> {
+  pub fn post_message(&self, msg: WorkerMessage) -> Result<(), ErrBox> {
     let mut sender = self.sender.clone();
-    sender.try_send(buf).map_err(ErrBox::from)
+    sender.try_send(msg).map_err(ErrBox::from)
   }
 
   // TODO: should use `try_lock` and return error if
@@ -60,7 +83,7 @@ impl WorkerHandle {
 }
 
 fn create_channels() -> (WorkerChannelsInternal, WorkerHandle) {
-  let (in_tx, in_rx) = mpsc::channel::<Buf>(1);
+  let (in_tx, in_rx) = mpsc::channel::<WorkerMessage>(1);
   let (out_tx, out_rx) = mpsc::channel::<WorkerEvent>(1);
   let internal_channels = WorkerChannelsInternal {
     sender: out_tx,
@@ -93,6 +116,8 @@ pub struct Worker {
   pub(crate) internal_channels: WorkerChannelsInternal,
   external_channels: WorkerHandle,
   pub(crate) inspector: Option<Box<DenoInspector>>,
+  #[cfg(unix)]
+  heap_snapshot_signal: Option<Signal>,
 }
 
 impl Worker {
@@ -104,7 +129,7 @@ impl Worker {
 
     let inspect = global_state.flags.inspect.as_ref();
     let inspect_brk = global_state.flags.inspect_brk.as_ref();
-    let inspector = inspect
+    let mut inspector = inspect
       .or(inspect_brk)
       .and_then(|host| match state.borrow().debug_type {
         DebugType::Main if inspect_brk.is_some() => Some((host, true)),
@@ -112,7 +137,39 @@ impl Worker {
         DebugType::Internal => None,
       })
       .map(|(host, wait_for_debugger)| {
-        DenoInspector::new(&mut isolate, *host, wait_for_debugger)
+        DenoInspector::new(&mut isolate, Some(*host), wait_for_debugger)
+      });
+
+    // CPU profiling uses an in-process inspector session, so an inspector
+    // without a websocket server is created if there isn't one already.
+    let cpu_prof_dir = global_state
+      .flags
+      .cpu_prof
+      .clone()
+      .filter(|_| state.borrow().debug_type == DebugType::Main);
+    if cpu_prof_dir.is_some() && inspector.is_none() {
+      inspector = Some(DenoInspector::new(&mut isolate, None, false));
+    }
+    if let Some(dir) = cpu_prof_dir {
+      match CpuProfiler::start(inspector.as_mut().unwrap(), dir) {
+        Ok(cpu_profiler) => {
+          state.borrow_mut().cpu_profiler = Some(cpu_profiler)
+        }
+        Err(err) => eprintln!("Failed to start CPU profiler: {}", err),
+      }
+    }
+
+    #[cfg(unix)]
+    let heap_snapshot_signal = global_state
+      .flags
+      .heap_snapshot_signal
+      .as_ref()
+      .filter(|_| state.borrow().debug_type == DebugType::Main)
+      .and_then(|signal_name| {
+        use std::str::FromStr;
+        // The flag value has already been validated during parsing.
+        let signo = nix::sys::signal::Signal::from_str(signal_name).ok()?;
+        signal(SignalKind::from_raw(signo as i32)).ok()
       });
 
     isolate.set_js_error_create_fn(move |core_js_error| {
@@ -129,6 +186,8 @@ impl Worker {
       internal_channels,
       external_channels,
       inspector,
+      #[cfg(unix)]
+      heap_snapshot_signal,
     }
   }
 
@@ -180,6 +239,41 @@ impl Worker {
     self.isolate.mod_evaluate(id)
   }
 
+  /// Writes a heap snapshot of the isolate to `path`. An inspector without
+  /// a websocket server is created if the worker doesn't have one yet.
+  pub fn write_heap_snapshot(&mut self, path: &Path) -> Result<(), ErrBox> {
+    if self.inspector.is_none() {
+      self.inspector = Some(DenoInspector::new(&mut self.isolate, None, false));
+    }
+    heap_snapshot::write_heap_snapshot(self.inspector.as_mut().unwrap(), path)
+  }
+
+  /// Handles heap snapshots requested by `Deno.writeHeapSnapshot()` or by
+  /// the `--heap-snapshot-signal` signal.
+  fn poll_heap_snapshots(&mut self, cx: &mut Context) {
+    #[cfg(unix)]
+    while let Some(signal) = self.heap_snapshot_signal.as_mut() {
+      match signal.poll_recv(cx) {
+        Poll::Ready(Some(())) => {
+          let path = heap_snapshot::default_heap_snapshot_path();
+          match self.write_heap_snapshot(&path) {
+            Ok(()) => eprintln!("Wrote heap snapshot to {}", path.display()),
+            Err(err) => eprintln!("Failed to write heap snapshot: {}", err),
+          }
+        }
+        Poll::Ready(None) => self.heap_snapshot_signal = None,
+        Poll::Pending => break,
+      }
+    }
+
+    let requests =
+      mem::take(&mut self.state.borrow_mut().heap_snapshot_requests);
+    for request in requests {
+      let result = self.write_heap_snapshot(&request.path);
+      let _ = request.sender.send(result);
+    }
+  }
+
   /// Returns a way to communicate with the Worker from other threads.
   pub fn thread_safe_handle(&self) -> WorkerHandle {
     self.external_channels.clone()
@@ -188,6 +282,8 @@ impl Worker {
 
 impl Drop for Worker {
   fn drop(&mut self) {
+    // The profile has to be collected while the inspector is still alive.
+    self.state.stop_cpu_profiler();
     // The Isolate object must outlive the Inspector object, but this is
     // currently not enforced by the type system.
     self.inspector.take();
@@ -205,7 +301,11 @@ impl Future for Worker {
       let _ = deno_inspector.poll_unpin(cx);
     }
     inner.waker.register(cx.waker());
-    inner.isolate.poll_unpin(cx)
+    let poll_result = inner.isolate.poll_unpin(cx);
+    // Snapshots are taken after the isolate has been polled, when no
+    // JavaScript is running.
+    inner.poll_heap_snapshots(cx);
+    poll_result
   }
 }
 
@@ -237,11 +337,13 @@ impl MainWorker {
     {
       let isolate = &mut worker.isolate;
       ops::runtime::init(isolate, &state);
+      ops::blob::init(isolate, &state);
       ops::runtime_compiler::init(isolate, &state);
       ops::errors::init(isolate, &state);
       ops::fetch::init(isolate, &state);
       ops::fs::init(isolate, &state);
       ops::fs_events::init(isolate, &state);
+      ops::http::init(isolate, &state);
       ops::io::init(isolate, &state);
       ops::plugins::init(isolate, &state);
       ops::net::init(isolate, &state);
@@ -255,6 +357,7 @@ impl MainWorker {
       ops::signal::init(isolate, &state);
       ops::timers::init(isolate, &state);
       ops::tty::init(isolate, &state);
+      ops::websocket::init(isolate, &state);
       ops::worker_host::init(isolate, &state);
     }
     Self(worker)
@@ -301,7 +301,10 @@ pub extern "C" fn host_initialize_import_meta_object_callback(
   let id = module.get_identity_hash();
   assert_ne!(id, 0);
 
-  let info = core_isolate.modules.get_info(id).expect("Module not found");
+  let info = core_isolate
+    .modules
+    .get_info_mut(id)
+    .expect("Module not found");
 
   meta.create_data_property(
     context,
@@ -313,6 +316,13 @@ pub extern "C" fn host_initialize_import_meta_object_callback(
     v8::String::new(scope, "main").unwrap().into(),
     v8::Boolean::new(scope, info.main).into(),
   );
+  if let Some(binary) = info.binary.take().filter(|b| !b.is_empty()) {
+    meta.create_data_property(
+      context,
+      v8::String::new(scope, "binary").unwrap().into(),
+      boxed_slice_to_uint8array(scope, binary).into(),
+    );
+  }
 }
 
 pub extern "C" fn promise_reject_callback(message: v8::PromiseRejectMessage) {
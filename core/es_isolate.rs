@@ -473,6 +473,7 @@ impl EsIsolate {
   ) -> Result<(), ErrBox> {
     let ModuleSource {
       code,
+      binary,
       module_url_specified,
       module_url_found,
     } = info;
@@ -507,7 +508,11 @@ impl EsIsolate {
         id
       }
       // Module not registered yet, do it now.
-      None => self.mod_new(is_main, &module_url_found, &code)?,
+      None => {
+        let id = self.mod_new(is_main, &module_url_found, &code)?;
+        self.modules.get_info_mut(id).unwrap().binary = binary;
+        id
+      }
     };
 
     // Now we must iterate over all imports of the module and load them.
@@ -804,6 +809,7 @@ pub mod tests {
           module_url_specified: specifier.to_string(),
           module_url_found: specifier.to_string(),
           code: "# not valid JS".to_owned(),
+          binary: None,
         };
         async move { Ok(info) }.boxed()
       }
@@ -887,6 +893,7 @@ pub mod tests {
           module_url_specified: specifier.to_string(),
           module_url_found: specifier.to_string(),
           code: "export function b() { return 'b' }".to_owned(),
+          binary: None,
         };
         async move { Ok(info) }.boxed()
       }
@@ -6,6 +6,7 @@ use crate::any_error::ErrBox;
 use crate::es_isolate::ModuleId;
 use crate::es_isolate::ModuleLoadId;
 use crate::module_specifier::ModuleSpecifier;
+use crate::ops::Buf;
 use futures::future::FutureExt;
 use futures::stream::FuturesUnordered;
 use futures::stream::Stream;
@@ -43,6 +44,10 @@ lazy_static! {
 #[derive(Debug, Eq, PartialEq)]
 pub struct ModuleSource {
   pub code: String,
+  /// Binary data the module is generated from, like a WebAssembly binary.
+  /// It is handed to `code` as a `Uint8Array` in `import.meta.binary`
+  /// instead of being embedded in the source.
+  pub binary: Option<Buf>,
   pub module_url_specified: String,
   pub module_url_found: String,
 }
@@ -215,6 +220,7 @@ impl RecursiveModuleLoad {
       LoadState::ResolveMain(_, Some(code)) => {
         futures::future::ok(ModuleSource {
           code: code.to_owned(),
+          binary: None,
           module_url_specified: module_specifier.to_string(),
           module_url_found: module_specifier.to_string(),
         })
@@ -280,6 +286,8 @@ pub struct ModuleInfo {
   pub name: String,
   pub handle: v8::Global<v8::Module>,
   pub import_specifiers: Vec<ModuleSpecifier>,
+  /// Taken when `import.meta` is initialized.
+  pub binary: Option<Buf>,
 }
 
 /// A symbolic module entity.
@@ -396,6 +404,7 @@ impl Modules {
         name,
         import_specifiers,
         handle,
+        binary: None,
       },
     );
   }
@@ -415,6 +424,13 @@ impl Modules {
     self.info.get(&id)
   }
 
+  pub fn get_info_mut(&mut self, id: ModuleId) -> Option<&mut ModuleInfo> {
+    if id == 0 {
+      return None;
+    }
+    self.info.get_mut(&id)
+  }
+
   pub fn deps(&self, module_specifier: &ModuleSpecifier) -> Option<Deps> {
     Deps::new(self, module_specifier)
   }
@@ -636,6 +652,7 @@ mod tests {
       match mock_source_code(&inner.url) {
         Some(src) => Poll::Ready(Ok(ModuleSource {
           code: src.0.to_owned(),
+          binary: None,
           module_url_specified: inner.url.clone(),
           module_url_found: src.1.to_owned(),
         })),
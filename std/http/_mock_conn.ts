@@ -13,6 +13,8 @@ export function mockConn(base: Partial<Deno.Conn> = {}): Deno.Conn {
     },
     rid: -1,
     closeWrite: (): void => {},
+    setNoDelay: (): void => {},
+    setKeepAlive: (): void => {},
     read: (): Promise<number | null> => {
       return Promise.resolve(0);
     },
@@ -283,6 +283,8 @@ function dummyConn(r: Reader, w: Writer): Conn {
   return {
     rid: -1,
     closeWrite: (): void => {},
+    setNoDelay: (): void => {},
+    setKeepAlive: (): void => {},
     read: (x): Promise<number | null> => r.read(x),
     write: (x): Promise<number> => w.write(x),
     close: (): void => {},
@@ -113,6 +113,36 @@ class ContentTypeHandler(QuietSimpleHTTPRequestHandler):
                 self.wfile.write(bytes("console.log('etag')"))
             return
 
+        if "last_modified_script.ts" in self.path:
+            self.protocol_version = 'HTTP/1.1'
+            last_modified = "Wed, 21 Oct 2015 07:28:00 GMT"
+            if_modified_since = self.headers.getheader('if-modified-since')
+            if if_modified_since == last_modified:
+                self.send_response(304, 'Not Modified')
+                self.send_header('Last-Modified', last_modified)
+                self.end_headers()
+            else:
+                self.send_response(200, 'OK')
+                self.send_header('Content-type', 'application/typescript')
+                self.send_header('Last-Modified', last_modified)
+                self.end_headers()
+                self.wfile.write(bytes("console.log('modified')"))
+            return
+
+        if "auth_script.ts" in self.path:
+            self.protocol_version = 'HTTP/1.1'
+            authorization = self.headers.getheader('authorization')
+            if authorization == "Bearer abcdef123456789":
+                self.send_response(200, 'OK')
+                self.send_header('Content-type', 'application/typescript')
+                self.end_headers()
+                self.wfile.write(bytes("console.log('auth')"))
+            else:
+                self.send_response(404, 'Not Found')
+                self.send_header('Content-Length', '0')
+                self.end_headers()
+            return
+
         if "xTypeScriptTypes.js" in self.path:
             self.protocol_version = "HTTP/1.1"
             self.send_response(200, 'OK')
@@ -166,6 +196,13 @@ class ContentTypeHandler(QuietSimpleHTTPRequestHandler):
     def do_POST(self):
         # Simple echo server for request reflection
         if "echo_server" in self.path:
+            # Read the whole body first, so the response can't complete
+            # before the request body has been sent.
+            if self.headers.getheader('transfer-encoding') == 'chunked':
+                data_string = self.read_chunked_body()
+            else:
+                data_string = self.rfile.read(
+                    int(self.headers['Content-Length']))
             self.protocol_version = 'HTTP/1.1'
             self.send_response(200, 'OK')
             if self.headers.has_key('content-type'):
@@ -175,7 +212,6 @@ class ContentTypeHandler(QuietSimpleHTTPRequestHandler):
                 self.send_header('user-agent',
                                  self.headers.getheader('user-agent'))
             self.end_headers()
-            data_string = self.rfile.read(int(self.headers['Content-Length']))
             self.wfile.write(bytes(data_string))
             return
         self.protocol_version = 'HTTP/1.1'
@@ -184,6 +220,17 @@ class ContentTypeHandler(QuietSimpleHTTPRequestHandler):
         self.end_headers()
         self.wfile.write(bytes('Server does not support this operation'))
 
+    def read_chunked_body(self):
+        data = ''
+        while True:
+            size = int(self.rfile.readline().strip(), 16)
+            if size == 0:
+                # Skip the final CRLF.
+                self.rfile.readline()
+                return data
+            data += self.rfile.read(size)
+            self.rfile.readline()
+
     def guess_type(self, path):
         if ".t1." in path:
             return "text/typescript"